@@ -0,0 +1,20 @@
+use clytia::{Clytia, ConfirmAll};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cli = Clytia::default();
+
+    let mut apply_all = false;
+    for file in ["notes.txt", "todo.txt", "draft.txt", "old.txt"] {
+        if !apply_all {
+            match cli.confirm_all(format!("Delete {}?", file))? {
+                ConfirmAll::Yes => {}
+                ConfirmAll::No => continue,
+                ConfirmAll::YesToAll => apply_all = true,
+                ConfirmAll::NoToAll => break,
+            }
+        }
+        println!("Deleted {}", file);
+    }
+
+    Ok(())
+}
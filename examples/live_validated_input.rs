@@ -0,0 +1,11 @@
+use clytia::Clytia;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cli = Clytia::default();
+
+    let port: u16 =
+        cli.live_validated_input("Pick a port above 1023", |port: &u16| *port > 1023)?;
+    println!("Listening on port {}", port);
+
+    Ok(())
+}
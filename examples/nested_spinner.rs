@@ -0,0 +1,23 @@
+use std::time::Duration;
+
+use clytia::Clytia;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cli = Clytia::default();
+
+    cli.nested_spinner("Installing", |subtasks| -> Result<(), &str> {
+        subtasks.run("Downloading", |_| {
+            std::thread::sleep(Duration::from_secs(2));
+            Ok(())
+        })?;
+        subtasks.run("Unpacking", |subtasks| {
+            std::thread::sleep(Duration::from_secs(1));
+            subtasks.run("Checking signatures", |_| {
+                std::thread::sleep(Duration::from_secs(2));
+                Ok(())
+            })
+        })
+    })??;
+
+    Ok(())
+}
@@ -13,7 +13,7 @@ fn main() -> Result<()> {
             println!("You didn't enter a number!");
             Ok(())
         }
-        Err(Error::ParseError(_)) => {
+        Err(Error::ParseError { .. }) => {
             println!("You didn't enter a number!");
             Ok(())
         }
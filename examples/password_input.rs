@@ -0,0 +1,23 @@
+use clytia::{Clytia, Error, Result};
+
+fn main() -> Result<()> {
+    let mut clytia = Clytia::default();
+
+    match clytia.password_input("Please enter a password") {
+        Ok(password) if password.chars().count() < 8 => {
+            println!("That password is too short!");
+            Ok(())
+        }
+        Ok(_) => {
+            println!("Password accepted!");
+            Ok(())
+        }
+        Err(Error::NonOptionalInput) => {
+            println!("You didn't enter a password!");
+            Ok(())
+        }
+        Err(e) => Err(e),
+    }?;
+
+    Ok(())
+}
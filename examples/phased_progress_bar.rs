@@ -0,0 +1,27 @@
+use std::{sync::Mutex, time::Duration};
+
+use clytia::{Clytia, Result};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cli = Clytia::default();
+    cli.with_progress_summary(true);
+
+    let phases = [("downloading", 5.0), ("unpacking", 2.0), ("linking", 1.0)];
+    let progress = Mutex::new((0, 0.0));
+    cli.phased_progress_bar(
+        "Installing",
+        &phases,
+        || *progress.lock().unwrap(),
+        || -> Result<(), &str> {
+            for phase in 0..phases.len() {
+                for step in 1..=50 {
+                    std::thread::sleep(Duration::from_millis(20));
+                    *progress.lock().unwrap() = (phase, step as f64 / 50.0);
+                }
+            }
+            Ok(())
+        },
+    )??;
+
+    Ok(())
+}
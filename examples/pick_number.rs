@@ -0,0 +1,10 @@
+use clytia::Clytia;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cli = Clytia::default();
+
+    let port = cli.pick_number("Which port should the server use?", 1024..=65535, 8080)?;
+    println!("Listening on port {}", port);
+
+    Ok(())
+}
@@ -0,0 +1,24 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use clytia::{Clytia, Result};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cli = Clytia::default();
+    cli.with_progress_summary(true);
+
+    let counter = AtomicUsize::new(0);
+    cli.progress_bar_counted(
+        "Processing 500 items",
+        500,
+        || counter.load(Ordering::Relaxed),
+        || -> Result<(), &str> {
+            while counter.load(Ordering::Relaxed) < 500 {
+                std::thread::sleep(std::time::Duration::from_millis(10));
+                counter.fetch_add(1, Ordering::Relaxed);
+            }
+            Ok(())
+        },
+    )??;
+
+    Ok(())
+}
@@ -0,0 +1,25 @@
+use std::fs::File;
+
+use clytia::{Clytia, Recorder, Replay};
+
+// Run with `record` to log a session to session.log, then with `replay` to play it back.
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let input: Box<dyn std::io::Read> = match std::env::args().nth(1).as_deref() {
+        Some("replay") => {
+            let mut replay = Replay::new(File::open("session.log")?)?;
+            replay.with_timing(true);
+            Box::new(replay)
+        }
+        _ => Box::new(Recorder::new(
+            std::io::stdin(),
+            File::create("session.log")?,
+        )),
+    };
+    let mut cli = Clytia::new(input, std::io::stdout());
+
+    let name: String = cli.parsed_input("What's your name?", None)?;
+    let pet = cli.options_menu(vec!["cats", "dogs", "both"])?;
+    println!("Hello {}, you like {}", name, pet);
+
+    Ok(())
+}
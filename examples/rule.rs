@@ -0,0 +1,15 @@
+use clytia::Clytia;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cli = Clytia::default();
+
+    cli.rule(Some("Config"))?;
+    println!("port = 8080");
+    println!("host = localhost");
+    cli.rule(None)?;
+
+    cli.with_ascii_rules(true);
+    cli.rule(Some("ASCII"))?;
+
+    Ok(())
+}
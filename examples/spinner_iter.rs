@@ -0,0 +1,15 @@
+use std::time::Duration;
+
+use clytia::Clytia;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cli = Clytia::default();
+
+    let lines = std::iter::repeat("line").take(100).inspect(|_| {
+        std::thread::sleep(Duration::from_millis(30));
+    });
+    let count = cli.spinner_iter("Reading lines", lines).count();
+    println!("Read {} lines", count);
+
+    Ok(())
+}
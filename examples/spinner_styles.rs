@@ -0,0 +1,17 @@
+use std::time::Duration;
+
+use clytia::{Clytia, SpinnerStyle};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cli = Clytia::default();
+
+    for style in SpinnerStyle::ALL {
+        for frame in style.frames().iter().cycle().take(30) {
+            cli.status_line(format!("{} {}", frame, style.name()))?;
+            std::thread::sleep(Duration::from_millis(80));
+        }
+    }
+    cli.clear_status_line()?;
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use std::process::Command;
+
+use clytia::Clytia;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cli = Clytia::default();
+
+    let mut child = Command::new("sleep").arg("3").spawn()?;
+    let status = cli.spinner_wait("Running `sleep 3`", &mut child)?;
+    println!("Exited with {}", status);
+
+    Ok(())
+}
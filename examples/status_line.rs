@@ -0,0 +1,14 @@
+use clytia::{Clytia, Result};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cli = Clytia::default();
+
+    for remaining in (1..=10).rev() {
+        cli.status_line(format!("Launching in {}...", remaining))?;
+        std::thread::sleep(std::time::Duration::from_secs(1));
+    }
+    cli.clear_status_line()?;
+    println!("Lift off!");
+
+    Ok(())
+}
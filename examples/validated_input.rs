@@ -10,7 +10,7 @@ fn main() -> Result<()> {
             println!("Double your number is {}", number * 2);
             Ok(())
         }
-        Err(Error::ParseError(_)) => {
+        Err(Error::ParseError { .. }) => {
             println!("You didn't enter a number!");
             Ok(())
         }
@@ -5,29 +5,63 @@
 //! or allow you to provide feedback to the user while some process is ongoing.
 //!
 //! Look at the [`Clytia`] struct for more information.
+//!
+//! # Features
+//! - `anyhow`: Adds [`Clytia::try_spinner`], for tasks returning an [`anyhow::Result`].
+//! - `serde`: Adds [`Clytia::prompt_struct`], filling in a [`serde::Deserialize`] type field
+//!   by field.
 
 #![deny(missing_docs)]
 #![cfg_attr(feature = "nightly", feature(scoped_threads))]
 
 use std::{
     collections::HashSet,
+    env::VarError,
+    ffi::OsStr,
     io::{self, Read, Stdin, Stdout, Write},
+    ops::RangeInclusive,
+    os::unix::io::{AsRawFd, RawFd},
     str::FromStr,
-    sync::atomic::AtomicBool,
-    time::Duration,
+    sync::{atomic::AtomicUsize, Arc, Mutex},
+    time::{Duration, Instant},
 };
 
-use crossbeam::thread::scope;
+use crossbeam::{
+    channel::{bounded, Receiver, RecvTimeoutError},
+    thread::scope,
+};
 use owo_colors::OwoColorize;
-use termion::{event::Key, input::TermRead, raw::IntoRawMode};
+use termion::{
+    input::TermRead,
+    raw::{IntoRawMode, RawTerminal},
+};
+
+/// A key press, as passed to [`Clytia::with_key_hook`].
+pub use termion::event::Key;
+
+/// A color and text style, e.g. as passed to [`Clytia::with_percent_style`].
+pub use owo_colors::{AnsiColors, Style};
 
 /// A alias for [`std::result::Result`] where the default error is [`Error`]
 pub type Result<T, E = Error> = core::result::Result<T, E>;
 
-static SPINNER_SYMBOLS: [char; 8] = ['⠹', '⢸', '⣰', '⣤', '⣆', '⡇', '⠏', '⠛'];
+const SPINNER_SYMBOLS: [char; 8] = ['⠹', '⢸', '⣰', '⣤', '⣆', '⡇', '⠏', '⠛'];
+
+/// Escape codes to turn bracketed paste mode on and off.
+static BRACKETED_PASTE_ON: &str = "\x1b[?2004h";
+static BRACKETED_PASTE_OFF: &str = "\x1b[?2004l";
+/// Markers the terminal wraps pasted content in while bracketed paste is on.
+static PASTE_START: &[u8] = b"\x1b[200~";
+static PASTE_END: &[u8] = b"\x1b[201~";
+/// Escape code resetting the scroll region to the whole screen.
+static SCROLL_REGION_RESET: &str = "\x1b[r";
 
 /// Clytia's Error type
+///
+/// More variants may be added, some only with certain features turned on, so matches on it
+/// need a wildcard arm.
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum Error {
     /// Represents an error where the user picked nothing, but input was required.
     /// See: [`parsed_input`]
@@ -37,8 +71,330 @@ pub enum Error {
     #[error("IO Error: {0}")]
     Io(#[from] io::Error),
     /// Represent a error where a given input could not be parsed correctly
-    #[error("Could not parse: {0}")]
-    ParseError(String),
+    #[error("Could not parse {input:?} as {expected}")]
+    ParseError {
+        /// The input that couldn't be parsed.
+        input: String,
+        /// The name of the type the input was being parsed as, see [`std::any::type_name`].
+        expected: &'static str,
+    },
+    /// Represents an error where a menu was given no options to pick from.
+    /// See: [`Clytia::options_menu`] and [`Clytia::multichoice`]
+    #[error("no options given to pick from")]
+    EmptyOptions,
+    /// Represents an error where the input could be parsed, but didn't pass validation.
+    /// See: [`validate_str`]
+    #[error("{0:?} is not valid")]
+    InvalidInput(String),
+    /// Represents an error where [`Clytia::password_input`], which needs control of the
+    /// terminal to hide the secret, was called while the process is running in the background.
+    #[error("not running in the terminal's foreground")]
+    Background,
+    /// Represents an error where a channel was disconnected before anything was received.
+    /// See: [`Clytia::spinner_recv`]
+    #[error("the channel was disconnected")]
+    Disconnected,
+    /// Represents an error where a menu's limits on how many options to select can't be met.
+    /// See: [`Clytia::multichoice_limited`]
+    #[error("the selection limits can't be met with the options given")]
+    UnmetLimits,
+    /// Represents an error where a spinner was given no frames to animate.
+    /// See: [`Clytia::with_spinner_frames`]
+    #[error("no frames given for the spinner")]
+    EmptyFrames,
+    /// Represents an error where the user backed out of a menu with escape or Ctrl-C.
+    /// See: [`Clytia::options_menu`] and [`Clytia::multichoice`]
+    #[error("the menu was cancelled")]
+    Cancelled,
+    /// Represents an error returned by the task given to [`Clytia::try_spinner`].
+    #[cfg(feature = "anyhow")]
+    #[error(transparent)]
+    Task(#[from] anyhow::Error),
+}
+
+/// A named preset of frames for a spinner animation.
+///
+/// # Usage
+/// ```rust
+/// use clytia::SpinnerStyle;
+///
+/// let moon = SpinnerStyle::by_name("moon").unwrap();
+/// assert_eq!(moon.frames()[0], '🌑');
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpinnerStyle {
+    name: &'static str,
+    frames: &'static [char],
+}
+
+impl SpinnerStyle {
+    /// Rotating braille dots, `⠋⠙⠹⠸`...
+    pub const DOTS: Self = Self::new("dots", &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏']);
+    /// A spinning line, `-\|/`.
+    pub const LINE: Self = Self::new("line", &['-', '\\', '|', '/']);
+    /// The braille used by Clytia's spinners, `⠹⢸⣰⣤`...
+    pub const BRAILLE: Self = Self::new("braille", &SPINNER_SYMBOLS);
+    /// An arrow going round, `←↖↑↗`...
+    pub const ARROWS: Self = Self::new("arrows", &['←', '↖', '↑', '↗', '→', '↘', '↓', '↙']);
+    /// A dot bouncing up and down, `⠁⠂⠄⠂`.
+    pub const BOUNCE: Self = Self::new("bounce", &['⠁', '⠂', '⠄', '⠂']);
+    /// Clock faces for every hour, `🕛🕐🕑`...
+    pub const CLOCK: Self = Self::new(
+        "clock",
+        &[
+            '🕛', '🕐', '🕑', '🕒', '🕓', '🕔', '🕕', '🕖', '🕗', '🕘', '🕙', '🕚',
+        ],
+    );
+    /// The phases of the moon, `🌑🌒🌓🌔`...
+    pub const MOON: Self = Self::new("moon", &['🌑', '🌒', '🌓', '🌔', '🌕', '🌖', '🌗', '🌘']);
+
+    /// Every preset.
+    pub const ALL: [Self; 7] = [
+        Self::DOTS,
+        Self::LINE,
+        Self::BRAILLE,
+        Self::ARROWS,
+        Self::BOUNCE,
+        Self::CLOCK,
+        Self::MOON,
+    ];
+
+    const fn new(name: &'static str, frames: &'static [char]) -> Self {
+        Self { name, frames }
+    }
+
+    /// Look up a preset by its name, ignoring case, e.g. `"moon"`. See [`SpinnerStyle::ALL`]
+    /// for every preset.
+    pub fn by_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|style| style.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The name of the preset.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The frames of the animation, shown one after another.
+    pub fn frames(&self) -> &'static [char] {
+        self.frames
+    }
+}
+
+/// An answer to [`Clytia::confirm_all`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmAll {
+    /// Apply to this item (`y`).
+    Yes,
+    /// Skip this item (`n`).
+    No,
+    /// Apply to this and every remaining item (`a`).
+    YesToAll,
+    /// Skip this and every remaining item (`q`).
+    NoToAll,
+}
+
+/// A node of the tree picked from by [`Clytia::tree_select`], e.g. a directory or a file in it.
+/// Only leaves can be picked, branches are there to be expanded.
+///
+/// ```rust
+/// use clytia::TreeNode;
+///
+/// let tree = TreeNode::branch("src", vec![
+///     TreeNode::leaf("lib.rs", 1),
+///     TreeNode::branch("bin", vec![TreeNode::leaf("main.rs", 2)]),
+/// ]);
+/// assert_eq!(tree.label(), "src");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeNode<T> {
+    /// A node that can be picked, returning its `value`.
+    Leaf {
+        /// What's shown for the node.
+        label: String,
+        /// What [`Clytia::tree_select`] returns if the node is picked.
+        value: T,
+    },
+    /// A node holding other nodes.
+    Branch {
+        /// What's shown for the node.
+        label: String,
+        /// The nodes shown under this one while it's expanded.
+        children: Vec<TreeNode<T>>,
+    },
+}
+
+impl<T> TreeNode<T> {
+    /// A node that can be picked, see [`TreeNode::Leaf`].
+    pub fn leaf<S: Into<String>>(label: S, value: T) -> Self {
+        Self::Leaf {
+            label: label.into(),
+            value,
+        }
+    }
+
+    /// A node holding `children`, see [`TreeNode::Branch`].
+    pub fn branch<S: Into<String>>(label: S, children: Vec<TreeNode<T>>) -> Self {
+        Self::Branch {
+            label: label.into(),
+            children,
+        }
+    }
+
+    /// What's shown for the node.
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Leaf { label, .. } | Self::Branch { label, .. } => label,
+        }
+    }
+
+    fn children(&self) -> &[TreeNode<T>] {
+        match self {
+            Self::Leaf { .. } => &[],
+            Self::Branch { children, .. } => children,
+        }
+    }
+
+    /// Whether there's a leaf at or under this node, i.e. anything to pick.
+    fn has_leaf(&self) -> bool {
+        match self {
+            Self::Leaf { .. } => true,
+            Self::Branch { children, .. } => children.iter().any(TreeNode::has_leaf),
+        }
+    }
+
+    /// The node at `path`, the index of the child to take at each level.
+    fn get(&self, path: &[usize]) -> &TreeNode<T> {
+        path.iter()
+            .fold(self, |node, &index| &node.children()[index])
+    }
+
+    /// The value of the leaf at `path`, if there is one.
+    fn into_value(self, path: &[usize]) -> Option<T> {
+        match (self, path.split_first()) {
+            (Self::Leaf { value, .. }, None) => Some(value),
+            (Self::Branch { children, .. }, Some((&index, rest))) => {
+                children.into_iter().nth(index)?.into_value(rest)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// How a prompt looks, for inputs that can be styled per call, such as
+/// [`Clytia::parsed_input_styled`]. The default is the usual blue prompt and `=>`.
+///
+/// ```rust
+/// use clytia::{AnsiColors, PromptStyle};
+///
+/// let warning = PromptStyle {
+///     indicator: "!>",
+///     indicator_color: AnsiColors::Red,
+///     ..PromptStyle::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PromptStyle {
+    /// The colour of the prompt text.
+    pub prompt_color: AnsiColors,
+    /// What's shown between the prompt and the input.
+    pub indicator: &'static str,
+    /// The colour of the indicator.
+    pub indicator_color: AnsiColors,
+}
+
+impl Default for PromptStyle {
+    fn default() -> Self {
+        Self {
+            prompt_color: AnsiColors::Blue,
+            indicator: "=>",
+            indicator_color: AnsiColors::Blue,
+        }
+    }
+}
+
+/// The colours Clytia draws with, see [`Clytia::with_theme`]. The default is the usual look,
+/// blue prompts with magenta hints.
+///
+/// ```rust
+/// use clytia::{AnsiColors, Theme};
+///
+/// let muted = Theme {
+///     prompt: AnsiColors::Cyan,
+///     highlight: AnsiColors::Cyan,
+///     ..Theme::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// The colour of prompts, their `=>`, spinner frames and progress bars.
+    pub prompt: AnsiColors,
+    /// The colour of hints next to a prompt, such as its default value.
+    pub hint: AnsiColors,
+    /// The colour of accepted answers and finished tasks.
+    pub success: AnsiColors,
+    /// The colour of rejected answers and failed tasks.
+    pub error: AnsiColors,
+    /// The colour of the highlighted option in a menu.
+    pub highlight: AnsiColors,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            prompt: AnsiColors::Blue,
+            hint: AnsiColors::Magenta,
+            success: AnsiColors::Green,
+            error: AnsiColors::Red,
+            highlight: AnsiColors::Blue,
+        }
+    }
+}
+
+impl Theme {
+    /// The [`PromptStyle`] of prompts that aren't styled per call.
+    fn prompt_style(&self) -> PromptStyle {
+        PromptStyle {
+            prompt_color: self.prompt,
+            indicator_color: self.prompt,
+            ..PromptStyle::default()
+        }
+    }
+}
+
+/// What to do with a prompt longer than [`Clytia::with_label_width`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LabelOverflow {
+    /// Show the whole prompt, pushing the `=>` further along.
+    Overflow,
+    /// Cut the prompt short, ending it with `…`.
+    Truncate,
+}
+
+/// What [`Clytia::parsed_input_or_default`] does when nothing is typed and there's no
+/// default, see [`Clytia::with_empty_input`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmptyInput {
+    /// Return [`Error::NonOptionalInput`], like [`Clytia::parsed_input`].
+    Error,
+    /// Return the type's [`Default`] value.
+    Default,
+}
+
+/// The type of a field asked for by [`Clytia::prompt_struct`], deciding how its input is
+/// parsed.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    /// Any text, taken as typed without the surrounding whitespace.
+    String,
+    /// A whole number.
+    Integer,
+    /// Any number.
+    Float,
+    /// A yes or no answer, see [`Clytia::bool_input`].
+    Bool,
 }
 
 /// Holder for an input an output, useful if you need custom buffer to read and write to.
@@ -48,14 +404,37 @@ pub enum Error {
 ///
 /// let cli = Clytia::default();
 /// ```
+///
+/// # Buffering
+/// Output is flushed whenever something needs to be on screen straight away, like each frame
+/// of a spinner or a prompt waiting for input, so a buffered output such as
+/// [`std::io::BufWriter`] works as is. If frames still come through late, e.g. an output that
+/// only writes out complete lines until flushed, [`Clytia::with_immediate_flush`] flushes it
+/// after every write.
 #[derive(Debug)]
 pub struct Clytia<I: Read, O: Write> {
     input: I,
-    output: O,
+    output: Output<O>,
+    settings: Settings,
+    /// Whether the bottom row is kept for a status bar, see [`Clytia::set_status_bar`].
+    status_bar: bool,
+}
+
+impl Error {
+    /// An [`Error::ParseError`] for an `input` that couldn't be parsed as a `T`.
+    fn parse_error<T>(input: &str) -> Self {
+        Self::ParseError {
+            input: input.to_string(),
+            expected: std::any::type_name::<T>(),
+        }
+    }
 }
 
 impl<I: Read, O: Write> Drop for Clytia<I, O> {
     fn drop(&mut self) {
+        if self.status_bar {
+            write!(self.output, "{}", SCROLL_REGION_RESET).unwrap();
+        }
         write!(self.output, "\r{}", termion::cursor::Show).unwrap();
     }
 }
@@ -63,7 +442,414 @@ impl<I: Read, O: Write> Drop for Clytia<I, O> {
 impl<I: Read, O: Write> Clytia<I, O> {
     /// Create a new [`Clytia`] for a given input and output stream.
     pub fn new(input: I, output: O) -> Self {
-        Self { input, output }
+        Self {
+            input,
+            output: Output::new(output),
+            settings: Settings::default(),
+            status_bar: false,
+        }
+    }
+
+    /// Set whether a newline is written after the completion line of a spinner or progress bar.
+    /// Defaults to `true`, turn it off if you're going to redraw over the completion line straight away.
+    pub fn with_finish_newline(&mut self, finish_newline: bool) -> &mut Self {
+        self.settings.finish_newline = finish_newline;
+        self
+    }
+
+    /// Set whether [`Clytia::progress_bar`] and [`Clytia::progress_bar_counted`] print a summary
+    /// line once the task is done, e.g. `Completed 1000 items in 12s (81/s)`. Defaults to `false`.
+    pub fn with_progress_summary(&mut self, progress_summary: bool) -> &mut Self {
+        self.settings.progress_summary = progress_summary;
+        self
+    }
+
+    /// Set whether [`Clytia::options_menu`] writes the picked option at the end of the line the
+    /// cursor was on, e.g. after a prompt printed with `print!("What animal?")`, rather than
+    /// on a line of its own. Defaults to `false`.
+    pub fn with_inline_selection(&mut self, inline: bool) -> &mut Self {
+        self.settings.inline_selection = inline;
+        self
+    }
+
+    /// Set whether [`Clytia::options_menu`] and [`Clytia::multichoice`] show the position of
+    /// the highlighted option above the menu, e.g. `(3/150)`. Defaults to `false`.
+    pub fn with_menu_position(&mut self, menu_position: bool) -> &mut Self {
+        self.settings.menu_position = menu_position;
+        self
+    }
+
+    /// Set a debounce for key presses in [`Clytia::options_menu`] and [`Clytia::multichoice`].
+    ///
+    /// When set, the menus wait at least this long between redraws and then apply every key
+    /// that arrived in the meantime at once, so holding down an arrow key scrolls smoothly
+    /// instead of redrawing (and lagging behind) for every repeat. Defaults to [`None`],
+    /// redrawing after each key.
+    pub fn with_menu_debounce(&mut self, debounce: Option<Duration>) -> &mut Self {
+        self.settings.menu_debounce = debounce;
+        self
+    }
+
+    /// Set whether menus and progress bars hide the cursor while they're drawn. Turning this
+    /// off keeps the cursor visible at all times, which some screen readers rely on.
+    /// Defaults to `true`.
+    pub fn with_hide_cursor(&mut self, hide: bool) -> &mut Self {
+        self.settings.hide_cursor = hide;
+        self
+    }
+
+    /// Set the frames spinners animate through, in place of the default braille, e.g. for
+    /// terminal fonts without braille. See [`SpinnerStyle`] for some presets.
+    ///
+    /// Returns [`Error::EmptyFrames`] if `frames` is empty, leaving the frames as they were.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::{Clytia, SpinnerStyle};
+    ///
+    /// let mut cli = Clytia::default();
+    /// cli.with_spinner_frames(SpinnerStyle::LINE.frames().to_vec())?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn with_spinner_frames(&mut self, frames: Vec<char>) -> Result<&mut Self> {
+        if frames.is_empty() {
+            return Err(Error::EmptyFrames);
+        }
+        self.settings.spinner_frames = frames;
+        Ok(self)
+    }
+
+    /// Set the colours used by the inputs, menus, spinners and progress bars. See [`Theme`] for
+    /// what each colour is used for. [`Clytia::with_percent_style`] overrides the colour of the
+    /// progress bars' percentage.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::{AnsiColors, Clytia, Theme};
+    ///
+    /// let mut cli = Clytia::default();
+    /// cli.with_theme(Theme {
+    ///     prompt: AnsiColors::Cyan,
+    ///     ..Theme::default()
+    /// });
+    /// ```
+    pub fn with_theme(&mut self, theme: Theme) -> &mut Self {
+        self.settings.theme = theme;
+        self
+    }
+
+    /// Set whether to draw everything for screen readers, which handle lines that keep updating
+    /// poorly. Defaults to `false`.
+    ///
+    /// When set, nothing moves the cursor or redraws a line:
+    /// - Spinners show their text once, then `Still working... 5s` on a new line every few
+    ///   seconds.
+    /// - Progress bars show their prompt, then milestones like `25% complete` on new lines.
+    /// - [`Clytia::options_menu`], [`Clytia::options_menu_two_column`] and
+    ///   [`Clytia::multichoice`] list the options numbered and ask for the number of the one
+    ///   picked, or a comma separated list of them for [`Clytia::multichoice`]. So do the other
+    ///   menus, e.g. [`Clytia::multichoice_limited`] and [`Clytia::quantity_select`], which
+    ///   then asks for the quantity of each item picked.
+    /// - Inputs that redraw as the user types, e.g. [`Clytia::pick_number`],
+    ///   [`Clytia::confirm_all`] and [`Clytia::choice_input`], read a line instead, asking again
+    ///   with the reason on a new line if it isn't accepted. [`Clytia::suggest_input`] doesn't
+    ///   list suggestions, and [`Clytia::confirm_diff`] prints the whole diff before asking.
+    /// - [`Clytia::status_line`] and [`Clytia::set_status_bar`] print each update on a new
+    ///   line, and [`Clytia::toast`] prints its message and leaves it.
+    ///
+    /// [`Clytia::password_input`] still masks the secret as it's typed.
+    ///
+    /// The same is done while the process is running in the terminal's background, e.g. after
+    /// being started with `&`, where taking control of the terminal would stop it.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    /// cli.with_accessible(std::env::var_os("SCREEN_READER").is_some());
+    /// ```
+    pub fn with_accessible(&mut self, accessible: bool) -> &mut Self {
+        self.settings.accessible = accessible;
+        self
+    }
+
+    /// Set the file descriptor the output is written to, so methods like [`Clytia::toast`] can
+    /// tell whether it's a terminal. [`Clytia::default`] sets it to stdout's, otherwise it
+    /// isn't known and the output is taken to be a terminal.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use std::{io, os::unix::io::AsRawFd};
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::new(io::stdin(), io::stderr());
+    /// cli.with_output_fd(Some(io::stderr().as_raw_fd()));
+    /// ```
+    pub fn with_output_fd(&mut self, fd: Option<RawFd>) -> &mut Self {
+        self.settings.output_fd = fd;
+        self
+    }
+
+    /// Set whether [`Clytia::masked_pattern_input`] returns only the digits typed, e.g.
+    /// `5551234567`, rather than the pattern filled in with them. Defaults to `false`.
+    pub fn with_pattern_digits(&mut self, digits_only: bool) -> &mut Self {
+        self.settings.pattern_digits = digits_only;
+        self
+    }
+
+    /// Set how long spinners show each frame for, and how often progress bars are redrawn. A
+    /// longer interval gives a calmer animation. Kept between 10ms and 1s, defaults to 50ms.
+    /// See also [`Clytia::with_fps`].
+    ///
+    /// # Usage
+    /// ```rust
+    /// use std::time::Duration;
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    /// cli.with_spinner_interval(Duration::from_millis(120));
+    /// ```
+    pub fn with_spinner_interval(&mut self, interval: Duration) -> &mut Self {
+        self.settings.frame_interval =
+            interval.clamp(Duration::from_millis(10), Duration::from_secs(1));
+        self
+    }
+
+    /// Like [`Clytia::with_spinner_interval`], but as a number of frames per second, kept between
+    /// 1 and 100. Defaults to 20.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    /// cli.with_fps(10);
+    /// ```
+    pub fn with_fps(&mut self, fps: u32) -> &mut Self {
+        let fps = fps.clamp(1, 100);
+        self.with_spinner_interval(Duration::from_millis(1000 / u64::from(fps)))
+    }
+
+    /// Set whether [`Clytia::multichoice`] shows its result on a single line, e.g.
+    /// `Selected: cats, dogs`, instead of one `[X]` line per selected option.
+    /// Defaults to `false`.
+    pub fn with_compact_multichoice(&mut self, compact: bool) -> &mut Self {
+        self.settings.compact_multichoice = compact;
+        self
+    }
+
+    /// Set the markers [`Clytia::multichoice`] shows for selected and unselected options.
+    /// The shorter one is padded with spaces so the options stay lined up.
+    /// Defaults to `[X]` and `[ ]`.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    /// cli.with_checkboxes("●", "○");
+    /// ```
+    pub fn with_checkboxes(&mut self, checked: &str, unchecked: &str) -> &mut Self {
+        let width = checked.chars().count().max(unchecked.chars().count());
+        self.settings.checkboxes = (
+            format!("{:<width$}", checked, width = width),
+            format!("{:<width$}", unchecked, width = width),
+        );
+        self
+    }
+
+    /// Set whether [`Clytia::validated_input`] passes the line to parsing and validation exactly
+    /// as typed, instead of trimming it and asking again when it's empty. This leaves it to the
+    /// validation function to decide if whitespace is acceptable. Defaults to `false`.
+    pub fn with_untrimmed_validation(&mut self, untrimmed: bool) -> &mut Self {
+        self.settings.untrimmed_validation = untrimmed;
+        self
+    }
+
+    /// Set how many decimal places progress bars show in their percentage, e.g. `042.5%` with
+    /// a precision of `1`. The bar is shortened to make room. Defaults to `0`.
+    pub fn with_percent_precision(&mut self, precision: usize) -> &mut Self {
+        self.settings.percent_precision = precision;
+        self
+    }
+
+    /// Set the most columns a progress bar takes up, including its percentage, so it doesn't
+    /// stretch across the whole of a wide terminal. Defaults to [`None`], using the full width.
+    pub fn with_max_bar_width(&mut self, max_bar_width: Option<usize>) -> &mut Self {
+        self.settings.max_bar_width = max_bar_width;
+        self
+    }
+
+    /// Set how long a progress bar takes to fill up to 100% once its task succeeds, for tasks
+    /// that finish before the bar does. Defaults to [`None`], finishing straight away.
+    pub fn with_progress_fill(&mut self, fill: Option<Duration>) -> &mut Self {
+        self.settings.progress_fill = fill;
+        self
+    }
+
+    /// Set the most options [`Clytia::multichoice`] and [`Clytia::options_menu`] show at once.
+    /// Longer lists scroll as the highlight moves, with a line above and below saying how many
+    /// options are out of view. Defaults to [`None`], showing every option, though
+    /// [`Clytia::options_menu`] always scrolls rather than run past the bottom of the terminal.
+    pub fn with_menu_height(&mut self, height: Option<usize>) -> &mut Self {
+        self.settings.menu_height = height;
+        self
+    }
+
+    /// Set whether the highlight in menus wraps around, moving from the last option to the first
+    /// and back. Defaults to `true`. [`Clytia::options_menu_with_wrap`] and
+    /// [`Clytia::multichoice_with_wrap`] choose for a single menu instead.
+    pub fn with_menu_wrap(&mut self, wrap: bool) -> &mut Self {
+        self.settings.menu_wrap = wrap;
+        self
+    }
+
+    /// Pad the prompts of inputs to at least `width` characters, so the `=>` of a series of
+    /// inputs line up, e.g. for a form. Prompts that are longer are left as they are, unless
+    /// [`Clytia::with_label_overflow`] says otherwise. Defaults to [`None`], no padding.
+    pub fn with_label_width(&mut self, width: Option<usize>) -> &mut Self {
+        self.settings.label_width = width;
+        self
+    }
+
+    /// Set what happens to prompts longer than [`Clytia::with_label_width`].
+    /// Defaults to [`LabelOverflow::Overflow`].
+    pub fn with_label_overflow(&mut self, overflow: LabelOverflow) -> &mut Self {
+        self.settings.label_overflow = overflow;
+        self
+    }
+
+    /// Set what [`Clytia::parsed_input_or_default`] returns when nothing is typed and no
+    /// default is given, so the policy can be chosen once for the whole app.
+    /// Defaults to [`EmptyInput::Error`].
+    pub fn with_empty_input(&mut self, empty: EmptyInput) -> &mut Self {
+        self.settings.empty_input = empty;
+        self
+    }
+
+    /// Set how many spaces every line written is indented by, including prompts, menus,
+    /// spinners and progress bars. Useful to nest the steps of a sub-flow under its parent.
+    /// Widths taken from the terminal, like the width of a progress bar, leave room for it.
+    /// Defaults to `0`.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    /// let name: String = cli.parsed_input("Project name", None)?;
+    ///
+    /// cli.with_indent(2);
+    /// let license: String = cli.parsed_input("License", Some("MIT".to_string()))?;
+    /// cli.with_indent(0);
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn with_indent(&mut self, indent: usize) -> &mut Self {
+        self.settings.indent = indent;
+        self.output.indent = indent;
+        self
+    }
+
+    /// Set whether the output is flushed after every write, rather than only when something
+    /// needs to be on screen, see [Buffering](Clytia#buffering). Defaults to `false`.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use std::io::{BufWriter, stdin, stdout};
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::new(stdin(), BufWriter::with_capacity(64 * 1024, stdout()));
+    /// cli.with_immediate_flush(true);
+    /// ```
+    pub fn with_immediate_flush(&mut self, immediate: bool) -> &mut Self {
+        self.output.immediate_flush = immediate;
+        self
+    }
+
+    /// Set the most suggestions [`Clytia::suggest_input`] lists below the input.
+    /// Defaults to `5`.
+    pub fn with_max_suggestions(&mut self, max: usize) -> &mut Self {
+        self.settings.max_suggestions = max;
+        self
+    }
+
+    /// Set the style of the line drawn by [`Clytia::rule`], its label isn't styled.
+    /// Defaults to dimmed.
+    pub fn with_rule_style(&mut self, style: Style) -> &mut Self {
+        self.settings.rule_style = style;
+        self
+    }
+
+    /// Set whether [`Clytia::rule`] draws with `-` instead of `─`, for terminals or fonts
+    /// without box drawing characters. Defaults to `false`.
+    pub fn with_ascii_rules(&mut self, ascii: bool) -> &mut Self {
+        self.settings.ascii_rules = ascii;
+        self
+    }
+
+    /// Set the style of the percentage shown by progress bars, separately from the bar itself.
+    /// Defaults to the theme's prompt colour, the same as the bar. A failed bar is always shown
+    /// in the theme's error colour, see [`Clytia::with_theme`].
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::{Clytia, Style};
+    ///
+    /// let mut cli = Clytia::default();
+    /// cli.with_percent_style(Style::new().white().bold());
+    /// ```
+    pub fn with_percent_style(&mut self, style: Style) -> &mut Self {
+        self.settings.percent_style = Some(style);
+        self
+    }
+
+    /// Set a hook called with every key read by [`Clytia::options_menu`], [`Clytia::multichoice`],
+    /// [`Clytia::quantity_select`], [`Clytia::confirm_all`], [`Clytia::confirm_diff`],
+    /// [`Clytia::pick_number`], [`Clytia::suggest_input`], [`Clytia::choice_input`],
+    /// [`Clytia::masked_pattern_input`], [`Clytia::options_menu_filtered`] and
+    /// [`Clytia::tree_select`], before it's handled.
+    ///
+    /// Useful for debugging how key presses come through in different terminals, or recording
+    /// real sessions to build tests from. Line based input, including [`Clytia::password_input`],
+    /// isn't passed to the hook.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    /// cli.with_key_hook(|key| eprintln!("{:?}", key));
+    /// ```
+    pub fn with_key_hook<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: Fn(Key) + Send + Sync + 'static,
+    {
+        self.settings.key_hook = Some(KeyHook(Arc::new(hook)));
+        self
+    }
+
+    /// Set a hook called whenever a spinner or progress bar finishes, with its text, whether
+    /// its task succeeded and how long it took.
+    ///
+    /// Useful for recording metrics about every task in one place. Tasks that fail to be shown,
+    /// e.g. from an IO error, aren't passed to the hook.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    /// cli.with_on_finish(|text, success, elapsed| {
+    ///     eprintln!("{} took {:?} (success: {})", text, elapsed, success)
+    /// });
+    /// ```
+    pub fn with_on_finish<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: Fn(&str, bool, Duration) + Send + Sync + 'static,
+    {
+        self.settings.on_finish = Some(FinishHook(Arc::new(hook)));
+        self
     }
 
     /// Get a reference to the input stream.
@@ -73,7 +859,7 @@ impl<I: Read, O: Write> Clytia<I, O> {
 
     /// Get a reference to the output stream.
     pub fn output(&self) -> &O {
-        &self.output
+        &self.output.inner
     }
 
     /// Get a mutable reference to the input stream.
@@ -83,7 +869,7 @@ impl<I: Read, O: Write> Clytia<I, O> {
 
     /// Get a mutable reference to the output stream.
     pub fn output_mut(&mut self) -> &mut O {
-        &mut self.output
+        &mut self.output.inner
     }
 
     /// Get input from the user with an optional default.
@@ -132,547 +918,605 @@ impl<I: Read, O: Write> Clytia<I, O> {
         T: FromStr,
         T: std::fmt::Display,
     {
-        let input_stream = &mut self.input;
-        let output_stream = &mut self.output;
-        write!(output_stream, "{} ", prompt.blue())?;
-        if let Some(d) = &default {
-            write!(output_stream, "{} ", format!("(default: {})", d).magenta())?;
-        }
-        write!(output_stream, "{} ", "=>".blue())?;
-        output_stream.flush()?;
+        self.normalized_input(prompt, default, |line| line)
+    }
 
-        let input = input_stream.read_line()?;
-        let ret = match input {
-            None => match default {
-                Some(v) => Ok(v),
-                None => Err(Error::NonOptionalInput),
-            },
-            Some(n) if n.trim().trim_end().is_empty() => match default {
-                Some(v) => Ok(v),
-                None => Err(Error::NonOptionalInput),
-            },
-            Some(n) => {
-                let trimmed = n.trim().trim_end();
-                trimmed
-                    .parse()
-                    .map_err(|_| Error::ParseError(trimmed.to_string()))
+    /// Like [`Clytia::parsed_input`], but for types with a [`Default`] value, where
+    /// [`Clytia::with_empty_input`] decides what happens when nothing is typed and `default`
+    /// is [`None`]: either [`Error::NonOptionalInput`] as usual, or `T::default()`.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::{Clytia, EmptyInput, Result};
+    ///
+    /// let mut cli = Clytia::default();
+    /// cli.with_empty_input(EmptyInput::Default);
+    ///
+    /// // Nothing typed gives 0.
+    /// let r: Result<usize> = cli.parsed_input_or_default("Retries", None);
+    /// ```
+    pub fn parsed_input_or_default<S, T>(&mut self, prompt: S, default: Option<T>) -> Result<T>
+    where
+        S: std::fmt::Display,
+        T: FromStr,
+        T: std::fmt::Display,
+        T: Default,
+    {
+        match self.parsed_input(prompt, default) {
+            Err(Error::NonOptionalInput) if self.settings.empty_input == EmptyInput::Default => {
+                Ok(T::default())
             }
-        }?;
-
-        Ok(ret)
+            ret => ret,
+        }
     }
 
-    /// Get input from the user with a custom validation function.
-    /// Takes in a given prompt and a validation function that is used to check the input before returning.
+    /// Like [`Clytia::parsed_input`], but the prompt is drawn with `style` rather than in
+    /// the usual blue.
     ///
     /// # Usage
     /// ```rust
-    /// use clytia::{Clytia, Error, Result};
+    /// use clytia::{AnsiColors, Clytia, PromptStyle, Result};
     ///
     /// let mut cli = Clytia::default();
     ///
-    /// let r = usize = cli.validated_input("Please enter a number", "0-10", |n| n >= 0 && n <= 10).unwrap();
-    /// assert!(r >= 0 && r <= 10);
+    /// let warning = PromptStyle {
+    ///     indicator_color: AnsiColors::Red,
+    ///     ..PromptStyle::default()
+    /// };
+    /// let r: Result<String> = cli.parsed_input_styled("Type the name to delete it", None, warning);
     /// ```
-    pub fn validated_input<T, S, R, F>(
+    pub fn parsed_input_styled<S, T>(
         &mut self,
         prompt: S,
-        requirements: R,
-        validate: F,
+        default: Option<T>,
+        style: PromptStyle,
     ) -> Result<T>
     where
         S: std::fmt::Display,
-        R: std::fmt::Display,
         T: FromStr,
-        F: Fn(&T) -> bool,
+        T: std::fmt::Display,
     {
-        let input_stream = &mut self.input;
-        let output_stream = &mut self.output;
-
-        loop {
-            write!(
-                output_stream,
-                "{}\r{} {} {} ",
-                termion::clear::CurrentLine,
-                prompt.blue(),
-                format!("(requirements: {})", requirements).magenta(),
-                "=>".blue()
-            )?;
-            output_stream.flush()?;
-
-            let input = input_stream.read_line()?;
-            let r = match input {
-                None => {
-                    write!(
-                        output_stream,
-                        "{}{}\r",
-                        termion::cursor::Up(1),
-                        termion::clear::CurrentLine,
-                    )?;
-                    write!(
-                        output_stream,
-                        "{} {} {} ",
-                        prompt.red(),
-                        format!("(requirements: {})", requirements).magenta(),
-                        "=>".red()
-                    )?;
-                    output_stream.flush()?;
-                    std::thread::sleep(Duration::from_millis(500));
-                    continue;
-                }
-                Some(n) if n.trim().trim_end().is_empty() => {
-                    write!(
-                        output_stream,
-                        "{}{}\r",
-                        termion::cursor::Up(1),
-                        termion::clear::CurrentLine,
-                    )?;
-                    write!(
-                        output_stream,
-                        "{} {} {} ",
-                        prompt.red(),
-                        format!("(requirements: {})", requirements).magenta(),
-                        "=>".red()
-                    )?;
-                    output_stream.flush()?;
-                    std::thread::sleep(Duration::from_millis(500));
-                    continue;
-                }
-                Some(n) => {
-                    let trimmed = n.trim().trim_end();
-                    let parsed = trimmed
-                        .parse()
-                        .map_err(|_| Error::ParseError(trimmed.to_string()))?;
-                    if validate(&parsed) {
-                        Ok::<_, Error>(parsed)
-                    } else {
-                        write!(output_stream, "\r{}", termion::cursor::Up(1),)?;
-                        write!(
-                            output_stream,
-                            "{} {} {} {}",
-                            prompt.red(),
-                            format!("(requirements: {})", requirements).magenta(),
-                            "=>".red(),
-                            n.white()
-                        )?;
-                        output_stream.flush()?;
-                        std::thread::sleep(Duration::from_millis(500));
-                        continue;
-                    }
-                }
-            }?;
-            return Ok(r);
-        }
+        self.read_normalized(prompt, default, |line| line, style)
     }
 
-    /// Show a loading animation using braille until a task completes,
-    /// The text is static and never changes.
+    /// Get input from the user with an optional default, like [`Clytia::parsed_input`],
+    /// passing the line through `normalize` before it's parsed.
     ///
-    /// The task should return a result, the output will show a ✔️ or ❌
-    /// depending on this value.
+    /// Useful to lowercase or strip characters from the input without parsing it to a
+    /// [`String`] first. `normalize` is given the line as it was typed, and the result is
+    /// trimmed before parsing. If the result is empty, `default` is used.
     ///
-    /// # Returns
-    /// Returns the result of the given task
+    /// # Result
+    /// Returns the same as [`Clytia::parsed_input`].
     ///
     /// # Usage
     /// ```rust
-    /// use std::time::Duration;
-    /// use clytia::Clytia;
+    /// use clytia::{Clytia, Result};
     ///
     /// let mut cli = Clytia::default();
     ///
-    /// cli.static_background_spinner("Waiting for 2 seconds", || -> Result<(), ()> {
-    ///     std::thread::sleep(Duration::from_secs(2));
-    ///     Ok(())
-    /// });
+    /// // Accept card numbers typed with or without dashes.
+    /// let r: Result<u64> = cli.normalized_input("Card number", None, |line| line.replace('-', ""));
     /// ```
-    pub fn static_background_spinner<S, F, R, E>(
+    pub fn normalized_input<S, T, N>(
         &mut self,
-        text: S,
-        task: F,
-    ) -> Result<std::result::Result<R, E>>
+        prompt: S,
+        default: Option<T>,
+        normalize: N,
+    ) -> Result<T>
     where
-        S: std::fmt::Display + Sync,
-        F: Fn() -> std::result::Result<R, E>,
-        O: Send,
+        S: std::fmt::Display,
+        T: FromStr,
+        T: std::fmt::Display,
+        N: Fn(String) -> String,
     {
-        let output_stream = &mut self.output;
-
-        let should_stop = AtomicBool::new(false);
-        let ret = scope::<_, Result<Result<R, E>>>(|scope| {
-            let spinner = scope.spawn::<_, Result<()>>(|_| {
-                let mut index = 0;
-                while !should_stop.load(std::sync::atomic::Ordering::SeqCst) {
-                    write!(
-                        output_stream,
-                        "\r{} {}",
-                        SPINNER_SYMBOLS[index].blue(),
-                        text
-                    )?;
-                    output_stream.flush()?;
-                    std::thread::sleep(Duration::from_millis(50));
-                    index = (index + 1) % SPINNER_SYMBOLS.len();
-                }
-                Ok(())
-            });
-
-            let ret = task();
-            should_stop.store(true, std::sync::atomic::Ordering::SeqCst);
-            spinner.join().unwrap()?;
+        let style = self.settings.theme.prompt_style();
+        self.read_normalized(prompt, default, normalize, style)
+    }
 
-            Ok(ret)
-        })
-        .unwrap()?;
+    /// [`Clytia::normalized_input`] with the prompt drawn in `style`.
+    fn read_normalized<S, T, N>(
+        &mut self,
+        prompt: S,
+        default: Option<T>,
+        normalize: N,
+        style: PromptStyle,
+    ) -> Result<T>
+    where
+        S: std::fmt::Display,
+        T: FromStr,
+        T: std::fmt::Display,
+        N: Fn(String) -> String,
+    {
+        self.draw_input_prompt(prompt, default.as_ref(), style)?;
+        let input = read_input_line(&mut self.input, &mut self.output)?.map(normalize);
+        parse_answer(input, default)
+    }
 
-        match &ret {
-            Ok(_) => {
-                writeln!(output_stream, "\r{}", format!("✔️  {}", text).green())
-            }
-            Err(_) => {
-                writeln!(output_stream, "\r{}", format!("❌ {}", text).red())
-            }
-        }?;
+    /// Draw the prompt of a line based input, with its default if there is one.
+    fn draw_input_prompt<S, T>(
+        &mut self,
+        prompt: S,
+        default: Option<&T>,
+        style: PromptStyle,
+    ) -> Result<()>
+    where
+        S: std::fmt::Display,
+        T: std::fmt::Display,
+    {
+        let prompt = self.settings.label(prompt);
+        let output_stream = &mut self.output;
+        write!(output_stream, "{} ", prompt.color(style.prompt_color))?;
+        if let Some(d) = default {
+            write!(
+                output_stream,
+                "{} ",
+                format!("(default: {})", d).color(self.settings.theme.hint)
+            )?;
+        }
+        write!(
+            output_stream,
+            "{} ",
+            style.indicator.color(style.indicator_color)
+        )?;
         output_stream.flush()?;
 
-        Ok(ret)
+        Ok(())
     }
 
-    /// Show a loading animating using braille until a task completes,
-    /// The text is dynamic and is generated from the `text` parameter.
+    /// Like [`Clytia::parsed_input`], but giving up on an answer after `timeout`, for
+    /// pipelines that should carry on when nobody's there to answer. Once timed out `default`
+    /// is returned, or [`Error::NonOptionalInput`] if there isn't one.
     ///
-    /// The `task` should return a result, which will be used to change the final output of the
-    /// spinner with a ✔️ or ❌, depending on success or failure.
+    /// The deadline is for the input to have an answer ready. A terminal only hands over the
+    /// line once enter is pressed, but piped input is read as soon as it starts arriving.
+    /// The answer is read straight from the input's file descriptor, so lines after it are
+    /// left for the next prompt even when the input is buffered, like [`Stdin`]. Anything an
+    /// earlier prompt without a timeout read ahead into that buffer isn't seen.
     ///
     /// # Usage
     /// ```rust
-    /// use std::{time::Duration, sync::atomic::{AtomicUsize, Ordering}};
-    /// use clytia::Clytia;
+    /// use std::time::Duration;
+    /// use clytia::{Clytia, Result};
     ///
     /// let mut cli = Clytia::default();
     ///
-    /// let counter = AtomicUsize::new(0);
-    /// cli.dynamic_background_spinner(
-    ///     || {
-    ///         let count = counter.load(Ordering::SeqCst);
-    ///         if count >= 2_000 {
-    ///             "Waited for 2secs".to_string()
-    ///         } else {
-    ///             format!("{}ms left", 2_000 - count)
-    ///         }
-    ///     },
-    ///     || -> Result<(), ()> {
-    ///         let mut count = counter.load(Ordering::SeqCst);
-    ///         while count < 2_000 {
-    ///             std::thread::sleep(Duration::from_millis(1));
-    ///             count = counter.fetch_add(1, Ordering::SeqCst);
-    ///         }
-    ///         Ok(())
-    ///     });
+    /// let r: Result<usize> =
+    ///     cli.parsed_input_timeout("Retries", Some(3), Duration::from_secs(10));
     /// ```
-    pub fn dynamic_background_spinner<S, P, F, R, E>(
+    pub fn parsed_input_timeout<S, T>(
         &mut self,
-        text_func: S,
-        task: F,
-    ) -> Result<Result<R, E>>
+        prompt: S,
+        default: Option<T>,
+        timeout: Duration,
+    ) -> Result<T>
     where
-        S: Fn() -> P + Sync,
-        P: std::fmt::Display,
-        F: Fn() -> Result<R, E>,
-        O: Send,
+        S: std::fmt::Display,
+        T: FromStr,
+        T: std::fmt::Display,
+        I: AsRawFd,
     {
-        let output_stream = &mut self.output;
-
-        let should_stop = AtomicBool::new(false);
-        let ret = scope::<_, Result<Result<R, E>>>(|scope| {
-            let spinner = scope.spawn::<_, Result<()>>(|_| {
-                let mut index = 0;
-                while !should_stop.load(std::sync::atomic::Ordering::SeqCst) {
-                    write!(
-                        output_stream,
-                        "{}\r{} {}",
-                        termion::clear::CurrentLine,
-                        SPINNER_SYMBOLS[index].blue(),
-                        text_func()
-                    )?;
-                    index = (index + 1) % SPINNER_SYMBOLS.len();
-                    output_stream.flush()?;
-                    std::thread::sleep(Duration::from_millis(50));
-                }
-                Ok(())
-            });
-
-            let ret = task();
-            should_stop.store(true, std::sync::atomic::Ordering::SeqCst);
-            spinner.join().unwrap()?;
-
-            Ok(ret)
-        })
-        .unwrap()?;
-
-        match &ret {
-            Ok(_) => {
-                writeln!(
-                    output_stream,
-                    "{}\r{}",
-                    termion::clear::CurrentLine,
-                    format!("✔️  {}", text_func()).green()
-                )
-            }
-            Err(_) => {
-                writeln!(
-                    output_stream,
-                    "{}\r{}",
-                    termion::clear::CurrentLine,
-                    format!("❌ {}", text_func()).red()
-                )
-            }
-        }?;
-        output_stream.flush()?;
+        let style = self.settings.theme.prompt_style();
+        self.draw_input_prompt(prompt, default.as_ref(), style)?;
+        if !wait_readable(self.input.as_raw_fd(), timeout)? {
+            writeln!(
+                self.output,
+                "{}",
+                "(timed out)".color(self.settings.theme.hint)
+            )?;
+            return default.ok_or(Error::NonOptionalInput);
+        }
 
-        Ok(ret)
+        let input = read_input_line(&mut FdReader(self.input.as_raw_fd()), &mut self.output)?;
+        parse_answer(input, default)
     }
 
-    /// Run a background task and display a progess bar with a percentage.
+    /// Get a yes or no answer from the user with an optional default, like
+    /// [`Clytia::parsed_input`] but accepting the spellings people actually type.
     ///
-    /// The `progress_func` parameter should return a number between `0` and `100`.
+    /// `yes`/`no`, `y`/`n`, `1`/`0`, `on`/`off` and `true`/`false` are accepted, ignoring case.
+    ///
+    /// # Result
+    /// Returns the same as [`Clytia::parsed_input`].
     ///
     /// # Usage
     /// ```rust
-    /// use std::{time::Duration, sync::atomic::{AtomicUsize, Ordering}};
-    /// use clytia::Clytia;
+    /// use clytia::{Clytia, Result};
     ///
     /// let mut cli = Clytia::default();
     ///
-    /// let counter = AtomicUsize::new(0);
-    /// cli.progress_bar(
-    ///     "Wait 2secs",
-    ///     || { counter.load(Ordering::SeqCst) / 20 },
-    ///     || -> Result<(), ()> {
-    ///         let mut count = counter.load(Ordering::SeqCst);
-    ///         while count < 2_000 {
-    ///             std::thread::sleep(Duration::from_millis(1));
-    ///             count = counter.fetch_add(1, Ordering::SeqCst);
-    ///         }
-    ///         Ok(())
-    ///     });
+    /// let r: Result<bool> = cli.bool_input("Enable telemetry?", Some(false));
     /// ```
-    pub fn progress_bar<S, P, F, R, E>(
-        &mut self,
-        prompt: S,
-        progress_func: P,
-        task: F,
-    ) -> Result<Result<R, E>>
+    pub fn bool_input<S>(&mut self, prompt: S, default: Option<bool>) -> Result<bool>
     where
-        S: std::fmt::Display + Sync,
-        P: Fn() -> usize + Sync,
-        F: Fn() -> Result<R, E>,
-        O: Send,
+        S: std::fmt::Display,
     {
-        let output_stream = &mut self.output;
-
-        let should_stop = AtomicBool::new(false);
+        self.normalized_input(prompt, default, |line| match parse_yes_no(&line) {
+            Some(answer) => answer.to_string(),
+            None => line,
+        })
+    }
 
-        let ret = scope::<_, Result<Result<R, E>>>(|scope| {
-            scope.spawn::<_, Result<()>>(|_| {
-                // Drop the cursor down one line to start with.
-                write!(output_stream, "\r")?;
-                while !should_stop.load(std::sync::atomic::Ordering::SeqCst) {
-                    let mut progress = progress_func();
-                    let complete = progress >= 100;
-                    if progress > 100 {
-                        progress = 100;
-                    }
+    /// Ask the user a yes or no question with no default, for decisions where pressing enter
+    /// by accident shouldn't answer for them.
+    ///
+    /// The same spellings as [`Clytia::bool_input`] are accepted. An empty or unrecognised
+    /// answer is marked in red and the user is asked again.
+    ///
+    /// # Result
+    /// Returns:
+    /// - [`Error::NonOptionalInput`] if the input is cancelled (Ctrl-C or Ctrl-D), or ends.
+    /// - [`Error::Io`] if there is a problem reading/writing from stdin/stdout.
+    /// - The user's answer otherwise.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// if cli.confirm_required("Drop the production database?")? {
+    ///     // ...
+    /// }
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn confirm_required<S>(&mut self, prompt: S) -> Result<bool>
+    where
+        S: std::fmt::Display,
+    {
+        let prompt = self.settings.label(prompt);
+        let theme = self.settings.theme;
+        let input_stream = &mut self.input;
+        let output_stream = &mut self.output;
 
-                    // Clear the line, move up, clear that line, go to the start
-                    write!(
-                        output_stream,
-                        "{}{}\r{}{}",
-                        termion::clear::CurrentLine,
-                        termion::cursor::Up(1),
-                        termion::clear::CurrentLine,
-                        termion::cursor::Hide
-                    )?;
-                    writeln!(output_stream, "{}", prompt)?;
+        loop {
+            draw_prompt(output_stream, &prompt, Some("[y/n]"), &theme)?;
 
-                    let cols: usize = termion::terminal_size()?.0.into();
-                    let bar_max_len = cols - 9;
+            let input = match read_input_line(input_stream, output_stream)? {
+                Some(input) => input,
+                None => return Err(Error::NonOptionalInput),
+            };
+            let error = match parse_yes_no(&input) {
+                Some(answer) => return Ok(answer),
+                None if input.trim().is_empty() => "an answer is required",
+                None => "expected yes or no",
+            };
+            draw_rejected(output_stream, &prompt, Some("[y/n]"), &input, error, &theme)?;
+        }
+    }
 
-                    if !complete {
-                        let bar_len =
-                            ((bar_max_len as f64 / 100f64) * (progress as f64).round()) as usize;
+    /// Get input from the user with an optional default and a custom validation function,
+    /// combining [`Clytia::parsed_input`] and [`Clytia::validated_input`].
+    ///
+    /// An empty input gives `default` if set. Otherwise the input is parsed and validated
+    /// like [`validate_str`], and on failure the line is marked in red with the reason and
+    /// the user is asked again.
+    ///
+    /// # Result
+    /// Returns:
+    /// - [`Error::NonOptionalInput`] if `default` is [`None`] and the input is cancelled
+    ///   (Ctrl-C or Ctrl-D).
+    /// - [`Error::Io`] if there is a problem reading/writing from stdin/stdout.
+    /// - `default` if set and no input is given, or the input is cancelled.
+    /// - The parsed and validated `T` otherwise.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let port: u16 = cli.field_input("Port", Some(8080), |port| *port >= 1024)?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn field_input<S, T, F>(&mut self, prompt: S, default: Option<T>, validate: F) -> Result<T>
+    where
+        S: std::fmt::Display,
+        T: FromStr + std::fmt::Display,
+        F: Fn(&T) -> bool,
+    {
+        let prompt = self.settings.label(prompt);
+        let theme = self.settings.theme;
+        let input_stream = &mut self.input;
+        let output_stream = &mut self.output;
 
-                        write!(
-                            output_stream,
-                            "{}",
-                            format!(
-                                "[{}>{}| {:03}%]",
-                                "=".repeat(bar_len),
-                                " ".repeat(bar_max_len - bar_len),
-                                progress
-                            )
-                            .blue()
-                        )?;
-                    } else {
-                        write!(
-                            output_stream,
-                            "{}",
-                            format!("[{}=| {:03}%]", "=".repeat(bar_max_len), progress).blue()
-                        )?;
-                    }
-                    output_stream.flush()?;
-                    std::thread::sleep(Duration::from_millis(50))
-                }
+        let hint = default.as_ref().map(|d| format!("(default: {})", d));
+        loop {
+            draw_prompt(output_stream, &prompt, hint.as_deref(), &theme)?;
 
-                Ok(())
-            });
+            let input = match read_input_line(input_stream, output_stream)? {
+                Some(input) => input,
+                None => return default.ok_or(Error::NonOptionalInput),
+            };
+            let error = match validate_str(&input, &validate) {
+                Ok(value) => return Ok(value),
+                Err(Error::NonOptionalInput) => match default {
+                    Some(default) => return Ok(default),
+                    None => "an input is required".to_string(),
+                },
+                Err(e) => e.to_string(),
+            };
+            draw_rejected(
+                output_stream,
+                &prompt,
+                hint.as_deref(),
+                &input,
+                &error,
+                &theme,
+            )?;
+        }
+    }
 
-            let ret = task();
-            should_stop.store(true, std::sync::atomic::Ordering::SeqCst);
+    /// Get several inputs from the user, one per line, until they enter an empty line.
+    ///
+    /// Each line is parsed to `T`. If a line can't be parsed it's marked in red with the reason
+    /// and the user is asked for that item again, keeping what was collected so far.
+    ///
+    /// # Result
+    /// Returns:
+    /// - [`Error::NonOptionalInput`] if the input is cancelled (Ctrl-C or Ctrl-D), or ends
+    ///   before the empty line.
+    /// - [`Error::Io`] if there is a problem reading/writing from stdin/stdout.
+    /// - Every parsed `T` in the order they were entered, which may be empty.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let hosts: Vec<String> = cli.collect_inputs("Host (empty line to finish)")?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn collect_inputs<S, T>(&mut self, prompt: S) -> Result<Vec<T>>
+    where
+        S: std::fmt::Display,
+        T: FromStr,
+    {
+        let prompt = self.settings.label(prompt);
+        let theme = self.settings.theme;
+        let input_stream = &mut self.input;
+        let output_stream = &mut self.output;
 
-            Ok(ret)
-        })
-        .unwrap()?;
+        let mut collected = Vec::new();
+        loop {
+            draw_prompt(output_stream, &prompt, None, &theme)?;
 
-        match &ret {
-            Ok(_) => {
-                writeln!(
-                    output_stream,
-                    "{}{}\r{}{}✔️  {}",
-                    termion::clear::CurrentLine,
-                    termion::cursor::Up(1),
-                    termion::clear::CurrentLine,
-                    termion::cursor::Hide,
-                    prompt.green()
-                )?;
+            let input = match read_input_line(input_stream, output_stream)? {
+                Some(input) => input,
+                None => return Err(Error::NonOptionalInput),
+            };
+            let trimmed = input.trim();
+            if trimmed.is_empty() {
+                return Ok(collected);
             }
-            Err(_) => {
-                let mut progress = progress_func();
-                if progress > 100 {
-                    progress = 100;
+            match trimmed.parse() {
+                Ok(item) => collected.push(item),
+                Err(_) => {
+                    let error = Error::parse_error::<T>(trimmed).to_string();
+                    draw_rejected(output_stream, &prompt, None, &input, &error, &theme)?;
                 }
+            }
+        }
+    }
 
-                writeln!(
-                    output_stream,
-                    "{}{}\r{}{}❌ {}",
-                    termion::clear::CurrentLine,
-                    termion::cursor::Up(1),
-                    termion::clear::CurrentLine,
-                    termion::cursor::Hide,
-                    prompt.red()
-                )?;
-
-                let cols: usize = termion::terminal_size()?.0.into();
-                // Weird thing where ❌ is wider is last char goes on newline, so minus one character.
-                // (maybe because ❌ is two bytes?)
-                let bar_max_len = cols - 10;
+    /// Get a number from the user, who can type it as an arithmetic expression like `12*3+1`.
+    ///
+    /// `+`, `-`, `*`, `/` and brackets are supported, with the usual precedence. If the
+    /// expression can't be worked out it's marked in red with the reason, and the user is
+    /// asked again.
+    ///
+    /// # Result
+    /// Returns:
+    /// - [`Error::NonOptionalInput`] if the input is cancelled (Ctrl-C or Ctrl-D), or ends.
+    /// - [`Error::Io`] if there is a problem reading/writing from stdin/stdout.
+    /// - The value of the expression otherwise.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let total = cli.amount_input("Total (e.g. 3*4.50)")?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn amount_input<S>(&mut self, prompt: S) -> Result<f64>
+    where
+        S: std::fmt::Display,
+    {
+        let prompt = self.settings.label(prompt);
+        let theme = self.settings.theme;
+        let input_stream = &mut self.input;
+        let output_stream = &mut self.output;
 
-                let bar_len = ((bar_max_len as f64 / 100f64) * (progress as f64).round()) as usize;
+        loop {
+            draw_prompt(output_stream, &prompt, None, &theme)?;
 
-                writeln!(
-                    output_stream,
-                    "{}",
-                    format!(
-                        "[{}❌{}| {:03}%]",
-                        "=".repeat(bar_len),
-                        " ".repeat(bar_max_len - bar_len),
-                        progress
-                    )
-                    .red()
-                )?;
-            }
+            let input = match read_input_line(input_stream, output_stream)? {
+                Some(input) => input,
+                None => return Err(Error::NonOptionalInput),
+            };
+            let error = match evaluate(&input) {
+                Ok(value) => return Ok(value),
+                Err(_) if input.trim().is_empty() => "an input is required".to_string(),
+                Err(e) => e,
+            };
+            draw_rejected(output_stream, &prompt, None, &input, &error, &theme)?;
         }
-
-        Ok(ret)
     }
 
-    /// Present several options to the user for them to pick from.
-    /// They can use the up and down arrow keys to highlight the option,
-    /// and enter to select it.
+    /// Get a range from the user, typed as `start..end`, e.g. `10..20`.
+    ///
+    /// Both ends are parsed as `T`, and `start` can't be after `end`. Otherwise the line is
+    /// marked in red with the reason and the user is asked again.
+    ///
+    /// # Result
+    /// Returns:
+    /// - [`Error::NonOptionalInput`] if the input is cancelled (Ctrl-C or Ctrl-D), or ends.
+    /// - [`Error::Io`] if there is a problem reading/writing from stdin/stdout.
+    /// - `(start, end)` otherwise.
     ///
     /// # Usage
     /// ```rust
     /// use clytia::Clytia;
     ///
     /// let mut cli = Clytia::default();
-    /// let selection = cli.options_menu(vec!["cats", "dogs", "both"]).unwrap();
     ///
-    /// println!("You selected: {}", selection);
+    /// let (first, last): (usize, usize) = cli.range_input("Lines")?;
+    /// # Ok::<(), clytia::Error>(())
     /// ```
-    pub fn options_menu<S, T>(&mut self, options: S) -> Result<T>
+    pub fn range_input<S, T>(&mut self, prompt: S) -> Result<(T, T)>
     where
-        S: AsRef<[T]>,
-        T: std::fmt::Display + Clone,
+        S: std::fmt::Display,
+        T: FromStr + PartialOrd,
     {
-        let output_stream = &mut self.output;
-        let mut output_stream = output_stream.into_raw_mode()?;
+        let prompt = self.settings.label(prompt);
+        let theme = self.settings.theme;
         let input_stream = &mut self.input;
+        let output_stream = &mut self.output;
 
-        let options_count = options.as_ref().len();
-        let mut selected: usize = 0;
+        loop {
+            draw_prompt(output_stream, &prompt, None, &theme)?;
 
-        for (index, option) in options.as_ref().iter().enumerate() {
-            if index == selected {
-                writeln!(
-                    output_stream,
-                    "{}{}\r",
-                    format!("=> {}", option).blue(),
-                    termion::cursor::Hide
-                )?;
-            } else {
-                writeln!(output_stream, "   {}{}\r", option, termion::cursor::Hide)?;
-            }
+            let input = match read_input_line(input_stream, output_stream)? {
+                Some(input) => input,
+                None => return Err(Error::NonOptionalInput),
+            };
+            let error = match parse_range(&input) {
+                Ok(range) => return Ok(range),
+                Err(e) => e,
+            };
+            draw_rejected(output_stream, &prompt, None, &input, error, &theme)?;
         }
-        for c in input_stream.keys() {
-            match c? {
-                Key::Up => selected = (selected + options_count - 1) % options_count,
-                Key::Down => selected = (selected + 1) % options_count,
-                Key::Char('\n') => break,
-                _ => {}
-            }
+    }
 
-            for _ in 0..options_count {
-                write!(
-                    output_stream,
-                    "{}{}",
-                    termion::cursor::Up(1),
-                    termion::clear::CurrentLine
-                )?;
-            }
-            write!(output_stream, "\r")?;
-            for (index, option) in options.as_ref().iter().enumerate() {
-                if index == selected {
-                    writeln!(
-                        output_stream,
-                        "{}{}\r",
-                        format!("=> {}", option).blue(),
-                        termion::cursor::Hide
-                    )?;
-                } else {
-                    writeln!(output_stream, "   {}{}\r", option, termion::cursor::Hide)?;
+    /// Fill in a `T` field by field, asking for each field of `schema` in turn.
+    ///
+    /// Each answer is parsed as its [`FieldType`], and marked in red with the reason if it
+    /// can't be, asking for that field again. The answers are then put together into a JSON
+    /// object to deserialize `T` from. If `T` rejects them, the reason is shown and the field
+    /// it names or quotes is asked for again. When the reason doesn't point at a single field,
+    /// every field is asked for again, with the previous answers as defaults.
+    ///
+    /// # Result
+    /// Returns:
+    /// - [`Error::NonOptionalInput`] if the input is cancelled (Ctrl-C or Ctrl-D).
+    /// - [`Error::Io`] if there is a problem reading/writing from stdin/stdout.
+    /// - The deserialized `T` otherwise.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::{Clytia, FieldType};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Config {
+    ///     name: String,
+    ///     port: u16,
+    ///     tls: bool,
+    /// }
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let config: Config = cli.prompt_struct(&[
+    ///     ("name", FieldType::String),
+    ///     ("port", FieldType::Integer),
+    ///     ("tls", FieldType::Bool),
+    /// ])?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn prompt_struct<T>(&mut self, schema: &[(&str, FieldType)]) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let mut answers = Vec::with_capacity(schema.len());
+        for (name, field_type) in schema {
+            answers.push(self.prompt_field(name, *field_type, None)?);
+        }
+        loop {
+            let object = schema
+                .iter()
+                .zip(&answers)
+                .map(|((name, _), (_, value))| (name.to_string(), value.clone()))
+                .collect();
+            let error = match serde_json::from_value(serde_json::Value::Object(object)) {
+                Ok(value) => return Ok(value),
+                Err(e) => e.to_string(),
+            };
+            let reason = format!("({})", error);
+            writeln!(self.output, "{}", reason.color(self.settings.theme.error))?;
+
+            let values: Vec<_> = answers.iter().map(|(_, value)| value).collect();
+            match offending_field(&error, schema, &values) {
+                Some(i) => {
+                    let (name, field_type) = schema[i];
+                    answers[i] = self.prompt_field(name, field_type, None)?;
+                }
+                None => {
+                    for ((name, field_type), answer) in schema.iter().zip(&mut answers) {
+                        let previous = answer.0.clone();
+                        *answer = self.prompt_field(name, *field_type, Some(&previous))?;
+                    }
                 }
             }
         }
+    }
 
-        for _ in 0..options_count {
-            write!(
+    /// Ask for one field of [`Clytia::prompt_struct`] until it parses as `field_type`,
+    /// returning what was typed along with the parsed value.
+    #[cfg(feature = "serde")]
+    fn prompt_field(
+        &mut self,
+        name: &str,
+        field_type: FieldType,
+        previous: Option<&str>,
+    ) -> Result<(String, serde_json::Value)> {
+        let prompt = self.settings.label(name);
+        let theme = self.settings.theme;
+        let input_stream = &mut self.input;
+        let output_stream = &mut self.output;
+
+        let hint = previous.map(|previous| format!("(default: {})", previous));
+        loop {
+            draw_prompt(output_stream, &prompt, hint.as_deref(), &theme)?;
+
+            let input = match read_input_line(input_stream, output_stream)? {
+                Some(input) => input,
+                None => return Err(Error::NonOptionalInput),
+            };
+            let typed = match (input.trim(), previous) {
+                ("", Some(previous)) => previous.to_string(),
+                (typed, _) => typed.to_string(),
+            };
+            let error = match parse_field(&typed, field_type) {
+                Ok(value) => return Ok((typed, value)),
+                Err(e) => e,
+            };
+            draw_rejected(
                 output_stream,
-                "{}{}",
-                termion::cursor::Up(1),
-                termion::clear::CurrentLine
+                &prompt,
+                hint.as_deref(),
+                &typed,
+                error,
+                &theme,
             )?;
         }
-        writeln!(
-            output_stream,
-            "{}",
-            format!("\r=> {}\r", options.as_ref()[selected]).green()
-        )?;
-
-        Ok(options.as_ref()[selected].clone())
     }
 
-    /// Presents multiple options to the user for them to select,
-    /// they can pick multiple. Up and Down arrow keys to change highlighted
-    /// option, space to modify selection, enter to confirm choices.
+    /// Get a color from the user, as red, green and blue values.
+    ///
+    /// It can be typed as hex (`#ff8800` or `#f80`), `rgb(255, 136, 0)`, or one of the basic
+    /// CSS color names like `orange`. Once accepted, a swatch of the color is shown after
+    /// the input. Anything else is marked in red with the formats accepted, and the user is
+    /// asked again.
+    ///
+    /// # Result
+    /// Returns:
+    /// - [`Error::NonOptionalInput`] if the input is cancelled (Ctrl-C or Ctrl-D), or ends.
+    /// - [`Error::Io`] if there is a problem reading/writing from stdin/stdout.
+    /// - The color as `(red, green, blue)` otherwise.
     ///
     /// # Usage
     /// ```rust
@@ -680,257 +1524,10038 @@ impl<I: Read, O: Write> Clytia<I, O> {
     ///
     /// let mut cli = Clytia::default();
     ///
-    /// let choices = cli.multichoice(vec!["cats", "dogs", "rabbits"]).unwrap();
-    ///
-    /// println!("You selected: {:?}", choices);
+    /// let (r, g, b) = cli.color_input("Accent color")?;
+    /// # Ok::<(), clytia::Error>(())
     /// ```
-    pub fn multichoice<S, T>(&mut self, options: S) -> Result<Vec<T>>
+    pub fn color_input<S>(&mut self, prompt: S) -> Result<(u8, u8, u8)>
     where
-        S: AsRef<[T]>,
-        T: std::fmt::Display + Clone,
+        S: std::fmt::Display,
     {
-        let output_stream = &mut self.output;
-        let mut output_stream = output_stream.into_raw_mode()?;
+        let prompt = self.settings.label(prompt);
+        let theme = self.settings.theme;
         let input_stream = &mut self.input;
+        let output_stream = &mut self.output;
 
-        let mut highlighted: usize = 0;
-        let options_count = options.as_ref().len();
-        let mut selected = HashSet::new();
-
-        write!(output_stream, "{}", termion::cursor::Hide)?;
+        loop {
+            draw_prompt(output_stream, &prompt, None, &theme)?;
 
-        for (index, option) in options.as_ref().iter().enumerate() {
-            if selected.contains(&index) {
-                if highlighted == index {
-                    writeln!(output_stream, "\r{}", format!("[X] {}", option).blue())?;
-                } else {
-                    writeln!(output_stream, "\r[X] {}", option)?;
+            let input = match read_input_line(input_stream, output_stream)? {
+                Some(input) => input,
+                None => return Err(Error::NonOptionalInput),
+            };
+            let input = input.trim();
+            match parse_color(input) {
+                Some((r, g, b)) => {
+                    writeln!(
+                        output_stream,
+                        "{}\r{}{} {} {} {}",
+                        termion::cursor::Up(1),
+                        termion::clear::CurrentLine,
+                        prompt.color(theme.prompt),
+                        "=>".color(theme.prompt),
+                        input,
+                        "██".truecolor(r, g, b)
+                    )?;
+                    return Ok((r, g, b));
                 }
-            } else if highlighted == index {
-                writeln!(output_stream, "\r{}", format!("[ ] {}", option).blue())?;
+                None => draw_rejected(
+                    output_stream,
+                    &prompt,
+                    None,
+                    input,
+                    "expected #rrggbb, rgb(r, g, b) or a color name",
+                    &theme,
+                )?,
+            }
+        }
+    }
+
+    /// Get input from the user with a custom validation function.
+    /// Takes in a given prompt and a validation function that is used to check the input before returning.
+    /// The user is asked again if the input is empty or fails validation, see [`validate_str`].
+    /// Returns [`Error::NonOptionalInput`] if the input is cancelled (Ctrl-C or Ctrl-D), or ends.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::{Clytia, Error, Result};
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let r = usize = cli.validated_input("Please enter a number", "0-10", |n| n >= 0 && n <= 10).unwrap();
+    /// assert!(r >= 0 && r <= 10);
+    /// ```
+    pub fn validated_input<T, S, R, F>(
+        &mut self,
+        prompt: S,
+        requirements: R,
+        validate: F,
+    ) -> Result<T>
+    where
+        S: std::fmt::Display,
+        R: std::fmt::Display,
+        T: FromStr,
+        F: Fn(&T) -> bool,
+    {
+        let style = self.settings.theme.prompt_style();
+        self.validated_input_styled(prompt, requirements, validate, style)
+    }
+
+    /// Like [`Clytia::validated_input`], but the prompt is drawn with `style` rather than in
+    /// the usual blue. It's still shown in red while the input is rejected.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::{AnsiColors, Clytia, PromptStyle};
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let style = PromptStyle {
+    ///     prompt_color: AnsiColors::Yellow,
+    ///     indicator: "?",
+    ///     indicator_color: AnsiColors::Yellow,
+    /// };
+    /// let n: usize = cli.validated_input_styled("How many?", "1-10", |n| (1..=10).contains(n), style)?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn validated_input_styled<T, S, R, F>(
+        &mut self,
+        prompt: S,
+        requirements: R,
+        validate: F,
+        style: PromptStyle,
+    ) -> Result<T>
+    where
+        S: std::fmt::Display,
+        R: std::fmt::Display,
+        T: FromStr,
+        F: Fn(&T) -> bool,
+    {
+        let validate = |value: &T| match validate(value) {
+            true => Ok(()),
+            false => Err(String::new()),
+        };
+        self.read_validated(prompt, requirements, validate, style)
+    }
+
+    /// Like [`Clytia::validated_input`], but `validate` gives the reason an input is rejected,
+    /// e.g. `must be even` or `must be positive`, which is shown in red after it before the
+    /// user is asked again.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let n: i64 = cli.validated_input_with_reason("Pairs", "even and positive", |n| {
+    ///     if n % 2 != 0 {
+    ///         Err("must be even".to_string())
+    ///     } else if *n <= 0 {
+    ///         Err("must be positive".to_string())
+    ///     } else {
+    ///         Ok(())
+    ///     }
+    /// })?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn validated_input_with_reason<T, S, R, F>(
+        &mut self,
+        prompt: S,
+        requirements: R,
+        validate: F,
+    ) -> Result<T>
+    where
+        S: std::fmt::Display,
+        R: std::fmt::Display,
+        T: FromStr,
+        F: Fn(&T) -> std::result::Result<(), String>,
+    {
+        let style = self.settings.theme.prompt_style();
+        self.read_validated(prompt, requirements, validate, style)
+    }
+
+    /// The prompt loop of [`Clytia::validated_input_styled`] and
+    /// [`Clytia::validated_input_with_reason`]. An empty reason from `validate` shows the
+    /// rejected input on its own.
+    fn read_validated<T, S, R, F>(
+        &mut self,
+        prompt: S,
+        requirements: R,
+        validate: F,
+        style: PromptStyle,
+    ) -> Result<T>
+    where
+        S: std::fmt::Display,
+        R: std::fmt::Display,
+        T: FromStr,
+        F: Fn(&T) -> std::result::Result<(), String>,
+    {
+        let prompt = self.settings.label(prompt);
+        let theme = self.settings.theme;
+        let input_stream = &mut self.input;
+        let output_stream = &mut self.output;
+
+        loop {
+            write!(
+                output_stream,
+                "{}\r{} {} {} ",
+                termion::clear::CurrentLine,
+                prompt.color(style.prompt_color),
+                format!("(requirements: {})", requirements).color(theme.hint),
+                style.indicator.color(style.indicator_color)
+            )?;
+            output_stream.flush()?;
+
+            let input = match read_input_line(input_stream, output_stream)? {
+                Some(input) => input,
+                None => return Err(Error::NonOptionalInput),
+            };
+            let parsed = if self.settings.untrimmed_validation {
+                validate_untrimmed(&input, |_| true)
             } else {
-                writeln!(output_stream, "\r[ ] {}", option)?;
+                validate_str(&input, |_| true)
+            };
+            let validated = parsed.and_then(|value| match validate(&value) {
+                Ok(()) => Ok(value),
+                Err(reason) => Err(Error::InvalidInput(reason)),
+            });
+            match validated {
+                Err(Error::NonOptionalInput) => {
+                    write!(
+                        output_stream,
+                        "{}{}\r",
+                        termion::cursor::Up(1),
+                        termion::clear::CurrentLine,
+                    )?;
+                    write!(
+                        output_stream,
+                        "{} {} {} ",
+                        prompt.color(theme.error),
+                        format!("(requirements: {})", requirements).color(theme.hint),
+                        style.indicator.color(theme.error)
+                    )?;
+                    output_stream.flush()?;
+                    std::thread::sleep(Duration::from_millis(500));
+                }
+                Err(Error::InvalidInput(reason)) => {
+                    write!(output_stream, "\r{}", termion::cursor::Up(1),)?;
+                    write!(
+                        output_stream,
+                        "{} {} {} {}",
+                        prompt.color(theme.error),
+                        format!("(requirements: {})", requirements).color(theme.hint),
+                        style.indicator.color(theme.error),
+                        input.white()
+                    )?;
+                    if !reason.is_empty() {
+                        let reason = format!("({})", reason);
+                        write!(output_stream, " {}", reason.color(theme.error))?;
+                    }
+                    output_stream.flush()?;
+                    std::thread::sleep(Duration::from_millis(500));
+                }
+                r => return r,
             }
         }
+    }
+
+    /// Clear the current line and write `content` at the start of it.
+    ///
+    /// Calling this repeatedly keeps updating the same line, so it can be used to build
+    /// your own live displays. Use [`Clytia::clear_status_line`] to remove it once done.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use std::time::Duration;
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// for remaining in (1..=3).rev() {
+    ///     cli.status_line(format!("Starting in {}...", remaining)).unwrap();
+    ///     std::thread::sleep(Duration::from_secs(1));
+    /// }
+    /// cli.clear_status_line().unwrap();
+    /// ```
+    pub fn status_line<S>(&mut self, content: S) -> Result<()>
+    where
+        S: std::fmt::Display,
+    {
+        if self.settings.plain() {
+            writeln!(self.output, "{}", content)?;
+            return Ok(());
+        }
+
+        redraw_line(&mut self.output, content)?;
+        Ok(())
+    }
+
+    /// Clear the current line, leaving the cursor at the start of it.
+    /// See [`Clytia::status_line`].
+    pub fn clear_status_line(&mut self) -> Result<()> {
+        if self.settings.plain() {
+            return Ok(());
+        }
+
+        redraw_line(&mut self.output, "")?;
+        Ok(())
+    }
+
+    /// Show `text` in a status bar pinned to the bottom row of the terminal, replacing what
+    /// was there. Output above it scrolls as usual without overwriting it, until
+    /// [`Clytia::clear_status_bar`] is called.
+    ///
+    /// Calling this again updates the status bar in place, and picks up on the terminal being
+    /// resized since.
+    ///
+    /// # Usage
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// for step in 1..=3 {
+    ///     cli.set_status_bar(format!("Step {}/3", step))?;
+    ///     println!("Doing step {}", step);
+    ///     std::thread::sleep(Duration::from_secs(1));
+    /// }
+    /// cli.clear_status_bar()?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn set_status_bar<S>(&mut self, text: S) -> Result<()>
+    where
+        S: std::fmt::Display,
+    {
+        if self.settings.plain() {
+            writeln!(self.output, "{}", text)?;
+            return Ok(());
+        }
+
+        let rows = termion::terminal_size()?.1;
+        draw_status_bar(&mut self.output, text, rows, !self.status_bar)?;
+        self.status_bar = true;
+        Ok(())
+    }
+
+    /// Remove the status bar shown by [`Clytia::set_status_bar`], giving the bottom row back
+    /// to normal output. Does nothing if there is no status bar.
+    pub fn clear_status_bar(&mut self) -> Result<()> {
+        if !self.status_bar {
+            return Ok(());
+        }
+        let rows = termion::terminal_size()?.1;
+        remove_status_bar(&mut self.output, rows)?;
+        self.status_bar = false;
+        Ok(())
+    }
+
+    /// Draw a horizontal rule across the terminal to separate sections of output, with an
+    /// optional `label` in the middle of it, e.g. `──── Config ────`.
+    ///
+    /// If the output isn't a terminal the rule is 80 columns wide. See
+    /// [`Clytia::with_rule_style`] and [`Clytia::with_ascii_rules`] to change how it looks.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// cli.rule(Some("Config"))?;
+    /// println!("port = 8080");
+    /// cli.rule(None)?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn rule(&mut self, label: Option<&str>) -> Result<()> {
+        let cols = self
+            .settings
+            .width(termion::terminal_size().map_or(80, |(cols, _)| cols));
+        draw_rule(&mut self.output, cols, label, &self.settings)?;
+        Ok(())
+    }
+
+    /// Draw `values` as a sparkline on its own line, e.g. `▁▂▃▅▇█▅▂`, scaled so the lowest
+    /// value is the shortest bar and the highest the tallest.
+    ///
+    /// If every value is the same they're all drawn half height. Values that aren't finite,
+    /// like [`f64::NAN`], are left as gaps. An empty `values` draws an empty line.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// cli.sparkline(&[1.0, 4.0, 9.0, 16.0, 9.0, 4.0, 1.0])?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn sparkline(&mut self, values: &[f64]) -> Result<()> {
+        writeln!(
+            self.output,
+            "{}",
+            format_sparkline(values).color(self.settings.theme.prompt)
+        )?;
+        self.output.flush()?;
+        Ok(())
+    }
+
+    /// Show a brief message for `duration`, then erase it again.
+    ///
+    /// Useful to confirm an action without leaving it in the output for good.
+    /// This blocks for the whole `duration`.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use std::time::Duration;
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// cli.toast("Copied to clipboard!", Duration::from_secs(2))?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn toast<S>(&mut self, text: S, duration: Duration) -> Result<()>
+    where
+        S: std::fmt::Display,
+    {
+        if self.settings.plain() || !self.settings.is_terminal() {
+            writeln!(self.output, "{}", text)?;
+            return Ok(());
+        }
+
+        redraw_line(&mut self.output, text.color(self.settings.theme.prompt))?;
+        std::thread::sleep(duration);
+        redraw_line(&mut self.output, "")?;
+        Ok(())
+    }
+
+    /// Switch to the terminal's alternate screen while running `f`, switching back once it returns.
+    ///
+    /// Anything drawn inside `f` (e.g. a large [`Clytia::options_menu`]) won't be left in the user's
+    /// scrollback. The main screen is restored whether `f` succeeds or fails.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let selection = cli
+    ///     .with_alternate_screen(|cli| cli.options_menu(vec!["cats", "dogs", "both"]))
+    ///     .unwrap();
+    ///
+    /// println!("You selected: {}", selection);
+    /// ```
+    pub fn with_alternate_screen<F, R>(&mut self, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut Self) -> Result<R>,
+    {
+        write!(self.output, "{}", termion::screen::ToAlternateScreen)?;
+        self.output.flush()?;
+
+        let ret = f(self);
+
+        let restored = write!(self.output, "{}", termion::screen::ToMainScreen)
+            .and_then(|_| self.output.flush());
+        let ret = ret?;
+        restored?;
+
+        Ok(ret)
+    }
+
+    /// Hold back everything written while running `f` and write it out in one go once it returns,
+    /// flushing the output only then. Useful for a lot of lines at once, like a table or a long
+    /// list, to save on writes to the terminal.
+    ///
+    /// Only use methods that draw something and return inside `f`, like [`Clytia::rule`] or
+    /// [`Clytia::sparkline`]. Spinners and progress bars have to flush every frame to be seen,
+    /// and prompts need to be on screen before the user answers them, so neither works inside a
+    /// batch. A batch inside a batch is written out with the outer one.
+    ///
+    /// What's been written is flushed whether `f` succeeds or fails.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// cli.batch(|cli| {
+    ///     for label in ["Build", "Test", "Deploy"] {
+    ///         cli.rule(Some(label))?;
+    ///     }
+    ///     Ok(())
+    /// })?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn batch<F, R>(&mut self, f: F) -> Result<R>
+    where
+        F: FnOnce(&mut Self) -> Result<R>,
+    {
+        if self.output.batch.is_some() {
+            return f(self);
+        }
+
+        self.output.batch = Some(Vec::new());
+        let ret = f(self);
+
+        let batched = self.output.batch.take().unwrap_or_default();
+        let written = self
+            .output
+            .inner
+            .write_all(&batched)
+            .and_then(|_| self.output.inner.flush());
+        let ret = ret?;
+        written?;
+
+        Ok(ret)
+    }
+
+    /// Run `f`, returning what it wrote instead of writing it to the output, e.g. to log a
+    /// sub-flow or check it in a test. The output is back to normal once `f` returns.
+    ///
+    /// Like [`Clytia::batch`], only methods that draw something and return work well inside
+    /// `f`: a prompt or menu would wait for an answer the user can't see. A batch inside `f`
+    /// is captured along with everything else.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let (ret, captured) = cli.capture(|cli| cli.rule(Some("Summary")));
+    /// ret?;
+    /// eprintln!("{}", captured);
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn capture<F, R>(&mut self, f: F) -> (R, String)
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        let outer = self.output.batch.replace(Vec::new());
+        let ret = f(self);
+        let captured = std::mem::replace(&mut self.output.batch, outer).unwrap_or_default();
+
+        (ret, String::from_utf8_lossy(&captured).into_owned())
+    }
+
+    /// Get a secret from the user, each character is masked with a `*` as it's typed.
+    /// Ctrl+R toggles between showing the masked and the plain secret, like a
+    /// "show password" checkbox.
+    ///
+    /// Input is read in bulk rather than key by key, and bracketed paste mode is turned on,
+    /// so a pasted secret is captured as a whole and masked in one go. Newlines inside a
+    /// paste are dropped instead of submitting the input early.
+    ///
+    /// # Result
+    /// Returns:
+    /// - [`Error::NonOptionalInput`] if the user didn't input anything, or cancelled with Ctrl+C/Ctrl+D.
+    /// - [`Error::Io`] if there is a problem reading/writing from stdin/stdout.
+    /// - [`Error::Background`] if the process is running in the terminal's background.
+    /// - The entered secret otherwise.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// if let Ok(password) = cli.password_input("Please enter your password") {
+    ///     println!("Your password is {} characters long", password.chars().count());
+    /// }
+    /// ```
+    pub fn password_input<S>(&mut self, prompt: S) -> Result<String>
+    where
+        S: std::fmt::Display,
+    {
+        let prompt = self.settings.label(prompt);
+        let output_stream = &mut self.output;
+        let mut output_stream = raw_mode(output_stream, &self.settings)?;
+
+        run_password_input(&mut self.input, &mut output_stream, prompt, &self.settings)
+    }
+
+    /// Like [`Clytia::password_input`], but if the environment variable `var` is set the
+    /// secret is taken from it instead of prompting. Useful for CI, where nobody is there to
+    /// type it in.
+    ///
+    /// An empty variable counts as unset, as that's what CI systems often give for
+    /// secrets that aren't available.
+    ///
+    /// # Result
+    /// Returns:
+    /// - [`Error::InvalidInput`] if `var` is set, but isn't valid unicode.
+    /// - Any error from [`Clytia::password_input`] if `var` isn't set.
+    /// - The secret otherwise.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// if let Ok(token) = cli.password_input_or_env("API token", "API_TOKEN") {
+    ///     println!("Your token is {} characters long", token.chars().count());
+    /// }
+    /// ```
+    pub fn password_input_or_env<S, K>(&mut self, prompt: S, var: K) -> Result<String>
+    where
+        S: std::fmt::Display,
+        K: AsRef<OsStr>,
+    {
+        match secret_from_env(var.as_ref(), std::env::var(var.as_ref()))? {
+            Some(secret) => Ok(secret),
+            None => self.password_input(prompt),
+        }
+    }
+
+    /// Get an input from the user, validated as it's typed.
+    ///
+    /// After every keystroke the line is parsed and checked with `validate`, showing a ✔ or ✖
+    /// at the end of it. Enter only submits the input once it's valid.
+    ///
+    /// # Result
+    /// - `Ok(T)` - The parsed and validated input.
+    /// - `Err(Error::NonOptionalInput)` - The input was cancelled with Ctrl-C or Ctrl-D.
+    /// - `Err(Error::Io(_))` - An IO error occurred, including when not running in a terminal.
+    ///
+    /// If the input ends before a valid value is submitted, the error from validating
+    /// what was typed so far is returned.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let port: u16 = cli.live_validated_input("Port", |port: &u16| *port >= 1024)?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn live_validated_input<T, F, S>(&mut self, prompt: S, validate: F) -> Result<T>
+    where
+        T: FromStr,
+        F: Fn(&T) -> bool,
+        S: std::fmt::Display,
+    {
+        let prompt = self.settings.label(prompt);
+        if self.settings.plain() {
+            return run_plain_input(
+                &mut self.input,
+                &mut self.output,
+                &prompt,
+                None,
+                &self.settings,
+                |answer| validate_str(answer, &validate).map_err(|_| "invalid".to_string()),
+            );
+        }
+
+        let output_stream = &mut self.output;
+        let mut output_stream = raw_mode(output_stream, &self.settings)?;
+
+        run_live_validated_input(
+            &mut self.input,
+            &mut output_stream,
+            prompt,
+            validate,
+            &self.settings,
+        )
+    }
+
+    /// Get input from the user, showing a dimmed `placeholder` (e.g. `e.g. Alice`) while
+    /// nothing has been typed yet. The placeholder disappears once typing begins.
+    ///
+    /// # Result
+    /// - `Ok(T)` - The parsed input.
+    /// - `Err(Error::NonOptionalInput)` - Nothing was typed, or the input was cancelled with
+    ///   Ctrl-C or Ctrl-D.
+    /// - `Err(Error::ParseError { .. })` - The input could not be parsed to type `T`.
+    /// - `Err(Error::Io(_))` - An IO error occurred, including when not running in a terminal.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let name: String = cli.placeholder_input("Name", "e.g. Alice")?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn placeholder_input<S, P, T>(&mut self, prompt: S, placeholder: P) -> Result<T>
+    where
+        S: std::fmt::Display,
+        P: std::fmt::Display,
+        T: FromStr,
+    {
+        let prompt = self.settings.label(prompt);
+        if self.settings.plain() {
+            let placeholder = placeholder.to_string();
+            let answer = run_plain_input(
+                &mut self.input,
+                &mut self.output,
+                &prompt,
+                Some(&placeholder),
+                &self.settings,
+                |answer| Ok(answer.to_string()),
+            )?;
+            return validate_str(&answer, |_| true);
+        }
+
+        let output_stream = &mut self.output;
+        let mut output_stream = raw_mode(output_stream, &self.settings)?;
+
+        run_placeholder_input(
+            &mut self.input,
+            &mut output_stream,
+            prompt,
+            placeholder,
+            &self.settings,
+        )
+    }
+
+    /// Get input from the user, who can press `?` before typing anything to show `help` under
+    /// the prompt, and again to hide it. A `?` typed after anything else, or pasted, is part
+    /// of the answer. With no `help`, `?` is always part of the answer.
+    ///
+    /// # Result
+    /// - `Ok(T)` - The parsed input.
+    /// - `Err(Error::NonOptionalInput)` - Nothing was typed, or the input was cancelled with
+    ///   Ctrl-C or Ctrl-D.
+    /// - `Err(Error::ParseError { .. })` - The input could not be parsed to type `T`.
+    /// - `Err(Error::Io(_))` - An IO error occurred, including when not running in a terminal.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let cron: String = cli.help_input(
+    ///     "Schedule",
+    ///     Some("Five fields: minute hour day month weekday\ne.g. 0 9 * * 1-5"),
+    /// )?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn help_input<S, H, T>(&mut self, prompt: S, help: Option<H>) -> Result<T>
+    where
+        S: std::fmt::Display,
+        H: std::fmt::Display,
+        T: FromStr,
+    {
+        let prompt = self.settings.label(prompt);
+        let help = help.map(|help| help.to_string());
+        if self.settings.plain() {
+            let hint = help.as_ref().map(|_| "(? for help)");
+            loop {
+                let answer = run_plain_input(
+                    &mut self.input,
+                    &mut self.output,
+                    &prompt,
+                    hint,
+                    &self.settings,
+                    |answer| Ok(answer.to_string()),
+                )?;
+                match &help {
+                    Some(help) if answer == "?" => writeln!(self.output, "{}", help.dimmed())?,
+                    _ => return validate_str(&answer, |_| true),
+                }
+            }
+        }
+
+        let output_stream = &mut self.output;
+        let mut output_stream = raw_mode(output_stream, &self.settings)?;
+
+        run_help_input(
+            &mut self.input,
+            &mut output_stream,
+            prompt,
+            help.as_deref(),
+            &self.settings,
+        )
+    }
+
+    /// Get a number, or anything else parsed from a line, with its `unit` (e.g. `MB`) shown
+    /// after what's typed, so the user only types the value.
+    ///
+    /// If the user types the unit anyway it's stripped off before parsing.
+    ///
+    /// # Result
+    /// - `Ok(T)` - The parsed input.
+    /// - `Err(Error::NonOptionalInput)` - Nothing was typed, or the input was cancelled with
+    ///   Ctrl-C or Ctrl-D.
+    /// - `Err(Error::ParseError { .. })` - The input could not be parsed to type `T`.
+    /// - `Err(Error::Io(_))` - An IO error occurred, including when not running in a terminal.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let memory: u64 = cli.unit_input("Memory limit", "MB")?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn unit_input<S, T>(&mut self, prompt: S, unit: &str) -> Result<T>
+    where
+        S: std::fmt::Display,
+        T: FromStr,
+    {
+        let prompt = self.settings.label(prompt);
+        if self.settings.plain() {
+            let answer = run_plain_input(
+                &mut self.input,
+                &mut self.output,
+                &prompt,
+                Some(unit),
+                &self.settings,
+                |answer| Ok(answer.to_string()),
+            )?;
+            let value = answer.strip_suffix(unit).unwrap_or(&answer);
+            return validate_str(value, |_| true);
+        }
+
+        let output_stream = &mut self.output;
+        let mut output_stream = raw_mode(output_stream, &self.settings)?;
+
+        run_unit_input(
+            &mut self.input,
+            &mut output_stream,
+            prompt,
+            unit,
+            &self.settings,
+        )
+    }
+
+    /// Get digits typed into a `pattern`, e.g. `(___) ___-____` for a phone number, where every
+    /// `_` is a slot for a digit and everything else is filled in as the user types. The slots
+    /// still to be filled are shown dimmed after the cursor.
+    ///
+    /// Only digits are accepted, backspace removes the last one and enter only submits once
+    /// every slot is filled. See [`Clytia::with_pattern_digits`] to get only the digits back.
+    ///
+    /// # Result
+    /// - `Ok(String)` - The input with the pattern filled in, e.g. `(555) 123-4567`.
+    /// - `Err(Error::InvalidInput(_))` - `pattern` has no `_` slots.
+    /// - `Err(Error::NonOptionalInput)` - The input was cancelled with Ctrl-C or Ctrl-D.
+    /// - `Err(Error::Io(_))` - An IO error occurred, including when not running in a terminal.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let phone = cli.masked_pattern_input("Phone", "(___) ___-____")?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn masked_pattern_input<S>(&mut self, prompt: S, pattern: &str) -> Result<String>
+    where
+        S: std::fmt::Display,
+    {
+        let prompt = self.settings.label(prompt);
+        if !pattern.contains('_') {
+            return Err(Error::InvalidInput(pattern.to_string()));
+        }
+        if self.settings.plain() {
+            let slots = pattern.chars().filter(|c| *c == '_').count();
+            let digits = run_plain_input(
+                &mut self.input,
+                &mut self.output,
+                &prompt,
+                Some(pattern),
+                &self.settings,
+                |answer| {
+                    let digits: String = answer.chars().filter(char::is_ascii_digit).collect();
+                    match digits.len() == slots {
+                        true => Ok(digits),
+                        false => Err(format!("expected {} digits", slots)),
+                    }
+                },
+            )?;
+            return match self.settings.pattern_digits {
+                true => Ok(digits),
+                false => Ok(fill_pattern(pattern, &digits).0),
+            };
+        }
+
+        let output_stream = &mut self.output;
+        let mut output_stream = raw_mode(output_stream, &self.settings)?;
+
+        run_masked_pattern_input(
+            &mut self.input,
+            &mut output_stream,
+            prompt,
+            pattern,
+            &self.settings,
+        )
+    }
+
+    /// Ask the user to confirm an action for one of several items, with the option of
+    /// answering for all the remaining items at once, like `git add -p`.
+    ///
+    /// A single key press answers, `y` (yes), `n` (no), `a` (yes to all) or `q` (no to all).
+    /// Other keys are ignored.
+    ///
+    /// # Result
+    /// - `Ok(ConfirmAll)` - The user's answer.
+    /// - `Err(Error::NonOptionalInput)` - The input was cancelled with Ctrl-C, or ended.
+    /// - `Err(Error::Io(_))` - An IO error occurred, including when not running in a terminal.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::{Clytia, ConfirmAll};
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let mut apply_all = false;
+    /// for file in ["a.txt", "b.txt", "c.txt"] {
+    ///     if !apply_all {
+    ///         match cli.confirm_all(format!("Delete {}?", file))? {
+    ///             ConfirmAll::Yes => {}
+    ///             ConfirmAll::No => continue,
+    ///             ConfirmAll::YesToAll => apply_all = true,
+    ///             ConfirmAll::NoToAll => break,
+    ///         }
+    ///     }
+    ///     println!("Deleting {}", file);
+    /// }
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn confirm_all<S>(&mut self, prompt: S) -> Result<ConfirmAll>
+    where
+        S: std::fmt::Display,
+    {
+        let prompt = self.settings.label(prompt);
+        if self.settings.plain() {
+            return run_plain_input(
+                &mut self.input,
+                &mut self.output,
+                &prompt,
+                Some("[y,n,a,q]"),
+                &self.settings,
+                |answer| match answer.to_lowercase().as_str() {
+                    "y" => Ok(ConfirmAll::Yes),
+                    "n" => Ok(ConfirmAll::No),
+                    "a" => Ok(ConfirmAll::YesToAll),
+                    "q" => Ok(ConfirmAll::NoToAll),
+                    _ => Err("expected y, n, a or q".to_string()),
+                },
+            );
+        }
+
+        let output_stream = &mut self.output;
+        let mut output_stream = raw_mode(output_stream, &self.settings)?;
+
+        run_confirm_all(&mut self.input, &mut output_stream, prompt, &self.settings)
+    }
+
+    /// Show the changes from `before` to `after` line by line, removed lines in red and added
+    /// ones in green, then ask the user whether to apply them.
+    ///
+    /// A diff taller than the terminal is shown a page at a time, any key shows the next page
+    /// and `q` skips to the question. Only `y` confirms, enter or `n` declines.
+    ///
+    /// # Result
+    /// - `Ok(bool)` - Whether the user confirmed the changes.
+    /// - `Err(Error::NonOptionalInput)` - The input was cancelled with Ctrl-C, or ended.
+    /// - `Err(Error::Io(_))` - An IO error occurred, including when not running in a terminal.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let before = "name = \"clytia\"\nversion = \"0.2.1\"\n";
+    /// let after = "name = \"clytia\"\nversion = \"0.3.0\"\n";
+    /// if cli.confirm_diff(before, after)? {
+    ///     // Write `after` out.
+    /// }
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn confirm_diff(&mut self, before: &str, after: &str) -> Result<bool> {
+        if self.settings.plain() {
+            for line in diff_lines(before, after) {
+                writeln!(
+                    self.output,
+                    "{}",
+                    format_diff_line(&line, &self.settings.theme)
+                )?;
+            }
+            return run_plain_input(
+                &mut self.input,
+                &mut self.output,
+                "Apply these changes?",
+                Some("[y/N]"),
+                &self.settings,
+                |answer| match answer {
+                    "" => Ok(false),
+                    answer => parse_yes_no(answer).ok_or_else(|| "expected yes or no".to_string()),
+                },
+            );
+        }
+
+        let rows = termion::terminal_size().map_or(24, |(_, rows)| rows.into());
+        let output_stream = &mut self.output;
+        let mut output_stream = raw_mode(output_stream, &self.settings)?;
+
+        run_confirm_diff(
+            &mut self.input,
+            &mut output_stream,
+            &diff_lines(before, after),
+            rows,
+            &self.settings,
+        )
+    }
+
+    /// Show a warning that doesn't need an answer, waiting for any key before carrying on. The
+    /// warning stays, but is dimmed once acknowledged so the user can tell it was seen.
+    ///
+    /// In accessible mode, see [`Clytia::with_accessible`], the warning is printed and enter
+    /// acknowledges it instead.
+    ///
+    /// # Result
+    /// - `Ok(())` - The warning was acknowledged.
+    /// - `Err(Error::NonOptionalInput)` - The input was cancelled with Ctrl-C, or ended.
+    /// - `Err(Error::Io(_))` - An IO error occurred, including when not running in a terminal.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    /// cli.warn_ack("The config file is world-readable")?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn warn_ack<S: std::fmt::Display>(&mut self, text: S) -> Result<()> {
+        if self.settings.plain() {
+            writeln!(self.output, "! {} {}", text, "(press enter)".dimmed())?;
+            return match read_input_line(&mut self.input, &mut self.output)? {
+                Some(_) => Ok(()),
+                None => Err(Error::NonOptionalInput),
+            };
+        }
+
+        let output_stream = &mut self.output;
+        let mut output_stream = raw_mode(output_stream, &self.settings)?;
+        run_warn_ack(&mut self.input, &mut output_stream, text, &self.settings)
+    }
+
+    /// Get a number within `range` from the user, starting at `default`.
+    ///
+    /// The left and right arrow keys step the number down and up, or it can be typed directly.
+    /// While a typed number is outside of `range` it's shown in red, and enter won't accept it.
+    ///
+    /// # Result
+    /// - `Ok(u64)` - The picked number.
+    /// - `Err(Error::EmptyOptions)` - `range` is empty.
+    /// - `Err(Error::NonOptionalInput)` - The input was cancelled with Ctrl-C, or ended.
+    /// - `Err(Error::Io(_))` - An IO error occurred, including when not running in a terminal.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let port = cli.pick_number("Port", 1024..=65535, 8080)?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn pick_number<S>(
+        &mut self,
+        prompt: S,
+        range: RangeInclusive<u64>,
+        default: u64,
+    ) -> Result<u64>
+    where
+        S: std::fmt::Display,
+    {
+        let prompt = self.settings.label(prompt);
+        if range.is_empty() {
+            return Err(Error::EmptyOptions);
+        }
+        if self.settings.plain() {
+            let (min, max) = (*range.start(), *range.end());
+            let default = default.clamp(min, max);
+            return run_plain_input(
+                &mut self.input,
+                &mut self.output,
+                &prompt,
+                Some(&format!("[{}-{}, default {}]", min, max, default)),
+                &self.settings,
+                |answer| match answer {
+                    "" => Ok(default),
+                    answer => answer
+                        .parse()
+                        .ok()
+                        .filter(|number| range.contains(number))
+                        .ok_or_else(|| format!("expected a number from {} to {}", min, max)),
+                },
+            );
+        }
+
+        let output_stream = &mut self.output;
+        let mut output_stream = raw_mode(output_stream, &self.settings)?;
+
+        run_pick_number(
+            &mut self.input,
+            &mut output_stream,
+            prompt,
+            range,
+            default,
+            &self.settings,
+        )
+    }
+
+    /// Get a line of input from the user, with suggestions for it listed below, like a search
+    /// box. `suggest` is called with what's been typed after every keystroke.
+    ///
+    /// The Down and Up arrow keys move through the suggestions, enter accepts the
+    /// highlighted one, or what's been typed if none is highlighted. Tab completes the input as
+    /// far as the suggestions agree.
+    /// See [`Clytia::with_max_suggestions`] to change how many are listed.
+    ///
+    /// # Result
+    /// - `Ok(String)` - The accepted suggestion, or the typed input.
+    /// - `Err(Error::NonOptionalInput)` - Nothing was typed, or the input was cancelled with
+    ///   Ctrl-C or Ctrl-D.
+    /// - `Err(Error::Io(_))` - An IO error occurred, including when not running in a terminal.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let fruits = ["apple", "apricot", "banana", "blackberry", "cherry"];
+    /// let fruit = cli.suggest_input("Fruit", |typed| {
+    ///     fruits
+    ///         .iter()
+    ///         .filter(|fruit| fruit.starts_with(typed))
+    ///         .map(|fruit| fruit.to_string())
+    ///         .collect()
+    /// })?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn suggest_input<S, F>(&mut self, prompt: S, suggest: F) -> Result<String>
+    where
+        S: std::fmt::Display,
+        F: Fn(&str) -> Vec<String>,
+    {
+        let prompt = self.settings.label(prompt);
+        if self.settings.plain() {
+            return run_plain_input(
+                &mut self.input,
+                &mut self.output,
+                &prompt,
+                None,
+                &self.settings,
+                |answer| match answer {
+                    "" => Err("expected an answer".to_string()),
+                    answer => Ok(answer.to_string()),
+                },
+            );
+        }
+
+        let output_stream = &mut self.output;
+        let mut output_stream = raw_mode(output_stream, &self.settings)?;
+
+        run_suggest_input(
+            &mut self.input,
+            &mut output_stream,
+            prompt,
+            suggest,
+            &self.settings,
+        )
+    }
+
+    /// Get a line of input that has to be one of `allowed`, e.g. a country code. The allowed
+    /// values starting with what's been typed are listed below the input, and Tab completes it
+    /// as far as they agree.
+    ///
+    /// Enter only accepts a value from `allowed`, anything else marks the prompt in red and
+    /// keeps the input to be corrected. The Down and Up arrow keys move through the listed
+    /// values like [`Clytia::suggest_input`]'s suggestions.
+    /// See [`Clytia::with_max_suggestions`] to change how many candidates are listed.
+    ///
+    /// # Result
+    /// - `Ok(String)` - The accepted value, one of `allowed`.
+    /// - `Err(Error::EmptyOptions)` - `allowed` is empty.
+    /// - `Err(Error::NonOptionalInput)` - The input was cancelled with Ctrl-C or Ctrl-D.
+    /// - `Err(Error::Io(_))` - An IO error occurred, including when not running in a terminal.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let country = cli.choice_input("Country", &["DE", "DK", "FR", "GB", "US"])?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn choice_input<S>(&mut self, prompt: S, allowed: &[&str]) -> Result<String>
+    where
+        S: std::fmt::Display,
+    {
+        let prompt = self.settings.label(prompt);
+        if allowed.is_empty() {
+            return Err(Error::EmptyOptions);
+        }
+        if self.settings.plain() {
+            return run_plain_input(
+                &mut self.input,
+                &mut self.output,
+                &prompt,
+                None,
+                &self.settings,
+                |answer| match allowed.contains(&answer) {
+                    true => Ok(answer.to_string()),
+                    false => Err(format!("expected one of {}", allowed.join(", "))),
+                },
+            );
+        }
+
+        let output_stream = &mut self.output;
+        let mut output_stream = raw_mode(output_stream, &self.settings)?;
+
+        run_choice_input(
+            &mut self.input,
+            &mut output_stream,
+            prompt,
+            allowed,
+            &self.settings,
+        )
+    }
+
+    /// Show a loading animation using braille until a task completes,
+    /// The text is static and never changes.
+    ///
+    /// The task should return a result, the output will show a ✔️ or ❌
+    /// depending on this value.
+    ///
+    /// When the process is running in the terminal's background the animation isn't drawn,
+    /// only the final ✔️ or ❌ line.
+    ///
+    /// # Returns
+    /// Returns the result of the given task
+    ///
+    /// # Usage
+    /// ```rust
+    /// use std::time::Duration;
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// cli.static_background_spinner("Waiting for 2 seconds", || -> Result<(), ()> {
+    ///     std::thread::sleep(Duration::from_secs(2));
+    ///     Ok(())
+    /// });
+    /// ```
+    pub fn static_background_spinner<S, F, R, E>(
+        &mut self,
+        text: S,
+        task: F,
+    ) -> Result<std::result::Result<R, E>>
+    where
+        S: std::fmt::Display + Sync,
+        F: Fn() -> std::result::Result<R, E>,
+        O: Send,
+    {
+        let output_stream = &mut self.output;
+        let animate = !self.settings.in_background();
+        let frames = &self.settings.spinner_frames;
+        let interval = self.settings.frame_interval;
+        let theme = self.settings.theme;
+        let started = Instant::now();
+        let accessible = self.settings.accessible;
+        let mut still_working = StillWorking::new();
+
+        let (stop, stopped) = bounded::<()>(0);
+        let ret = scope::<_, Result<Result<R, E>>>(|scope| {
+            let spinner = scope.spawn::<_, Result<()>>(|_| {
+                let mut index = 0;
+                loop {
+                    if animate && accessible {
+                        still_working.report(output_stream, &text)?;
+                    } else if animate {
+                        write!(
+                            output_stream,
+                            "\r{} {}",
+                            frames[index].color(theme.prompt),
+                            text
+                        )?;
+                        output_stream.flush()?;
+                    }
+                    if !next_frame(&stopped, interval) {
+                        break;
+                    }
+                    index = (index + 1) % frames.len();
+                }
+                Ok(())
+            });
+
+            let ret = task();
+            drop(stop);
+            spinner.join().unwrap()?;
+
+            Ok(ret)
+        })
+        .unwrap()?;
+
+        match &ret {
+            Ok(_) => write!(
+                output_stream,
+                "\r{}",
+                format!("✔️  {}", text).color(theme.success)
+            ),
+            Err(_) => write!(
+                output_stream,
+                "\r{}",
+                format!("❌ {}", text).color(theme.error)
+            ),
+        }?;
+        if self.settings.finish_newline {
+            writeln!(output_stream)?;
+        }
+        output_stream.flush()?;
+
+        self.settings
+            .finished(&text, ret.is_ok(), started.elapsed());
+
+        Ok(ret)
+    }
+
+    /// Like [`Clytia::static_background_spinner`], but for a task returning an
+    /// [`anyhow::Result`], its error is returned as [`Error::Task`] rather than in a
+    /// nested result.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use std::time::Duration;
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let answer = cli.try_spinner("Thinking", || {
+    ///     std::thread::sleep(Duration::from_secs(2));
+    ///     Ok(42)
+    /// })?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    #[cfg(feature = "anyhow")]
+    pub fn try_spinner<S, F, R>(&mut self, text: S, task: F) -> Result<R>
+    where
+        S: std::fmt::Display + Sync,
+        F: FnOnce() -> anyhow::Result<R>,
+        O: Send,
+    {
+        let task = std::cell::Cell::new(Some(task));
+        let ret = self.static_background_spinner(text, || match task.take() {
+            Some(task) => task(),
+            None => unreachable!("the spinner only runs its task once"),
+        })?;
+
+        Ok(ret?)
+    }
+
+    /// Show a loading animating using braille until a task completes,
+    /// The text is dynamic and is generated from the `text` parameter.
+    ///
+    /// The `task` should return a result, which will be used to change the final output of the
+    /// spinner with a ✔️ or ❌, depending on success or failure.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use std::{time::Duration, sync::atomic::{AtomicUsize, Ordering}};
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let counter = AtomicUsize::new(0);
+    /// cli.dynamic_background_spinner(
+    ///     || {
+    ///         let count = counter.load(Ordering::SeqCst);
+    ///         if count >= 2_000 {
+    ///             "Waited for 2secs".to_string()
+    ///         } else {
+    ///             format!("{}ms left", 2_000 - count)
+    ///         }
+    ///     },
+    ///     || -> Result<(), ()> {
+    ///         let mut count = counter.load(Ordering::SeqCst);
+    ///         while count < 2_000 {
+    ///             std::thread::sleep(Duration::from_millis(1));
+    ///             count = counter.fetch_add(1, Ordering::SeqCst);
+    ///         }
+    ///         Ok(())
+    ///     });
+    /// ```
+    pub fn dynamic_background_spinner<S, P, F, R, E>(
+        &mut self,
+        text_func: S,
+        task: F,
+    ) -> Result<Result<R, E>>
+    where
+        S: Fn() -> P + Sync,
+        P: std::fmt::Display,
+        F: Fn() -> Result<R, E>,
+        O: Send,
+    {
+        let output_stream = &mut self.output;
+        let animate = !self.settings.in_background();
+        let frames = &self.settings.spinner_frames;
+        let interval = self.settings.frame_interval;
+        let theme = self.settings.theme;
+        let started = Instant::now();
+        let accessible = self.settings.accessible;
+        let mut still_working = StillWorking::new();
+
+        let (stop, stopped) = bounded::<()>(0);
+        let ret = scope::<_, Result<Result<R, E>>>(|scope| {
+            let spinner = scope.spawn::<_, Result<()>>(|_| {
+                let mut index = 0;
+                loop {
+                    if animate && accessible {
+                        still_working.report(output_stream, text_func())?;
+                    } else if animate {
+                        redraw_line(
+                            output_stream,
+                            format_args!("{} {}", frames[index].color(theme.prompt), text_func()),
+                        )?;
+                    }
+                    index = (index + 1) % frames.len();
+                    if !next_frame(&stopped, interval) {
+                        break;
+                    }
+                }
+                Ok(())
+            });
+
+            let ret = task();
+            drop(stop);
+            spinner.join().unwrap()?;
+
+            Ok(ret)
+        })
+        .unwrap()?;
+
+        match &ret {
+            Ok(_) => {
+                write!(
+                    output_stream,
+                    "{}\r{}",
+                    termion::clear::CurrentLine,
+                    format!("✔️  {}", text_func()).color(theme.success)
+                )
+            }
+            Err(_) => {
+                write!(
+                    output_stream,
+                    "{}\r{}",
+                    termion::clear::CurrentLine,
+                    format!("❌ {}", text_func()).color(theme.error)
+                )
+            }
+        }?;
+        if self.settings.finish_newline {
+            writeln!(output_stream)?;
+        }
+        output_stream.flush()?;
+
+        self.settings
+            .finished(text_func(), ret.is_ok(), started.elapsed());
+
+        Ok(ret)
+    }
+
+    /// Show a loading animation using braille until a child process exits.
+    ///
+    /// The final output shows a ✔️ or ❌ depending on whether the process exited successfully.
+    ///
+    /// # Returns
+    /// Returns the exit status of the child process
+    ///
+    /// # Usage
+    /// ```rust
+    /// use std::process::Command;
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let mut child = Command::new("sleep").arg("2").spawn()?;
+    /// let status = cli.spinner_wait("Sleeping for 2 seconds", &mut child)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn spinner_wait<S>(
+        &mut self,
+        text: S,
+        child: &mut std::process::Child,
+    ) -> Result<std::process::ExitStatus>
+    where
+        S: std::fmt::Display,
+    {
+        let output_stream = &mut self.output;
+        let animate = !self.settings.in_background();
+        let frames = &self.settings.spinner_frames;
+        let interval = self.settings.frame_interval;
+        let theme = self.settings.theme;
+        let started = Instant::now();
+        let accessible = self.settings.accessible;
+        let mut still_working = StillWorking::new();
+
+        let mut index = 0;
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if animate && accessible {
+                still_working.report(output_stream, &text)?;
+            } else if animate {
+                write!(
+                    output_stream,
+                    "\r{} {}",
+                    frames[index].color(theme.prompt),
+                    text
+                )?;
+                output_stream.flush()?;
+            }
+            std::thread::sleep(interval);
+            index = (index + 1) % frames.len();
+        };
+
+        if status.success() {
+            write!(
+                output_stream,
+                "\r{}",
+                format!("✔️  {}", text).color(theme.success)
+            )?;
+        } else {
+            write!(
+                output_stream,
+                "\r{}",
+                format!("❌ {}", text).color(theme.error)
+            )?;
+        }
+        if self.settings.finish_newline {
+            writeln!(output_stream)?;
+        }
+        output_stream.flush()?;
+
+        self.settings
+            .finished(&text, status.success(), started.elapsed());
+
+        Ok(status)
+    }
+
+    /// Show a loading animation using braille until a value is received on `rx`.
+    ///
+    /// The final output shows a ✔️ once a value arrives, or a ❌ if every sender is dropped
+    /// first.
+    ///
+    /// # Returns
+    /// Returns the value received, or [`Error::Disconnected`] if the channel disconnects.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use std::time::Duration;
+    /// use crossbeam::channel;
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let (tx, rx) = channel::unbounded();
+    /// std::thread::spawn(move || {
+    ///     std::thread::sleep(Duration::from_secs(2));
+    ///     tx.send(42).unwrap();
+    /// });
+    /// let answer = cli.spinner_recv("Waiting for the worker", &rx)?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn spinner_recv<S, T>(&mut self, text: S, rx: &Receiver<T>) -> Result<T>
+    where
+        S: std::fmt::Display,
+    {
+        let output_stream = &mut self.output;
+        let animate = !self.settings.in_background();
+        let frames = &self.settings.spinner_frames;
+        let interval = self.settings.frame_interval;
+        let theme = self.settings.theme;
+        let started = Instant::now();
+        let accessible = self.settings.accessible;
+        let mut still_working = StillWorking::new();
+
+        let mut index = 0;
+        let ret = loop {
+            if animate && accessible {
+                still_working.report(output_stream, &text)?;
+            } else if animate {
+                write!(
+                    output_stream,
+                    "\r{} {}",
+                    frames[index].color(theme.prompt),
+                    text
+                )?;
+                output_stream.flush()?;
+            }
+            match rx.recv_timeout(interval) {
+                Ok(value) => break Ok(value),
+                Err(RecvTimeoutError::Disconnected) => break Err(Error::Disconnected),
+                Err(RecvTimeoutError::Timeout) => {}
+            }
+            index = (index + 1) % frames.len();
+        };
+
+        match &ret {
+            Ok(_) => write!(
+                output_stream,
+                "\r{}",
+                format!("✔️  {}", text).color(theme.success)
+            ),
+            Err(_) => write!(
+                output_stream,
+                "\r{}",
+                format!("❌ {}", text).color(theme.error)
+            ),
+        }?;
+        if self.settings.finish_newline {
+            writeln!(output_stream)?;
+        }
+        output_stream.flush()?;
+
+        self.settings
+            .finished(&text, ret.is_ok(), started.elapsed());
+
+        ret
+    }
+
+    /// Show a loading animation using braille while items are pulled from an iterator,
+    /// along with how many have been processed so far.
+    ///
+    /// Useful when the number of items isn't known up front. Once the iterator is exhausted
+    /// the final output shows a ✔️ with the total count. The spinner only moves as items are
+    /// pulled, and errors writing to the output are ignored as the iterator can't return them.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let total: u64 = cli.spinner_iter("Summing", 0..1_000_000u64).sum();
+    /// ```
+    pub fn spinner_iter<'a, S, T>(
+        &'a mut self,
+        text: S,
+        iter: T,
+    ) -> impl Iterator<Item = T::Item> + 'a
+    where
+        S: std::fmt::Display + 'a,
+        T: IntoIterator,
+        T::IntoIter: 'a,
+    {
+        SpinnerIter {
+            output: &mut self.output,
+            finish_newline: self.settings.finish_newline,
+            animate: !self.settings.in_background(),
+            frames: &self.settings.spinner_frames,
+            interval: self.settings.frame_interval,
+            theme: self.settings.theme,
+            on_finish: self.settings.on_finish.clone(),
+            started: Instant::now(),
+            still_working: self.settings.accessible.then(StillWorking::new),
+            text,
+            iter: iter.into_iter(),
+            count: 0,
+            index: 0,
+            last_draw: None,
+            done: false,
+        }
+    }
+
+    /// Show a loading animation using braille until a task completes, along with how many of
+    /// `total` items it has done, e.g. `Copying (3/10)`. A lighter alternative to
+    /// [`Clytia::progress_bar_counted`].
+    ///
+    /// The task is given a counter to add to as it finishes items. The final output shows a ✔️
+    /// with `(total/total)` if the task succeeds, or a ❌ with the count it got to if it fails.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use std::{time::Duration, sync::atomic::Ordering};
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// cli.spinner_counted("Copying", 10, |done| -> Result<(), ()> {
+    ///     for _ in 0..10 {
+    ///         std::thread::sleep(Duration::from_millis(200));
+    ///         done.fetch_add(1, Ordering::SeqCst);
+    ///     }
+    ///     Ok(())
+    /// });
+    /// ```
+    pub fn spinner_counted<S, F, R, E>(
+        &mut self,
+        prefix: S,
+        total: usize,
+        task: F,
+    ) -> Result<Result<R, E>>
+    where
+        S: std::fmt::Display + Sync,
+        F: Fn(&AtomicUsize) -> Result<R, E>,
+        O: Send,
+    {
+        let done = AtomicUsize::new(0);
+
+        self.dynamic_background_spinner(
+            || {
+                let done = done.load(std::sync::atomic::Ordering::SeqCst);
+                format!("{} ({}/{})", prefix, done.min(total), total)
+            },
+            || {
+                let ret = task(&done);
+                if ret.is_ok() {
+                    done.store(total, std::sync::atomic::Ordering::SeqCst);
+                }
+                ret
+            },
+        )
+    }
+
+    /// Show a loading animation using braille until a task completes, where the task can run
+    /// subtasks that each get their own indented spinner on the lines below.
+    ///
+    /// Subtasks are started through the [`Subtasks`] handle given to `task`, and can start
+    /// subtasks of their own. A subtask's line is removed once it finishes, collapsing back
+    /// to its parent. The final output shows a ✔️ or ❌ depending on the result of `task`.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use std::time::Duration;
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// cli.nested_spinner("Installing", |subtasks| -> Result<(), ()> {
+    ///     subtasks.run("Downloading", |_| {
+    ///         std::thread::sleep(Duration::from_secs(1));
+    ///         Ok(())
+    ///     })?;
+    ///     subtasks.run("Unpacking", |subtasks| {
+    ///         subtasks.run("Checking signatures", |_| {
+    ///             std::thread::sleep(Duration::from_secs(1));
+    ///             Ok(())
+    ///         })
+    ///     })
+    /// });
+    /// ```
+    pub fn nested_spinner<S, F, R, E>(&mut self, text: S, task: F) -> Result<Result<R, E>>
+    where
+        S: std::fmt::Display + Sync,
+        F: FnOnce(&Subtasks) -> Result<R, E>,
+        O: Send,
+    {
+        let output_stream = &mut self.output;
+        let animate = !self.settings.in_background();
+        let frames = &self.settings.spinner_frames;
+        let interval = self.settings.frame_interval;
+        let theme = self.settings.theme;
+        let started = Instant::now();
+        let accessible = self.settings.accessible;
+        let mut still_working = StillWorking::new();
+
+        let subtasks = Subtasks::default();
+        let (stop, stopped) = bounded::<()>(0);
+        let (ret, drawn) = scope::<_, Result<(Result<R, E>, usize)>>(|scope| {
+            let spinner = scope.spawn::<_, Result<usize>>(|_| {
+                let mut index = 0;
+                let mut drawn = 0;
+                loop {
+                    if animate && accessible {
+                        still_working.report(output_stream, &text)?;
+                    } else if animate {
+                        drawn = draw_nested_spinner(
+                            output_stream,
+                            frames[index],
+                            &text,
+                            &subtasks.running(),
+                            drawn,
+                            theme.prompt,
+                        )?;
+                    }
+                    index = (index + 1) % frames.len();
+                    if !next_frame(&stopped, interval) {
+                        break;
+                    }
+                }
+                Ok(drawn)
+            });
+
+            let ret = task(&subtasks);
+            drop(stop);
+            let drawn = spinner.join().unwrap()?;
+
+            Ok((ret, drawn))
+        })
+        .unwrap()?;
+
+        write!(output_stream, "{}", termion::clear::CurrentLine)?;
+        clear_lines(output_stream, drawn)?;
+        match &ret {
+            Ok(_) => write!(
+                output_stream,
+                "\r{}",
+                format!("✔️  {}", text).color(theme.success)
+            ),
+            Err(_) => write!(
+                output_stream,
+                "\r{}",
+                format!("❌ {}", text).color(theme.error)
+            ),
+        }?;
+        if self.settings.finish_newline {
+            writeln!(output_stream)?;
+        }
+        output_stream.flush()?;
+
+        self.settings
+            .finished(&text, ret.is_ok(), started.elapsed());
+
+        Ok(ret)
+    }
+
+    /// Run a background task and display a progess bar with a percentage.
+    ///
+    /// The `progress_func` parameter should return a number between `0` and `100`.
+    /// See [`Clytia::with_progress_summary`] to show how long the task took once it's done.
+    ///
+    /// When the process is running in the terminal's background the bar isn't drawn, only
+    /// the final ✔️ or ❌ line.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use std::{time::Duration, sync::atomic::{AtomicUsize, Ordering}};
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let counter = AtomicUsize::new(0);
+    /// cli.progress_bar(
+    ///     "Wait 2secs",
+    ///     || { counter.load(Ordering::SeqCst) / 20 },
+    ///     || -> Result<(), ()> {
+    ///         let mut count = counter.load(Ordering::SeqCst);
+    ///         while count < 2_000 {
+    ///             std::thread::sleep(Duration::from_millis(1));
+    ///             count = counter.fetch_add(1, Ordering::SeqCst);
+    ///         }
+    ///         Ok(())
+    ///     });
+    /// ```
+    pub fn progress_bar<S, P, F, R, E>(
+        &mut self,
+        prompt: S,
+        progress_func: P,
+        task: F,
+    ) -> Result<Result<R, E>>
+    where
+        S: std::fmt::Display + Sync,
+        P: Fn() -> usize + Sync,
+        F: Fn() -> Result<R, E>,
+        O: Send,
+    {
+        let percent_func = || progress_func() as f64;
+
+        self.run_progress_bar(prompt, percent_func, task, |success, elapsed| {
+            if success {
+                format!("Completed in {}", format_duration(elapsed))
+            } else {
+                format!(
+                    "Failed at {}% after {}",
+                    progress_func().min(100),
+                    format_duration(elapsed)
+                )
+            }
+        })
+    }
+
+    /// Ask the user to confirm before running a task that can't be undone, then run it with a
+    /// [`Clytia::progress_bar`]. The question is shown with a `[y/N]` hint, and an empty
+    /// answer means no. The same spellings as [`Clytia::bool_input`] are accepted, anything
+    /// else is marked with the reason and the user is asked again.
+    ///
+    /// # Result
+    /// - `Ok(Some(_))` - The user agreed, holding what the task returned.
+    /// - `Ok(None)` - The user declined, the task wasn't run.
+    /// - `Err(Error::NonOptionalInput)` - The input was cancelled (Ctrl-C or Ctrl-D), or ended.
+    /// - `Err(_)` - Otherwise the same as [`Clytia::progress_bar`].
+    ///
+    /// # Usage
+    /// ```rust
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let deleted = AtomicUsize::new(0);
+    /// let ret = cli.confirm_then_progress(
+    ///     "Delete 1000 files?",
+    ///     "Deleting files",
+    ///     || deleted.load(Ordering::SeqCst) / 10,
+    ///     || -> Result<(), ()> {
+    ///         while deleted.load(Ordering::SeqCst) < 1000 {
+    ///             deleted.fetch_add(1, Ordering::SeqCst);
+    ///         }
+    ///         Ok(())
+    ///     },
+    /// )?;
+    ///
+    /// if ret.is_none() {
+    ///     println!("Nothing was deleted");
+    /// }
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn confirm_then_progress<C, S, P, F, R, E>(
+        &mut self,
+        confirm_prompt: C,
+        bar_prompt: S,
+        progress_func: P,
+        task: F,
+    ) -> Result<Option<Result<R, E>>>
+    where
+        C: std::fmt::Display,
+        S: std::fmt::Display + Sync,
+        P: Fn() -> usize + Sync,
+        F: Fn() -> Result<R, E>,
+        O: Send,
+    {
+        let prompt = self.settings.label(confirm_prompt);
+        let confirmed = run_plain_input(
+            &mut self.input,
+            &mut self.output,
+            &prompt,
+            Some("[y/N]"),
+            &self.settings,
+            |answer| match (answer, parse_yes_no(answer)) {
+                ("", _) => Ok(false),
+                (_, Some(answer)) => Ok(answer),
+                (_, None) => Err("expected yes or no".to_string()),
+            },
+        )?;
+        if !confirmed {
+            return Ok(None);
+        }
+
+        self.progress_bar(bar_prompt, progress_func, task).map(Some)
+    }
+
+    /// Run a background task and display a progress bar for a known number of items.
+    ///
+    /// The `count_func` parameter should return how many of the `total` items have been processed,
+    /// the bar shows this as a percentage.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use std::{time::Duration, sync::atomic::{AtomicUsize, Ordering}};
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    /// cli.with_progress_summary(true);
+    ///
+    /// let counter = AtomicUsize::new(0);
+    /// cli.progress_bar_counted(
+    ///     "Process 200 items",
+    ///     200,
+    ///     || counter.load(Ordering::SeqCst),
+    ///     || -> Result<(), ()> {
+    ///         while counter.load(Ordering::SeqCst) < 200 {
+    ///             std::thread::sleep(Duration::from_millis(10));
+    ///             counter.fetch_add(1, Ordering::SeqCst);
+    ///         }
+    ///         Ok(())
+    ///     });
+    /// ```
+    pub fn progress_bar_counted<S, P, F, R, E>(
+        &mut self,
+        prompt: S,
+        total: usize,
+        count_func: P,
+        task: F,
+    ) -> Result<Result<R, E>>
+    where
+        S: std::fmt::Display + Sync,
+        P: Fn() -> usize + Sync,
+        F: Fn() -> Result<R, E>,
+        O: Send,
+    {
+        let percent_func = || match total {
+            0 => 100.0,
+            total => count_func() as f64 * 100.0 / total as f64,
+        };
+
+        self.run_progress_bar(prompt, percent_func, task, |success, elapsed| {
+            let count = count_func().min(total);
+            let rate = count as f64 / elapsed.as_secs_f64().max(0.001);
+            if success {
+                format!(
+                    "Completed {} items in {} ({:.0}/s)",
+                    count,
+                    format_duration(elapsed),
+                    rate
+                )
+            } else {
+                format!(
+                    "Failed after {}/{} items in {} ({:.0}/s)",
+                    count,
+                    total,
+                    format_duration(elapsed),
+                    rate
+                )
+            }
+        })
+    }
+
+    /// Run a background task made up of several phases, and display a single progress bar for
+    /// all of them, labelled with the phase currently running.
+    ///
+    /// Each phase is given as its name and weight, how much of the whole task it makes up.
+    /// The `progress_func` parameter should return the index of the current phase and how far
+    /// through it the task is, from `0.0` to `1.0`. The completion line shows the phase the
+    /// task finished in.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use std::{time::Duration, sync::Mutex};
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let progress = Mutex::new((0, 0.0));
+    /// cli.phased_progress_bar(
+    ///     "Installing",
+    ///     &[("downloading", 3.0), ("unpacking", 1.0)],
+    ///     || *progress.lock().unwrap(),
+    ///     || -> Result<(), ()> {
+    ///         for phase in 0..2 {
+    ///             for step in 1..=10 {
+    ///                 std::thread::sleep(Duration::from_millis(100));
+    ///                 *progress.lock().unwrap() = (phase, step as f64 / 10.0);
+    ///             }
+    ///         }
+    ///         Ok(())
+    ///     });
+    /// ```
+    pub fn phased_progress_bar<S, N, P, F, R, E>(
+        &mut self,
+        prompt: S,
+        phases: &[(N, f64)],
+        progress_func: P,
+        task: F,
+    ) -> Result<Result<R, E>>
+    where
+        S: std::fmt::Display + Sync,
+        N: std::fmt::Display + Sync,
+        P: Fn() -> (usize, f64) + Sync,
+        F: Fn() -> Result<R, E>,
+        O: Send,
+    {
+        let weights: Vec<f64> = phases.iter().map(|(_, weight)| *weight).collect();
+        let percent_func = || {
+            let (phase, fraction) = progress_func();
+            weighted_percent(&weights, phase, fraction)
+        };
+        let label = PhaseLabel {
+            prompt,
+            phases,
+            phase: || progress_func().0,
+        };
+
+        self.run_progress_bar(label, percent_func, task, |success, elapsed| {
+            if success {
+                format!("Completed in {}", format_duration(elapsed))
+            } else {
+                format!(
+                    "Failed at {}% after {}",
+                    percent_func().min(100.0).floor(),
+                    format_duration(elapsed)
+                )
+            }
+        })
+    }
+
+    /// Like [`Clytia::progress_bar`], but `task` is given a [`ProgressPause`] handle to pause
+    /// the bar with while it's stalled, e.g. waiting on the user. A paused bar says so after
+    /// its prompt, and the time spent paused isn't counted in the summary.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use std::{time::Duration, sync::atomic::{AtomicUsize, Ordering}};
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    /// cli.with_progress_summary(true);
+    ///
+    /// let percent = AtomicUsize::new(0);
+    /// cli.pausable_progress_bar(
+    ///     "Downloading",
+    ///     || percent.load(Ordering::SeqCst),
+    ///     |pause| -> Result<(), ()> {
+    ///         for step in 1..=100 {
+    ///             if step == 50 {
+    ///                 pause.pause();
+    ///                 std::thread::sleep(Duration::from_secs(1));
+    ///                 pause.resume();
+    ///             }
+    ///             std::thread::sleep(Duration::from_millis(20));
+    ///             percent.store(step, Ordering::SeqCst);
+    ///         }
+    ///         Ok(())
+    ///     });
+    /// ```
+    pub fn pausable_progress_bar<S, P, F, R, E>(
+        &mut self,
+        prompt: S,
+        progress_func: P,
+        task: F,
+    ) -> Result<Result<R, E>>
+    where
+        S: std::fmt::Display + Sync,
+        P: Fn() -> usize + Sync,
+        F: Fn(&ProgressPause) -> Result<R, E>,
+        O: Send,
+    {
+        let pause = ProgressPause::default();
+        let percent_func = || progress_func() as f64;
+        let label = PauseLabel {
+            prompt,
+            pause: &pause,
+        };
+
+        self.run_progress_bar(
+            label,
+            percent_func,
+            || task(&pause),
+            |success, elapsed| {
+                let elapsed = elapsed.saturating_sub(pause.paused_for());
+                if success {
+                    format!("Completed in {}", format_duration(elapsed))
+                } else {
+                    format!(
+                        "Failed at {}% after {}",
+                        progress_func().min(100),
+                        format_duration(elapsed)
+                    )
+                }
+            },
+        )
+    }
+
+    /// Show a progress bar with the last `lines` lines of a log above it, both updating live
+    /// while `task` runs, e.g. for the output of a build. `task` is given a [`LogTail`] handle
+    /// to add lines to the log and set the bar's progress, in percent, with.
+    ///
+    /// Once the task finishes the log is cleared, leaving a ✔️ or ❌ line like
+    /// [`Clytia::progress_bar`]. When the process is running in the terminal's background, or in
+    /// accessible mode, neither the log nor the bar is drawn, only the final line.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use std::time::Duration;
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// cli.progress_with_log_tail("Building", 5, |log| -> Result<(), ()> {
+    ///     for step in 1..=20 {
+    ///         log.log(format!("Compiling crate {}", step));
+    ///         log.set_progress(step as f64 * 5.0);
+    ///         std::thread::sleep(Duration::from_millis(100));
+    ///     }
+    ///     Ok(())
+    /// });
+    /// ```
+    pub fn progress_with_log_tail<S, F, R, E>(
+        &mut self,
+        prompt: S,
+        lines: usize,
+        task: F,
+    ) -> Result<Result<R, E>>
+    where
+        S: std::fmt::Display + Sync,
+        F: FnOnce(&LogTail) -> Result<R, E>,
+        O: Send,
+    {
+        let output_stream = &mut self.output;
+        let settings = &self.settings;
+        let animate = !settings.plain();
+        let log = LogTail::new(lines);
+
+        let (stop, stopped) = bounded::<()>(0);
+        let started = Instant::now();
+
+        let ret = scope::<_, Result<Result<R, E>>>(|scope| {
+            let drawer = scope.spawn::<_, Result<()>>(|_| {
+                if animate {
+                    // Make room for the log and the prompt above the bar.
+                    write!(output_stream, "\r{}", "\n".repeat(lines + 1))?;
+                }
+                loop {
+                    if animate {
+                        let cols = settings.width(termion::terminal_size()?.0);
+                        draw_log_tail(
+                            output_stream,
+                            &log.tail(),
+                            lines,
+                            cols,
+                            &prompt,
+                            log.progress(),
+                            settings,
+                        )?;
+                    }
+                    if !next_frame(&stopped, settings.frame_interval) {
+                        break;
+                    }
+                }
+                Ok(())
+            });
+
+            let ret = task(&log);
+            drop(stop);
+            drawer.join().unwrap()?;
+
+            Ok(ret)
+        })
+        .unwrap()?;
+        let elapsed = started.elapsed();
+
+        if animate {
+            write!(output_stream, "{}", termion::clear::CurrentLine)?;
+            clear_lines(output_stream, lines + 1)?;
+        }
+        match &ret {
+            Ok(_) => write!(
+                output_stream,
+                "\r{}",
+                format!("✔️  {}", prompt).color(settings.theme.success)
+            ),
+            Err(_) => write!(
+                output_stream,
+                "\r{}",
+                format!("❌ {}", prompt).color(settings.theme.error)
+            ),
+        }?;
+        if settings.finish_newline {
+            writeln!(output_stream)?;
+        }
+        output_stream.flush()?;
+        settings.finished(&prompt, ret.is_ok(), elapsed);
+
+        Ok(ret)
+    }
+
+    /// Draws the progress bar for [`Clytia::progress_bar`], [`Clytia::progress_bar_counted`],
+    /// [`Clytia::phased_progress_bar`] and [`Clytia::pausable_progress_bar`], `summary` gives the line shown after completion if
+    /// summaries are turned on.
+    fn run_progress_bar<S, P, F, R, E, M>(
+        &mut self,
+        prompt: S,
+        percent_func: P,
+        task: F,
+        summary: M,
+    ) -> Result<Result<R, E>>
+    where
+        S: std::fmt::Display + Sync,
+        P: Fn() -> f64 + Sync,
+        F: Fn() -> Result<R, E>,
+        M: Fn(bool, Duration) -> String,
+        O: Send,
+    {
+        let output_stream = &mut self.output;
+        let settings = &self.settings;
+        // Accessible mode reports milestones on lines of their own instead of drawing the bar.
+        let milestones = settings.accessible && !settings.in_background();
+        let animate = !settings.plain();
+
+        let (stop, stopped) = bounded::<()>(0);
+        let started = Instant::now();
+
+        let ret = scope::<_, Result<Result<R, E>>>(|scope| {
+            scope.spawn::<_, Result<()>>(|_| {
+                // Drop the cursor down one line to start with.
+                write!(output_stream, "\r")?;
+                if milestones {
+                    writeln!(output_stream, "{}", prompt)?;
+                }
+                let mut reported = 0;
+                let mut throttle = RedrawThrottle::default();
+                loop {
+                    let progress = percent_func().min(100.0);
+                    if milestones {
+                        report_milestone(output_stream, progress, &mut reported)?;
+                    } else if animate {
+                        let percent = format_percent(progress, settings.percent_precision);
+                        let cols = bar_width(
+                            settings.width(termion::terminal_size()?.0),
+                            settings.max_bar_width,
+                        );
+                        // The prompt is part of the key since phase labels change with it.
+                        if throttle.should_redraw((percent, cols, prompt.to_string())) {
+                            draw_progress_bar(output_stream, &prompt, progress, cols, settings)?;
+                        }
+                    }
+                    if !next_frame(&stopped, settings.frame_interval) {
+                        break;
+                    }
+                }
+                if milestones {
+                    // The task may have got further since the last frame.
+                    report_milestone(output_stream, percent_func().min(100.0), &mut reported)?;
+                }
+
+                Ok(())
+            });
+
+            let ret = task();
+            drop(stop);
+
+            Ok(ret)
+        })
+        .unwrap()?;
+        let elapsed = started.elapsed();
+
+        let from = percent_func().min(100.0);
+        if let (Ok(_), Some(fill), true) = (&ret, settings.progress_fill, animate && from < 100.0) {
+            let steps = (fill.as_millis() / 50).max(1) as u32;
+            for step in 1..=steps {
+                let progress = from + (100.0 - from) * f64::from(step) / f64::from(steps);
+                let cols = bar_width(
+                    settings.width(termion::terminal_size()?.0),
+                    settings.max_bar_width,
+                );
+                draw_progress_bar(output_stream, &prompt, progress, cols, settings)?;
+                std::thread::sleep(fill / steps);
+            }
+        }
+
+        // Replace the bar that was drawn, if it was.
+        let rewind = match animate {
+            true => format!(
+                "{}{}\r{}{}",
+                termion::clear::CurrentLine,
+                termion::cursor::Up(1),
+                termion::clear::CurrentLine,
+                settings.cursor_hide()
+            ),
+            false => String::new(),
+        };
+        match &ret {
+            Ok(_) => {
+                write!(
+                    output_stream,
+                    "{}✔️  {}",
+                    rewind,
+                    prompt.color(settings.theme.success)
+                )?;
+            }
+            Err(_) => {
+                let progress = percent_func().min(100.0);
+                let percent = format_percent(progress, settings.percent_precision);
+
+                writeln!(
+                    output_stream,
+                    "{}❌ {}",
+                    rewind,
+                    prompt.color(settings.theme.error)
+                )?;
+
+                let cols = bar_width(
+                    settings.width(termion::terminal_size()?.0),
+                    settings.max_bar_width,
+                );
+                // Weird thing where ❌ is wider is last char goes on newline, so minus one character.
+                // (maybe because ❌ is two bytes?)
+                match cols.checked_sub(7 + percent.len()) {
+                    Some(bar_max_len) if bar_max_len > 0 => {
+                        let bar_len = ((bar_max_len as f64 / 100f64) * progress.round()) as usize;
+
+                        write!(
+                            output_stream,
+                            "{}",
+                            format!(
+                                "[{}❌{}| {}%]",
+                                "=".repeat(bar_len),
+                                " ".repeat(bar_max_len - bar_len),
+                                percent
+                            )
+                            .color(settings.theme.error)
+                        )?;
+                    }
+                    _ => {
+                        let percent = format!("{}%", percent);
+                        write!(output_stream, "{}", percent.color(settings.theme.error))?
+                    }
+                }
+            }
+        }
+        if settings.progress_summary {
+            writeln!(output_stream)?;
+            write!(output_stream, "{}", summary(ret.is_ok(), elapsed).dimmed())?;
+        }
+        if settings.finish_newline {
+            writeln!(output_stream)?;
+        }
+        output_stream.flush()?;
+        settings.finished(&prompt, ret.is_ok(), elapsed);
+
+        Ok(ret)
+    }
+
+    /// Present several options to the user for them to pick from.
+    /// They can use the up and down arrow keys to highlight the option,
+    /// and enter to select it, or escape or Ctrl-C to back out.
+    ///
+    /// Returns [`Error::EmptyOptions`] if `options` is empty, or [`Error::Cancelled`] if the
+    /// menu is backed out of.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    /// let selection = cli.options_menu(vec!["cats", "dogs", "both"]).unwrap();
+    ///
+    /// println!("You selected: {}", selection);
+    /// ```
+    pub fn options_menu<S, T>(&mut self, options: S) -> Result<T>
+    where
+        S: AsRef<[T]>,
+        T: std::fmt::Display + Clone,
+    {
+        let selected = self.options_menu_indexed(options.as_ref())?;
+        Ok(options.as_ref()[selected].clone())
+    }
+
+    /// Like [`Clytia::options_menu`], but returning the index of the selected option rather than
+    /// a copy of it, e.g. to look it up in another list or to tell apart options shown the same.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    /// let names = ["Alex", "Alex", "Sam"];
+    /// let emails = ["alex@work.com", "alex@home.com", "sam@work.com"];
+    /// let index = cli.options_menu_indexed(names)?;
+    ///
+    /// println!("Sending to: {}", emails[index]);
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn options_menu_indexed<S, T>(&mut self, options: S) -> Result<usize>
+    where
+        S: AsRef<[T]>,
+        T: std::fmt::Display,
+    {
+        self.options_menu_from(options.as_ref(), 0)
+    }
+
+    /// Like [`Clytia::options_menu`], but starting with the option at `default_index`
+    /// highlighted rather than the first, e.g. the current value of a setting being changed.
+    /// An index past the end highlights the last option.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    /// let selection = cli.options_menu_with_default(vec!["light", "dark", "system"], 2)?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn options_menu_with_default<S, T>(&mut self, options: S, default_index: usize) -> Result<T>
+    where
+        S: AsRef<[T]>,
+        T: std::fmt::Display + Clone,
+    {
+        let selected = self.options_menu_from(options.as_ref(), default_index)?;
+        Ok(options.as_ref()[selected].clone())
+    }
+
+    /// Like [`Clytia::options_menu`], but typing narrows the options down to those containing
+    /// what's typed, ignoring case, shown on a line above them. Backspace edits the filter, and
+    /// the arrow keys move through the options that match it.
+    ///
+    /// Returns [`Error::EmptyOptions`] if `options` is empty, [`Error::Cancelled`] if the menu
+    /// is backed out of, or [`Error::NonOptionalInput`] if the input ends before an option is
+    /// picked.
+    /// Accessible mode, see [`Clytia::with_accessible`], lists every option numbered instead.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    /// let timezones = vec!["Europe/London", "Europe/Paris", "America/New_York"];
+    /// let timezone = cli.options_menu_filtered(timezones)?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn options_menu_filtered<S, T>(&mut self, options: S) -> Result<T>
+    where
+        S: AsRef<[T]>,
+        T: std::fmt::Display + Clone,
+    {
+        let options = options.as_ref();
+        if options.is_empty() || self.settings.plain() {
+            let selected = self.options_menu_from(options, 0)?;
+            return Ok(options[selected].clone());
+        }
+
+        let height = termion::terminal_size().map_or(24, |(_, rows)| rows.into());
+        let output_stream = &mut self.output;
+        let mut output_stream = raw_mode(output_stream, &self.settings)?;
+        let selected = run_filtered_menu(
+            &mut self.input,
+            &mut output_stream,
+            options,
+            height,
+            &self.settings,
+        )?;
+
+        Ok(options[selected].clone())
+    }
+
+    /// Pick a leaf of a tree, e.g. a file in a directory tree, returning its value. Branches
+    /// show `▶` while collapsed and `▼` while expanded, only `root` starts expanded.
+    ///
+    /// The up and down arrow keys move through the nodes shown, right expands a branch (or
+    /// moves into it once expanded), and left collapses it (or moves up to the branch holding
+    /// the node). Enter picks a leaf, or expands or collapses a branch. Escape or Ctrl-C backs
+    /// out. Trees taller than the terminal scroll, like [`Clytia::options_menu`].
+    ///
+    /// In accessible mode, see [`Clytia::with_accessible`], every leaf is listed numbered
+    /// along with the branches leading to it instead.
+    ///
+    /// Returns [`Error::EmptyOptions`] if the tree has no leaves, or [`Error::Cancelled`] if the
+    /// menu is backed out of.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::{Clytia, TreeNode};
+    ///
+    /// let mut cli = Clytia::default();
+    /// let tree = TreeNode::branch("src", vec![
+    ///     TreeNode::leaf("lib.rs", "src/lib.rs"),
+    ///     TreeNode::branch("bin", vec![TreeNode::leaf("main.rs", "src/bin/main.rs")]),
+    /// ]);
+    ///
+    /// let path = cli.tree_select(tree)?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn tree_select<T>(&mut self, root: TreeNode<T>) -> Result<T> {
+        if !root.has_leaf() {
+            return Err(Error::EmptyOptions);
+        }
+
+        let path = if self.settings.plain() {
+            let leaves = tree_leaves(&root);
+            let labels: Vec<&String> = leaves.iter().map(|(_, label)| label).collect();
+            draw_numbered(&mut self.output, &labels, "\n")?;
+            let selected = run_numbered_menu(
+                &mut self.input,
+                &mut self.output,
+                &labels,
+                false,
+                &self.settings,
+            )?;
+            leaves[selected[0]].0.clone()
+        } else {
+            let height = termion::terminal_size().map_or(24, |(_, rows)| rows.into());
+            let output_stream = &mut self.output;
+            let mut output_stream = raw_mode(output_stream, &self.settings)?;
+            run_tree_select(
+                &mut self.input,
+                &mut output_stream,
+                &root,
+                height,
+                &self.settings,
+            )?
+        };
+
+        Ok(root
+            .into_value(&path)
+            .expect("only the path of a leaf is returned"))
+    }
+
+    /// Select one of `options` by typing its number, e.g. `1) a  2) b  3) c`, asking again
+    /// until a number in range is entered.
+    ///
+    /// Unlike [`Clytia::options_menu`] this reads whole lines without raw mode, so it can be
+    /// scripted over piped input.
+    ///
+    /// Returns [`Error::EmptyOptions`] if `options` is empty, or [`Error::NonOptionalInput`] if
+    /// the input is cancelled (Ctrl-C or Ctrl-D) or ends before a number is picked.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::new(&b"2\n"[..], Vec::new());
+    /// let selection = cli.numbered_select(["a", "b", "c"])?;
+    /// assert_eq!(selection, "b");
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn numbered_select<S, T>(&mut self, options: S) -> Result<T>
+    where
+        S: AsRef<[T]>,
+        T: std::fmt::Display + Clone,
+    {
+        let options = options.as_ref();
+        if options.is_empty() {
+            return Err(Error::EmptyOptions);
+        }
+        draw_numbered(&mut self.output, options, "  ")?;
+        let selected = run_numbered_menu(
+            &mut self.input,
+            &mut self.output,
+            options,
+            false,
+            &self.settings,
+        )?;
+        Ok(options[selected[0]].clone())
+    }
+
+    /// The menu behind [`Clytia::options_menu_indexed`] and
+    /// [`Clytia::options_menu_with_default`], starting with `selected` highlighted.
+    fn options_menu_from<T>(&mut self, options: &[T], selected: usize) -> Result<usize>
+    where
+        T: std::fmt::Display,
+    {
+        if options.is_empty() {
+            return Err(Error::EmptyOptions);
+        }
+        if self.settings.plain() {
+            draw_numbered(&mut self.output, options, "\n")?;
+            let selected = run_numbered_menu(
+                &mut self.input,
+                &mut self.output,
+                options,
+                false,
+                &self.settings,
+            )?;
+            return Ok(selected[0]);
+        }
+
+        let height = termion::terminal_size().map_or(24, |(_, rows)| rows.into());
+        let output_stream = &mut self.output;
+        let mut output_stream = raw_mode(output_stream, &self.settings)?;
+        run_options_menu_from(
+            &mut self.input,
+            &mut output_stream,
+            options,
+            selected.min(options.len() - 1),
+            height,
+            &self.settings,
+        )
+    }
+
+    /// Like [`Clytia::options_menu`], but choosing whether the highlight wraps around for this
+    /// menu only, whatever [`Clytia::with_menu_wrap`] is set to.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let selection = cli.options_menu_with_wrap(vec!["cats", "dogs", "both"], false)?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn options_menu_with_wrap<S, T>(&mut self, options: S, wrap: bool) -> Result<T>
+    where
+        S: AsRef<[T]>,
+        T: std::fmt::Display + Clone,
+    {
+        let menu_wrap = std::mem::replace(&mut self.settings.menu_wrap, wrap);
+        let ret = self.options_menu(options);
+        self.settings.menu_wrap = menu_wrap;
+        ret
+    }
+
+    /// Like [`Clytia::options_menu`], but each option is a name with a description, shown
+    /// in two columns. Descriptions are cut short to fit the width of the terminal.
+    ///
+    /// Returns the value paired with the picked option, [`Error::EmptyOptions`] if `items` is
+    /// empty, or [`Error::Cancelled`] if the menu is backed out of.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    /// let commands = vec![
+    ///     ("build".to_string(), "Compile the project".to_string(), 0),
+    ///     ("test".to_string(), "Run the tests".to_string(), 1),
+    /// ];
+    /// let command = cli.options_menu_two_column(&commands).unwrap();
+    /// ```
+    pub fn options_menu_two_column<T>(&mut self, items: &[(String, String, T)]) -> Result<T>
+    where
+        T: Clone,
+    {
+        if items.is_empty() {
+            return Err(Error::EmptyOptions);
+        }
+
+        let cols = self
+            .settings
+            .width(termion::terminal_size().map_or(80, |(cols, _)| cols));
+        let rows = format_two_columns(items, cols);
+        if self.settings.plain() {
+            draw_numbered(&mut self.output, &rows, "\n")?;
+            let selected = run_numbered_menu(
+                &mut self.input,
+                &mut self.output,
+                &rows,
+                false,
+                &self.settings,
+            )?;
+            return Ok(items[selected[0]].2.clone());
+        }
+
+        let height = termion::terminal_size().map_or(24, |(_, rows)| rows.into());
+        let output_stream = &mut self.output;
+        let mut output_stream = raw_mode(output_stream, &self.settings)?;
+        let selected = run_options_menu(
+            &mut self.input,
+            &mut output_stream,
+            &rows,
+            height,
+            &self.settings,
+        )?;
+
+        Ok(items[selected].2.clone())
+    }
+
+    /// Presents multiple options to the user for them to select,
+    /// they can pick multiple. Up and Down arrow keys to change highlighted
+    /// option, space to modify selection, enter to confirm choices, escape or Ctrl-C to back
+    /// out.
+    ///
+    /// Returns [`Error::EmptyOptions`] if `options` is empty, or [`Error::Cancelled`] if the
+    /// menu is backed out of. See [`Clytia::multichoice_cancellable`] to keep what was selected
+    /// when the menu is backed out of.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let choices = cli.multichoice(vec!["cats", "dogs", "rabbits"]).unwrap();
+    ///
+    /// println!("You selected: {:?}", choices);
+    /// ```
+    pub fn multichoice<S, T>(&mut self, options: S) -> Result<Vec<T>>
+    where
+        S: AsRef<[T]>,
+        T: std::fmt::Display + Clone,
+    {
+        let options = options.as_ref();
+        let selected = self.multichoice_from(options, HashSet::new())?;
+        Ok(selected.into_iter().map(|i| options[i].clone()).collect())
+    }
+
+    /// Like [`Clytia::multichoice`], but with the options at the `preselected` indexes already
+    /// ticked when the menu appears, e.g. the subscriptions a user already has. Indexes past
+    /// the end are ignored. Accessible mode, see [`Clytia::with_accessible`], still asks for
+    /// every number to be typed.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let choices = cli.multichoice_with_defaults(vec!["news", "offers", "updates"], &[0, 2])?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn multichoice_with_defaults<S, T>(
+        &mut self,
+        options: S,
+        preselected: &[usize],
+    ) -> Result<Vec<T>>
+    where
+        S: AsRef<[T]>,
+        T: std::fmt::Display + Clone,
+    {
+        let options = options.as_ref();
+        let selected = preselected
+            .iter()
+            .copied()
+            .filter(|&index| index < options.len())
+            .collect();
+        let selected = self.multichoice_from(options, selected)?;
+        Ok(selected.into_iter().map(|i| options[i].clone()).collect())
+    }
+
+    /// The menu behind [`Clytia::multichoice`] and [`Clytia::multichoice_with_defaults`],
+    /// starting with the `selected` indexes ticked. Returns the indexes picked, in order.
+    fn multichoice_from<T>(&mut self, options: &[T], selected: HashSet<usize>) -> Result<Vec<usize>>
+    where
+        T: std::fmt::Display,
+    {
+        if options.is_empty() {
+            return Err(Error::EmptyOptions);
+        }
+        if self.settings.plain() {
+            draw_numbered(&mut self.output, options, "\n")?;
+            return run_numbered_menu(
+                &mut self.input,
+                &mut self.output,
+                options,
+                true,
+                &self.settings,
+            );
+        }
+
+        let output_stream = &mut self.output;
+        let mut output_stream = raw_mode(output_stream, &self.settings)?;
+        run_multichoice(
+            &mut self.input,
+            &mut output_stream,
+            options,
+            selected,
+            &self.settings,
+        )
+    }
+
+    /// Like [`Clytia::multichoice`], but choosing whether the highlight wraps around for this
+    /// menu only, whatever [`Clytia::with_menu_wrap`] is set to.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let choices = cli.multichoice_with_wrap(vec!["cats", "dogs", "rabbits"], false)?;
+    /// # Ok::<(), clytia::Error>(())
+    /// ```
+    pub fn multichoice_with_wrap<S, T>(&mut self, options: S, wrap: bool) -> Result<Vec<T>>
+    where
+        S: AsRef<[T]>,
+        T: std::fmt::Display + Clone,
+    {
+        let menu_wrap = std::mem::replace(&mut self.settings.menu_wrap, wrap);
+        let ret = self.multichoice(options);
+        self.settings.menu_wrap = menu_wrap;
+        ret
+    }
+
+    /// Like [`Clytia::multichoice`], but the user can also cancel with escape or Ctrl-C. The
+    /// options toggled so far are returned either way, along with whether the user confirmed
+    /// them with enter (`true`) or cancelled (`false`).
+    ///
+    /// Returns [`Error::EmptyOptions`] if `options` is empty.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let (choices, confirmed) = cli.multichoice_cancellable(vec!["cats", "dogs"]).unwrap();
+    ///
+    /// if !confirmed {
+    ///     println!("Saving draft: {:?}", choices);
+    /// }
+    /// ```
+    pub fn multichoice_cancellable<S, T>(&mut self, options: S) -> Result<(Vec<T>, bool)>
+    where
+        S: AsRef<[T]>,
+        T: std::fmt::Display + Clone,
+    {
+        if options.as_ref().is_empty() {
+            return Err(Error::EmptyOptions);
+        }
+
+        let (selected, confirmed) = if self.settings.plain() {
+            draw_numbered(&mut self.output, options.as_ref(), "\n")?;
+            match run_numbered_menu(
+                &mut self.input,
+                &mut self.output,
+                options.as_ref(),
+                true,
+                &self.settings,
+            ) {
+                Ok(selected) => (selected, true),
+                Err(Error::NonOptionalInput) => (Vec::new(), false),
+                Err(e) => return Err(e),
+            }
+        } else {
+            let output_stream = &mut self.output;
+            let mut output_stream = raw_mode(output_stream, &self.settings)?;
+            run_multichoice_cancellable(
+                &mut self.input,
+                &mut output_stream,
+                options.as_ref(),
+                &self.settings,
+            )?
+        };
+
+        let selected = selected
+            .into_iter()
+            .map(|index| options.as_ref()[index].clone())
+            .collect();
+        Ok((selected, confirmed))
+    }
+
+    /// Like [`Clytia::multichoice`], but once the user presses enter they're asked to confirm
+    /// their selection with `y` (or enter again). Pressing `n` goes back to the menu, keeping
+    /// what was selected.
+    ///
+    /// Returns [`Error::EmptyOptions`] if `options` is empty, [`Error::Cancelled`] if the menu
+    /// or the confirmation is backed out of with escape or Ctrl-C, or
+    /// [`Error::NonOptionalInput`] if the input ends first.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let choices = cli.multichoice_confirmed(vec!["cats", "dogs", "rabbits"]).unwrap();
+    ///
+    /// println!("You selected: {:?}", choices);
+    /// ```
+    pub fn multichoice_confirmed<S, T>(&mut self, options: S) -> Result<Vec<T>>
+    where
+        S: AsRef<[T]>,
+        T: std::fmt::Display + Clone,
+    {
+        if options.as_ref().is_empty() {
+            return Err(Error::EmptyOptions);
+        }
+
+        let selected = if self.settings.plain() {
+            loop {
+                draw_numbered(&mut self.output, options.as_ref(), "\n")?;
+                let selected = run_numbered_menu(
+                    &mut self.input,
+                    &mut self.output,
+                    options.as_ref(),
+                    true,
+                    &self.settings,
+                )?;
+                let confirmed = run_plain_input(
+                    &mut self.input,
+                    &mut self.output,
+                    "Confirm?",
+                    Some("[Y/n]"),
+                    &self.settings,
+                    |answer| match answer {
+                        "" => Ok(true),
+                        answer => {
+                            parse_yes_no(answer).ok_or_else(|| "expected yes or no".to_string())
+                        }
+                    },
+                )?;
+                if confirmed {
+                    break selected;
+                }
+            }
+        } else {
+            let output_stream = &mut self.output;
+            let mut output_stream = raw_mode(output_stream, &self.settings)?;
+            run_multichoice_confirmed(
+                &mut self.input,
+                &mut output_stream,
+                options.as_ref(),
+                &self.settings,
+            )?
+        };
+
+        Ok(selected
+            .into_iter()
+            .map(|index| options.as_ref()[index].clone())
+            .collect())
+    }
+
+    /// Like [`Clytia::multichoice`], but the number of options selected has to be within
+    /// `range`. Pressing enter with too few or too many selected shows a warning saying how
+    /// many more to select or how many to deselect, and goes back to the menu.
+    ///
+    /// Returns [`Error::EmptyOptions`] if `options` is empty, [`Error::UnmetLimits`] if `range`
+    /// is empty or needs more options than there are, [`Error::Cancelled`] if the menu is
+    /// backed out of, or [`Error::NonOptionalInput`] if the input ends first.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let toppings = cli
+    ///     .multichoice_limited(vec!["cheese", "ham", "olives", "peppers"], 1..=3)
+    ///     .unwrap();
+    ///
+    /// println!("You selected: {:?}", toppings);
+    /// ```
+    pub fn multichoice_limited<S, T>(
+        &mut self,
+        options: S,
+        range: RangeInclusive<usize>,
+    ) -> Result<Vec<T>>
+    where
+        S: AsRef<[T]>,
+        T: std::fmt::Display + Clone,
+    {
+        if options.as_ref().is_empty() {
+            return Err(Error::EmptyOptions);
+        }
+        if range.is_empty() || *range.start() > options.as_ref().len() {
+            return Err(Error::UnmetLimits);
+        }
+
+        let selected = if self.settings.plain() {
+            let count = options.as_ref().len();
+            draw_numbered(&mut self.output, options.as_ref(), "\n")?;
+            let selected = run_plain_input(
+                &mut self.input,
+                &mut self.output,
+                "Numbers, separated by commas",
+                None,
+                &self.settings,
+                |answer| {
+                    let selected = parse_numbered(answer, count, true)
+                        .ok_or_else(|| format!("expected numbers from 1 to {}", count))?;
+                    match limit_warning(selected.len(), &range) {
+                        Some(warning) => Err(warning),
+                        None => Ok(selected),
+                    }
+                },
+            )?;
+            draw_multichoice_result(
+                &mut self.output,
+                options.as_ref(),
+                &selected,
+                &self.settings,
+            )?;
+            selected
+        } else {
+            let output_stream = &mut self.output;
+            let mut output_stream = raw_mode(output_stream, &self.settings)?;
+            run_multichoice_limited(
+                &mut self.input,
+                &mut output_stream,
+                options.as_ref(),
+                range,
+                &self.settings,
+            )?
+        };
+
+        Ok(selected
+            .into_iter()
+            .map(|index| options.as_ref()[index].clone())
+            .collect())
+    }
+
+    /// Presents multiple items to the user to pick from, each with a quantity. Up and Down
+    /// arrow keys change the highlighted item, space includes or excludes it, Left and Right
+    /// lower or raise its quantity, and enter confirms.
+    ///
+    /// Every item starts with a quantity of 1. Only the included items with a quantity of at
+    /// least 1 are returned, in the order they were given.
+    ///
+    /// Returns [`Error::EmptyOptions`] if `items` is empty.
+    ///
+    /// # Usage
+    /// ```rust
+    /// use clytia::Clytia;
+    ///
+    /// let mut cli = Clytia::default();
+    ///
+    /// let order = cli.quantity_select(&["coffee", "tea", "cake"]).unwrap();
+    ///
+    /// for (item, quantity) in order {
+    ///     println!("{} x {}", quantity, item);
+    /// }
+    /// ```
+    pub fn quantity_select<T>(&mut self, items: &[T]) -> Result<Vec<(T, u32)>>
+    where
+        T: std::fmt::Display + Clone,
+    {
+        if items.is_empty() {
+            return Err(Error::EmptyOptions);
+        }
+
+        let selected = if self.settings.plain() {
+            draw_numbered(&mut self.output, items, "\n")?;
+            let picked = run_numbered_menu(
+                &mut self.input,
+                &mut self.output,
+                items,
+                true,
+                &self.settings,
+            )?;
+            let mut selected = Vec::new();
+            for index in picked {
+                let quantity = run_plain_input(
+                    &mut self.input,
+                    &mut self.output,
+                    &format!("Quantity of {}", items[index]),
+                    Some("[1]"),
+                    &self.settings,
+                    |answer| match answer {
+                        "" => Ok(1),
+                        answer => answer
+                            .parse::<u32>()
+                            .map_err(|_| "expected a whole number".to_string()),
+                    },
+                )?;
+                if quantity > 0 {
+                    selected.push((index, quantity));
+                }
+            }
+            selected
+        } else {
+            let output_stream = &mut self.output;
+            let mut output_stream = raw_mode(output_stream, &self.settings)?;
+            run_quantity_select(&mut self.input, &mut output_stream, items, &self.settings)?
+        };
+
+        Ok(selected
+            .into_iter()
+            .map(|(index, quantity)| (items[index].clone(), quantity))
+            .collect())
+    }
+}
+
+impl Default for Clytia<Stdin, Stdout> {
+    fn default() -> Self {
+        let mut cli = Self::new(io::stdin(), io::stdout());
+        cli.settings.output_fd = Some(io::stdout().as_raw_fd());
+        cli
+    }
+}
+
+/// Parse the answer to a numbered menu into the sorted indices of the options picked, which
+/// are numbered from 1 to `count`. Exactly one has to be picked, unless `multiple` is set.
+fn parse_numbered(answer: &str, count: usize, multiple: bool) -> Option<Vec<usize>> {
+    let mut selected = Vec::new();
+    for number in answer
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|number| !number.is_empty())
+    {
+        match number.parse::<usize>() {
+            Ok(number) if (1..=count).contains(&number) => selected.push(number - 1),
+            _ => return None,
+        }
+    }
+    selected.sort_unstable();
+    selected.dedup();
+
+    match multiple || selected.len() == 1 {
+        true => Some(selected),
+        false => None,
+    }
+}
+
+/// Parse a yes or no answer, see [`Clytia::bool_input`].
+fn parse_yes_no(answer: &str) -> Option<bool> {
+    match answer.trim().to_lowercase().as_str() {
+        "yes" | "y" | "1" | "on" | "true" => Some(true),
+        "no" | "n" | "0" | "off" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse an answer for a field of [`Clytia::prompt_struct`] as a JSON value.
+#[cfg(feature = "serde")]
+fn parse_field(
+    typed: &str,
+    field_type: FieldType,
+) -> std::result::Result<serde_json::Value, &'static str> {
+    if typed.is_empty() && field_type != FieldType::String {
+        return Err("an input is required");
+    }
+    match field_type {
+        FieldType::String => Ok(serde_json::Value::String(typed.to_string())),
+        FieldType::Integer => typed
+            .parse::<i64>()
+            .map(serde_json::Value::from)
+            .or_else(|_| typed.parse::<u64>().map(serde_json::Value::from))
+            .map_err(|_| "expected a whole number"),
+        FieldType::Float => typed
+            .parse()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(serde_json::Value::Number)
+            .ok_or("expected a number"),
+        FieldType::Bool => parse_yes_no(typed)
+            .map(serde_json::Value::Bool)
+            .ok_or("expected yes or no"),
+    }
+}
+
+/// The secret [`Clytia::password_input_or_env`] takes from `value`, the result of reading the
+/// environment variable `var`. `None` if it's unset or empty.
+fn secret_from_env(
+    var: &OsStr,
+    value: std::result::Result<String, VarError>,
+) -> Result<Option<String>> {
+    match value {
+        Ok(secret) if !secret.is_empty() => Ok(Some(secret)),
+        Err(VarError::NotUnicode(_)) => {
+            Err(Error::InvalidInput(var.to_string_lossy().into_owned()))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Find the field of [`Clytia::prompt_struct`] a deserialization error is about, either by
+/// the field name it quotes (e.g. ``missing field `port` ``) or by the rejected value (e.g.
+/// ``invalid value: integer `70000`, expected u16``). `None` unless exactly one field matches.
+#[cfg(feature = "serde")]
+fn offending_field(
+    error: &str,
+    schema: &[(&str, FieldType)],
+    values: &[&serde_json::Value],
+) -> Option<usize> {
+    let only = |matches: Vec<usize>| match matches[..] {
+        [i] => Some(i),
+        _ => None,
+    };
+    let by_name = schema
+        .iter()
+        .enumerate()
+        .filter(|(_, (name, _))| error.contains(&format!("`{}`", name)))
+        .map(|(i, _)| i)
+        .collect();
+    only(by_name).or_else(|| {
+        let by_value = values
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| {
+                let quoted = match value {
+                    serde_json::Value::String(s) => format!("string {:?}", s),
+                    serde_json::Value::Bool(b) => format!("boolean `{}`", b),
+                    serde_json::Value::Number(n) => format!(" `{}`", n),
+                    _ => return false,
+                };
+                error.contains(&quoted)
+            })
+            .map(|(i, _)| i)
+            .collect();
+        only(by_value)
+    })
+}
+
+/// Parse a range typed as `start..end`, see [`Clytia::range_input`].
+fn parse_range<T>(s: &str) -> std::result::Result<(T, T), &'static str>
+where
+    T: FromStr + PartialOrd,
+{
+    let s = s.trim();
+    if s.is_empty() {
+        return Err("an input is required");
+    }
+    let (start, end) = s.split_once("..").ok_or("expected start..end")?;
+    let start: T = start.trim().parse().map_err(|_| "invalid start")?;
+    let end: T = end.trim().parse().map_err(|_| "invalid end")?;
+    if start > end {
+        return Err("start is after end");
+    }
+
+    Ok((start, end))
+}
+
+/// The colors [`Clytia::color_input`] accepts by name, the basic CSS ones.
+const COLOR_NAMES: [(&str, (u8, u8, u8)); 18] = [
+    ("black", (0, 0, 0)),
+    ("silver", (192, 192, 192)),
+    ("gray", (128, 128, 128)),
+    ("grey", (128, 128, 128)),
+    ("white", (255, 255, 255)),
+    ("maroon", (128, 0, 0)),
+    ("red", (255, 0, 0)),
+    ("purple", (128, 0, 128)),
+    ("magenta", (255, 0, 255)),
+    ("green", (0, 128, 0)),
+    ("lime", (0, 255, 0)),
+    ("olive", (128, 128, 0)),
+    ("yellow", (255, 255, 0)),
+    ("navy", (0, 0, 128)),
+    ("blue", (0, 0, 255)),
+    ("teal", (0, 128, 128)),
+    ("cyan", (0, 255, 255)),
+    ("orange", (255, 165, 0)),
+];
+
+/// Parse a color typed as hex, `rgb(r, g, b)` or a name, see [`Clytia::color_input`].
+fn parse_color(s: &str) -> Option<(u8, u8, u8)> {
+    let s = s.trim().to_lowercase();
+
+    if let Some(hex) = s.strip_prefix('#') {
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel =
+            |i: usize, len: usize| u8::from_str_radix(hex.get(i * len..(i + 1) * len)?, 16).ok();
+        return match hex.len() {
+            6 => Some((channel(0, 2)?, channel(1, 2)?, channel(2, 2)?)),
+            // Each digit is doubled, so #f80 is #ff8800.
+            3 => Some((
+                channel(0, 1)? * 17,
+                channel(1, 1)? * 17,
+                channel(2, 1)? * 17,
+            )),
+            _ => None,
+        };
+    }
+
+    if let Some(args) = s.strip_prefix("rgb(").and_then(|s| s.strip_suffix(')')) {
+        let channels: Vec<u8> = args
+            .split(',')
+            .map(|channel| channel.trim().parse().ok())
+            .collect::<Option<_>>()?;
+        return match channels[..] {
+            [r, g, b] => Some((r, g, b)),
+            _ => None,
+        };
+    }
+
+    COLOR_NAMES
+        .iter()
+        .find(|(name, _)| *name == s)
+        .map(|(_, rgb)| *rgb)
+}
+
+/// Work out the value of an arithmetic expression like `12*3+1`, see [`Clytia::amount_input`].
+///
+/// Uses the shunting yard algorithm, with `-` before a number or bracket taken as negation.
+fn evaluate(expr: &str) -> std::result::Result<f64, String> {
+    fn precedence(op: char) -> u8 {
+        match op {
+            '+' | '-' => 1,
+            '*' | '/' => 2,
+            // Negation
+            _ => 3,
+        }
+    }
+
+    fn apply(values: &mut Vec<f64>, op: char) -> std::result::Result<(), String> {
+        let b = values.pop().ok_or("incomplete expression")?;
+        let value = match op {
+            '~' => -b,
+            op => {
+                let a = values.pop().ok_or("incomplete expression")?;
+                match op {
+                    '+' => a + b,
+                    '-' => a - b,
+                    '*' => a * b,
+                    _ if b == 0.0 => return Err("division by zero".to_string()),
+                    _ => a / b,
+                }
+            }
+        };
+        values.push(value);
+        Ok(())
+    }
+
+    let mut values = Vec::new();
+    let mut ops = Vec::new();
+    // Whether a number is expected next, rather than an operator.
+    let mut expect_value = true;
+    let mut chars = expr.chars().filter(|c| !c.is_whitespace()).peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '0'..='9' | '.' if expect_value => {
+                let mut number = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || *c == '.') {
+                    number.push(c);
+                }
+                let value = number
+                    .parse()
+                    .map_err(|_| format!("{:?} isn't a number", number))?;
+                values.push(value);
+                expect_value = false;
+            }
+            '-' if expect_value => ops.push('~'),
+            '(' if expect_value => ops.push('('),
+            ')' if !expect_value => loop {
+                match ops.pop() {
+                    Some('(') => break,
+                    Some(op) => apply(&mut values, op)?,
+                    None => return Err("unmatched ')'".to_string()),
+                }
+            },
+            '+' | '-' | '*' | '/' if !expect_value => {
+                while let Some(&top) = ops.last() {
+                    if top == '(' || precedence(top) < precedence(c) {
+                        break;
+                    }
+                    ops.pop();
+                    apply(&mut values, top)?;
+                }
+                ops.push(c);
+                expect_value = true;
+            }
+            c => return Err(format!("unexpected {:?}", c)),
+        }
+    }
+    if expect_value {
+        return Err("incomplete expression".to_string());
+    }
+    while let Some(op) = ops.pop() {
+        if op == '(' {
+            return Err("unmatched '('".to_string());
+        }
+        apply(&mut values, op)?;
+    }
+
+    values
+        .pop()
+        .ok_or_else(|| "incomplete expression".to_string())
+}
+
+/// Format a duration the way Clytia shows them, e.g. in progress bar summaries, picking the
+/// units by how long it is: `820ms`, `45s`, `3m12s` or `1h02m`. Each is rounded down.
+///
+/// # Usage
+/// ```rust
+/// use std::time::Duration;
+/// use clytia::format_duration;
+///
+/// assert_eq!(format_duration(Duration::from_millis(820)), "820ms");
+/// assert_eq!(format_duration(Duration::from_secs(192)), "3m12s");
+/// assert_eq!(format_duration(Duration::from_secs(3_720)), "1h02m");
+/// ```
+pub fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    match secs {
+        0 => format!("{}ms", d.as_millis()),
+        1..=59 => format!("{}s", secs),
+        60..=3_599 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3_600, secs % 3_600 / 60),
+    }
+}
+
+/// Parse and validate a string the same way [`Clytia::validated_input`] does, without any IO.
+///
+/// # Result
+/// Returns:
+/// - [`Error::NonOptionalInput`] if `s` is empty or only whitespace.
+/// - [`Error::ParseError`] if the trimmed `s` could not be parsed to type `T`.
+/// - [`Error::InvalidInput`] if the parsed value didn't pass `validate`.
+/// - The parsed `T` otherwise.
+///
+/// # Usage
+/// ```rust
+/// use clytia::{validate_str, Error};
+///
+/// let r: usize = validate_str(" 7 ", |n| *n <= 10).unwrap();
+/// assert_eq!(r, 7);
+///
+/// assert!(matches!(validate_str::<usize, _>("11", |n| *n <= 10), Err(Error::InvalidInput(_))));
+/// ```
+pub fn validate_str<T, F>(s: &str, validate: F) -> Result<T>
+where
+    T: FromStr,
+    F: Fn(&T) -> bool,
+{
+    let trimmed = s.trim();
+    if trimmed.is_empty() {
+        return Err(Error::NonOptionalInput);
+    }
+
+    validate_untrimmed(trimmed, validate)
+}
+
+/// Parse and validate a string as is, leaving it to `validate` to reject empty or
+/// whitespace input.
+fn validate_untrimmed<T, F>(s: &str, validate: F) -> Result<T>
+where
+    T: FromStr,
+    F: Fn(&T) -> bool,
+{
+    let parsed = s.parse().map_err(|_| Error::parse_error::<T>(s))?;
+    if validate(&parsed) {
+        Ok(parsed)
+    } else {
+        Err(Error::InvalidInput(s.to_string()))
+    }
+}
+
+/// The iterator returned by [`Clytia::spinner_iter`].
+struct SpinnerIter<'a, W, S, I> {
+    output: &'a mut W,
+    finish_newline: bool,
+    animate: bool,
+    frames: &'a [char],
+    interval: Duration,
+    theme: Theme,
+    on_finish: Option<FinishHook>,
+    started: Instant,
+    /// Set in accessible mode, in place of drawing frames.
+    still_working: Option<StillWorking>,
+    text: S,
+    iter: I,
+    count: usize,
+    index: usize,
+    last_draw: Option<Instant>,
+    done: bool,
+}
+
+impl<'a, W, S, I> SpinnerIter<'a, W, S, I>
+where
+    W: Write,
+    S: std::fmt::Display,
+{
+    /// Draw the next spinner frame, unless the last one was drawn too recently.
+    fn tick(&mut self) -> io::Result<()> {
+        if !self.animate {
+            return Ok(());
+        }
+        if let Some(still_working) = &mut self.still_working {
+            return still_working.report(self.output, &self.text);
+        }
+        if matches!(self.last_draw, Some(drawn) if drawn.elapsed() < self.interval) {
+            return Ok(());
+        }
+        write!(
+            self.output,
+            "\r{} {} ({})",
+            self.frames[self.index].color(self.theme.prompt),
+            self.text,
+            self.count
+        )?;
+        self.output.flush()?;
+        self.index = (self.index + 1) % self.frames.len();
+        self.last_draw = Some(Instant::now());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        write!(
+            self.output,
+            "{}\r{}",
+            termion::clear::CurrentLine,
+            format!("✔️  {} ({})", self.text, self.count).color(self.theme.success)
+        )?;
+        if self.finish_newline {
+            writeln!(self.output)?;
+        }
+        self.output.flush()?;
+        if let Some(FinishHook(hook)) = &self.on_finish {
+            hook(&self.text.to_string(), true, self.started.elapsed());
+        }
+        Ok(())
+    }
+}
+
+impl<'a, W, S, I> Iterator for SpinnerIter<'a, W, S, I>
+where
+    W: Write,
+    S: std::fmt::Display,
+    I: Iterator,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let _ = self.tick();
+        match self.iter.next() {
+            Some(item) => {
+                self.count += 1;
+                Some(item)
+            }
+            None => {
+                self.done = true;
+                let _ = self.finish();
+                None
+            }
+        }
+    }
+}
+
+/// Handle for running subtasks under a [`Clytia::nested_spinner`].
+#[derive(Debug, Default)]
+pub struct Subtasks {
+    running: Mutex<Vec<String>>,
+}
+
+impl Subtasks {
+    /// Run `task` as a subtask, showing `text` with its own spinner under its parent until it returns.
+    /// `task` is given this handle too, so it can run subtasks of its own.
+    ///
+    /// Subtasks are expected to run one after another, a subtask started while another one
+    /// is running is shown nested under it.
+    pub fn run<S, F, R, E>(&self, text: S, task: F) -> Result<R, E>
+    where
+        S: std::fmt::Display,
+        F: FnOnce(&Subtasks) -> Result<R, E>,
+    {
+        self.running.lock().unwrap().push(text.to_string());
+        let ret = task(self);
+        self.running.lock().unwrap().pop();
+        ret
+    }
+
+    /// The text of every subtask currently running, outermost first.
+    fn running(&self) -> Vec<String> {
+        self.running.lock().unwrap().clone()
+    }
+}
+
+/// Handle for adding to the log of a [`Clytia::progress_with_log_tail`] and moving its bar.
+#[derive(Debug)]
+pub struct LogTail {
+    /// The last lines logged, as many as are shown.
+    lines: Mutex<std::collections::VecDeque<String>>,
+    capacity: usize,
+    progress: Mutex<f64>,
+}
+
+impl LogTail {
+    fn new(capacity: usize) -> Self {
+        Self {
+            lines: Mutex::new(std::collections::VecDeque::with_capacity(capacity)),
+            capacity,
+            progress: Mutex::new(0.0),
+        }
+    }
+
+    /// Add `line` to the bottom of the log, pushing the oldest line out once it's full.
+    /// A line containing line breaks is added as several lines.
+    pub fn log<S: std::fmt::Display>(&self, line: S) {
+        let mut lines = self.lines.lock().unwrap();
+        for line in line.to_string().lines() {
+            if lines.len() == self.capacity {
+                lines.pop_front();
+            }
+            if self.capacity > 0 {
+                lines.push_back(line.to_string());
+            }
+        }
+    }
+
+    /// Move the bar to `percent`, from 0 to 100.
+    pub fn set_progress(&self, percent: f64) {
+        *self.progress.lock().unwrap() = percent;
+    }
+
+    /// The lines shown, oldest first.
+    fn tail(&self) -> Vec<String> {
+        self.lines.lock().unwrap().iter().cloned().collect()
+    }
+
+    fn progress(&self) -> f64 {
+        self.progress.lock().unwrap().clamp(0.0, 100.0)
+    }
+}
+
+/// Handle for pausing a [`Clytia::pausable_progress_bar`] while its task is stalled.
+#[derive(Debug, Default)]
+pub struct ProgressPause {
+    /// When the current pause started, if the bar is paused.
+    paused_at: Mutex<Option<Instant>>,
+    /// How long the bar was paused for before the current pause.
+    paused_for: Mutex<Duration>,
+}
+
+impl ProgressPause {
+    /// Pause the bar, until [`ProgressPause::resume`] is called. Does nothing if it's already
+    /// paused.
+    pub fn pause(&self) {
+        self.paused_at
+            .lock()
+            .unwrap()
+            .get_or_insert_with(Instant::now);
+    }
+
+    /// Resume the bar after [`ProgressPause::pause`]. Does nothing if it isn't paused.
+    pub fn resume(&self) {
+        if let Some(paused_at) = self.paused_at.lock().unwrap().take() {
+            *self.paused_for.lock().unwrap() += paused_at.elapsed();
+        }
+    }
+
+    /// Whether the bar is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.lock().unwrap().is_some()
+    }
+
+    /// How long the bar has been paused for in total, including the current pause.
+    fn paused_for(&self) -> Duration {
+        let current = self
+            .paused_at
+            .lock()
+            .unwrap()
+            .map_or(Duration::ZERO, |paused_at| paused_at.elapsed());
+        *self.paused_for.lock().unwrap() + current
+    }
+}
+
+/// An input that writes everything read through it to a log, with the time it was read,
+/// so a session can be played back later with [`Replay`].
+///
+/// Each read is logged on its own line, as the milliseconds since the recorder was created
+/// and the bytes read in hex.
+///
+/// # Usage
+/// ```rust,no_run
+/// use std::fs::File;
+/// use clytia::{Clytia, Recorder};
+///
+/// let log = File::create("session.log")?;
+/// let mut cli = Clytia::new(Recorder::new(std::io::stdin(), log), std::io::stdout());
+///
+/// let name: String = cli.parsed_input("Name", None)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct Recorder<R: Read, W: Write> {
+    input: R,
+    log: W,
+    start: Instant,
+}
+
+impl<R: Read, W: Write> Recorder<R, W> {
+    /// Record everything read from `input` to `log`.
+    pub fn new(input: R, log: W) -> Self {
+        Self {
+            input,
+            log,
+            start: Instant::now(),
+        }
+    }
+}
+
+impl<R: Read, W: Write> Read for Recorder<R, W> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.input.read(buf)?;
+        if read > 0 {
+            let hex: String = buf[..read].iter().map(|b| format!("{:02x}", b)).collect();
+            writeln!(self.log, "{} {}", self.start.elapsed().as_millis(), hex)?;
+            self.log.flush()?;
+        }
+        Ok(read)
+    }
+}
+
+/// An input playing back a session logged by a [`Recorder`], to reproduce it against
+/// the same prompts.
+///
+/// By default everything is handed over straight away, see [`Replay::with_timing`] to
+/// wait as long as the user originally did.
+///
+/// # Usage
+/// ```rust,no_run
+/// use std::fs::File;
+/// use clytia::{Clytia, Replay};
+///
+/// let mut replay = Replay::new(File::open("session.log")?)?;
+/// replay.with_timing(true);
+/// let mut cli = Clytia::new(replay, std::io::stdout());
+///
+/// let name: String = cli.parsed_input("Name", None)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct Replay {
+    reads: std::collections::VecDeque<(Duration, Vec<u8>)>,
+    timing: bool,
+    start: Option<Instant>,
+}
+
+impl Replay {
+    /// Load a session from the log written by a [`Recorder`].
+    ///
+    /// # Result
+    /// Returns:
+    /// - [`Error::InvalidInput`] with the offending line if the log isn't one written by a [`Recorder`].
+    /// - [`Error::Io`] if there is a problem reading the log.
+    /// - The session otherwise.
+    pub fn new<R: Read>(mut log: R) -> Result<Self> {
+        let mut content = String::new();
+        log.read_to_string(&mut content)?;
+
+        let reads = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                parse_replay_line(line).ok_or_else(|| Error::InvalidInput(line.to_string()))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            reads,
+            timing: false,
+            start: None,
+        })
+    }
+
+    /// Set whether each read waits until as long after the first read as it originally
+    /// happened. Defaults to `false`.
+    pub fn with_timing(&mut self, timing: bool) -> &mut Self {
+        self.timing = timing;
+        self
+    }
+}
+
+impl Read for Replay {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let (at, bytes) = match self.reads.front_mut() {
+            Some(read) => read,
+            None => return Ok(0),
+        };
+        let start = *self.start.get_or_insert_with(Instant::now);
+        if self.timing {
+            if let Some(wait) = (start + *at).checked_duration_since(Instant::now()) {
+                std::thread::sleep(wait);
+            }
+        }
+
+        let len = bytes.len().min(buf.len());
+        buf[..len].copy_from_slice(&bytes[..len]);
+        bytes.drain(..len);
+        if bytes.is_empty() {
+            self.reads.pop_front();
+        }
+        Ok(len)
+    }
+}
+
+/// Parse a line logged by a [`Recorder`] into when it was read and what was read.
+fn parse_replay_line(line: &str) -> Option<(Duration, Vec<u8>)> {
+    let (millis, hex) = line.trim().split_once(' ')?;
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect::<Option<_>>()?;
+    Some((Duration::from_millis(millis.parse().ok()?), bytes))
+}
+
+/// Settings changing the behaviour of [`Clytia`]'s methods, set through its `with_*` methods.
+#[derive(Debug, Clone)]
+struct Settings {
+    finish_newline: bool,
+    progress_summary: bool,
+    menu_debounce: Option<Duration>,
+    menu_position: bool,
+    inline_selection: bool,
+    menu_height: Option<usize>,
+    menu_wrap: bool,
+    hide_cursor: bool,
+    compact_multichoice: bool,
+    spinner_frames: Vec<char>,
+    frame_interval: Duration,
+    theme: Theme,
+    /// The markers for selected and unselected options, padded to the same width.
+    checkboxes: (String, String),
+    untrimmed_validation: bool,
+    percent_precision: usize,
+    /// The style of progress bars' percentage, the theme's prompt colour if [`None`].
+    percent_style: Option<Style>,
+    max_bar_width: Option<usize>,
+    progress_fill: Option<Duration>,
+    max_suggestions: usize,
+    label_width: Option<usize>,
+    label_overflow: LabelOverflow,
+    empty_input: EmptyInput,
+    indent: usize,
+    rule_style: Style,
+    ascii_rules: bool,
+    key_hook: Option<KeyHook>,
+    on_finish: Option<FinishHook>,
+    accessible: bool,
+    pattern_digits: bool,
+    /// The file descriptor behind the output, see [`Clytia::with_output_fd`].
+    output_fd: Option<RawFd>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            finish_newline: true,
+            progress_summary: false,
+            menu_debounce: None,
+            menu_position: false,
+            inline_selection: false,
+            menu_height: None,
+            menu_wrap: true,
+            hide_cursor: true,
+            compact_multichoice: false,
+            spinner_frames: SPINNER_SYMBOLS.to_vec(),
+            frame_interval: Duration::from_millis(50),
+            theme: Theme::default(),
+            checkboxes: ("[X]".to_string(), "[ ]".to_string()),
+            untrimmed_validation: false,
+            percent_precision: 0,
+            percent_style: None,
+            max_bar_width: None,
+            progress_fill: None,
+            max_suggestions: 5,
+            label_width: None,
+            label_overflow: LabelOverflow::Overflow,
+            empty_input: EmptyInput::Error,
+            indent: 0,
+            rule_style: Style::new().dimmed(),
+            ascii_rules: false,
+            key_hook: None,
+            on_finish: None,
+            accessible: false,
+            pattern_digits: false,
+            output_fd: None,
+        }
+    }
+}
+
+impl Settings {
+    /// The marker for a selected or unselected option of a multichoice menu.
+    fn checkbox(&self, checked: bool) -> &str {
+        match checked {
+            true => &self.checkboxes.0,
+            false => &self.checkboxes.1,
+        }
+    }
+
+    /// The width left for output in a terminal `cols` wide, once it's indented.
+    fn width(&self, cols: u16) -> usize {
+        usize::from(cols).saturating_sub(self.indent)
+    }
+
+    /// The escape code hiding the cursor, or nothing if it should stay visible.
+    fn cursor_hide(&self) -> String {
+        match self.hide_cursor {
+            true => termion::cursor::Hide.to_string(),
+            false => String::new(),
+        }
+    }
+
+    /// The text of an input's prompt, padded or truncated to the label width if there is one.
+    fn label<S: std::fmt::Display>(&self, prompt: S) -> String {
+        let prompt = prompt.to_string();
+        let width = match self.label_width {
+            Some(width) => width,
+            None => return prompt,
+        };
+        let len = prompt.chars().count();
+        match self.label_overflow {
+            LabelOverflow::Truncate if len > width => match width {
+                0 => String::new(),
+                width => prompt.chars().take(width - 1).chain(['…']).collect(),
+            },
+            _ => format!("{:width$}", prompt),
+        }
+    }
+
+    /// Whether the process is running in the background of the terminal the output goes to,
+    /// or stdout's if its file descriptor isn't known. See [`in_background`].
+    fn in_background(&self) -> bool {
+        in_background(self.output_fd.unwrap_or(libc::STDOUT_FILENO))
+    }
+
+    /// Whether to ask and draw line by line, in accessible mode or in the background.
+    fn plain(&self) -> bool {
+        self.accessible || self.in_background()
+    }
+
+    /// Whether the output is a terminal, assumed so if its file descriptor isn't known.
+    fn is_terminal(&self) -> bool {
+        self.output_fd.map_or(true, |fd| termion::is_tty(&fd))
+    }
+
+    /// Pass a key that was read to the key hook, if there is one.
+    fn log_key(&self, key: Key) {
+        if let Some(KeyHook(hook)) = &self.key_hook {
+            hook(key);
+        }
+    }
+
+    /// Pass a finished spinner or progress bar to the hook set with [`Clytia::with_on_finish`].
+    fn finished<S: std::fmt::Display>(&self, text: S, success: bool, elapsed: Duration) {
+        if let Some(FinishHook(hook)) = &self.on_finish {
+            hook(&text.to_string(), success, elapsed);
+        }
+    }
+}
+
+/// The hook set with [`Clytia::with_key_hook`].
+#[derive(Clone)]
+struct KeyHook(Arc<dyn Fn(Key) + Send + Sync>);
+
+impl std::fmt::Debug for KeyHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("KeyHook")
+    }
+}
+
+/// The hook set with [`Clytia::with_on_finish`].
+#[derive(Clone)]
+struct FinishHook(Arc<FinishFn>);
+
+/// What [`FinishHook`] calls, with a task's text, whether it succeeded and how long it took.
+type FinishFn = dyn Fn(&str, bool, Duration) + Send + Sync;
+
+impl std::fmt::Debug for FinishHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("FinishHook")
+    }
+}
+
+/// The next key from `keys`, passed on to the key hook, or [`None`] once the input ends.
+///
+/// Make `keys` once per prompt rather than once per key: termion reads ahead, and whatever it
+/// has read past the key it hands out is lost along with the iterator.
+fn next_key<R: Read>(
+    keys: &mut termion::input::Keys<R>,
+    settings: &Settings,
+) -> io::Result<Option<Key>> {
+    let key = keys.next().transpose()?;
+    if let Some(key) = key {
+        settings.log_key(key);
+    }
+    Ok(key)
+}
+
+/// Key presses grouped into batches, menus redraw once per batch.
+///
+/// Without a debounce each key is a batch of its own. With one, the input is read in bulk
+/// after waiting out the debounce, so every key that arrived in the meantime is one batch.
+enum KeyBatches<R: Read> {
+    Single(termion::input::Keys<R>),
+    Debounced {
+        input: R,
+        debounce: Duration,
+        unparsed: Vec<u8>,
+        started: bool,
+    },
+}
+
+impl<R: Read> KeyBatches<R> {
+    fn new(input: R, debounce: Option<Duration>) -> Self {
+        match debounce {
+            None => Self::Single(input.keys()),
+            Some(debounce) => Self::Debounced {
+                input,
+                debounce,
+                unparsed: Vec::new(),
+                started: false,
+            },
+        }
+    }
+}
+
+impl<R: Read> Iterator for KeyBatches<R> {
+    type Item = io::Result<Vec<Key>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Single(keys) => keys.next().map(|key| key.map(|key| vec![key])),
+            Self::Debounced {
+                input,
+                debounce,
+                unparsed,
+                started,
+            } => {
+                if *started {
+                    std::thread::sleep(*debounce);
+                }
+                *started = true;
+
+                let mut buffer = [0; 1024];
+                let read = match input.read(&mut buffer) {
+                    Ok(0) => return None,
+                    Ok(read) => read,
+                    Err(e) => return Some(Err(e)),
+                };
+                unparsed.extend_from_slice(&buffer[..read]);
+
+                // Hold back an escape sequence that was cut off by the end of the read,
+                // a lone escape at the end is the escape key itself.
+                let complete = match unparsed.iter().rposition(|b| *b == b'\x1b') {
+                    Some(start)
+                        if start + 1 < unparsed.len()
+                            && escape_len(&unparsed[start..]).is_none() =>
+                    {
+                        start
+                    }
+                    _ => unparsed.len(),
+                };
+                let bytes: Vec<u8> = unparsed.drain(..complete).collect();
+                Some(bytes.as_slice().keys().collect())
+            }
+        }
+    }
+}
+
+/// The output of a [`Clytia`], writing to `inner`. Every line is indented by `indent` spaces,
+/// see [`Clytia::with_indent`], and `inner` is flushed after every write if
+/// [`Clytia::with_immediate_flush`] is on.
+///
+/// Lines start after a `\r` or `\n`, and the indent is written before the first character
+/// shown on the line, so escape codes moving the cursor or clearing the line don't push it
+/// along.
+#[derive(Debug)]
+struct Output<W> {
+    inner: W,
+    indent: usize,
+    immediate_flush: bool,
+    /// Everything written during [`Clytia::batch`], held back until it ends, or during
+    /// [`Clytia::capture`].
+    batch: Option<Vec<u8>>,
+    /// Whether nothing has been shown on the current line yet.
+    line_start: bool,
+    escape: Escape,
+}
+
+/// How far through an escape code [`Output`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Escape {
+    None,
+    Started,
+    Csi,
+}
+
+impl<W> Output<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            indent: 0,
+            immediate_flush: false,
+            batch: None,
+            line_start: true,
+            escape: Escape::None,
+        }
+    }
+}
+
+impl<W: Write> Write for Output<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = match (self.indent, &mut self.batch) {
+            (0, Some(batch)) => {
+                batch.extend_from_slice(buf);
+                buf.len()
+            }
+            (0, None) => self.inner.write(buf)?,
+            _ => self.write_indented(buf)?,
+        };
+        if self.immediate_flush && self.batch.is_none() {
+            self.inner.flush()?;
+        }
+
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.batch {
+            Some(_) => Ok(()),
+            None => self.inner.flush(),
+        }
+    }
+}
+
+impl<W: Write> Output<W> {
+    /// Write all of `buf`, adding the indent at the start of every line.
+    fn write_indented(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut indented = Vec::with_capacity(buf.len());
+        for byte in buf {
+            match (self.escape, byte) {
+                (Escape::Started, b'[') => self.escape = Escape::Csi,
+                (Escape::Started, _) => self.escape = Escape::None,
+                (Escape::Csi, 0x40..=0x7e) => self.escape = Escape::None,
+                (Escape::Csi, _) => {}
+                (Escape::None, b'\x1b') => self.escape = Escape::Started,
+                (Escape::None, b'\r' | b'\n') => self.line_start = true,
+                (Escape::None, _) => {
+                    if self.line_start {
+                        indented.extend(std::iter::repeat(b' ').take(self.indent));
+                        self.line_start = false;
+                    }
+                }
+            }
+            indented.push(*byte);
+        }
+        match &mut self.batch {
+            Some(batch) => batch.append(&mut indented),
+            None => self.inner.write_all(&indented)?,
+        }
+
+        Ok(buf.len())
+    }
+}
+
+/// Read a line typed by the user, or [`None`] if they cancelled with Ctrl+C or Ctrl+D, or the
+/// input ended before anything was typed. The terminal echoes their enter, so the cursor is at
+/// the start of a line again afterwards.
+fn read_input_line<R, W>(input: &mut R, output: &mut Output<W>) -> io::Result<Option<String>>
+where
+    R: Read,
+{
+    // Like termion's `read_line`, which gives an empty line at the end of the input instead,
+    // so prompts asking again after an empty answer would never stop.
+    let mut line = Vec::new();
+    let mut ended = true;
+    for byte in input.bytes() {
+        match byte? {
+            0 | 3 | 4 => return Ok(None),
+            0x7f => {
+                line.pop();
+            }
+            b'\n' | b'\r' => {
+                ended = false;
+                break;
+            }
+            byte => line.push(byte),
+        }
+    }
+    if ended && line.is_empty() {
+        return Ok(None);
+    }
+    output.line_start = true;
+
+    String::from_utf8(line)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Draw `prompt` and its `=>` ready for an answer, with `hint` between them, e.g. `[y/n]`.
+fn draw_prompt<W: Write>(
+    output: &mut W,
+    prompt: &str,
+    hint: Option<&str>,
+    theme: &Theme,
+) -> io::Result<()> {
+    write!(output, "{} ", prompt.color(theme.prompt))?;
+    if let Some(hint) = hint {
+        write!(output, "{} ", hint.color(theme.hint))?;
+    }
+    write!(output, "{} ", "=>".color(theme.prompt))?;
+    output.flush()
+}
+
+/// Redraw the line the user just answered in the error colour, with their rejected `answer`
+/// and the `reason` it was rejected, ready to ask them again. `hint` is anything shown between
+/// the prompt and its `=>`, e.g. `[y/n]`.
+fn draw_rejected<W: Write>(
+    output: &mut W,
+    prompt: &str,
+    hint: Option<&str>,
+    answer: &str,
+    reason: &str,
+    theme: &Theme,
+) -> io::Result<()> {
+    write!(
+        output,
+        "{}\r{}{} ",
+        termion::cursor::Up(1),
+        termion::clear::CurrentLine,
+        prompt.color(theme.error)
+    )?;
+    if let Some(hint) = hint {
+        write!(output, "{} ", hint.color(theme.error))?;
+    }
+    writeln!(
+        output,
+        "{} {} {}",
+        "=>".color(theme.error),
+        answer.trim().white(),
+        format!("({})", reason).color(theme.error)
+    )
+}
+
+/// The index above `index` in a list of `count` items, wrapping around to the bottom if `wrap`
+/// is set and staying on the first otherwise.
+fn previous_index(index: usize, count: usize, wrap: bool) -> usize {
+    match wrap {
+        true => (index + count - 1) % count,
+        false => index.saturating_sub(1),
+    }
+}
+
+/// The index below `index` in a list of `count` items, wrapping around to the top if `wrap` is
+/// set and staying on the last otherwise.
+fn next_index(index: usize, count: usize, wrap: bool) -> usize {
+    match wrap {
+        true => (index + 1) % count,
+        false => (index + 1).min(count - 1),
+    }
+}
+
+/// Move the cursor up `count` lines, clearing each line on the way.
+fn clear_lines<W: Write>(output: &mut W, count: usize) -> io::Result<()> {
+    for _ in 0..count {
+        write!(
+            output,
+            "{}{}",
+            termion::cursor::Up(1),
+            termion::clear::CurrentLine
+        )?;
+    }
+    Ok(())
+}
+
+/// How many lines a menu of `options_count` options takes up.
+fn menu_lines(options_count: usize, settings: &Settings) -> usize {
+    options_count + usize::from(settings.menu_position)
+}
+
+/// Draw the position of the highlighted option above a menu, e.g. `(3/150)`, if enabled.
+fn draw_menu_position<W: Write>(
+    output: &mut W,
+    highlighted: usize,
+    options_count: usize,
+    settings: &Settings,
+) -> io::Result<()> {
+    if settings.menu_position {
+        writeln!(
+            output,
+            "\r{}\r",
+            format!("({}/{})", highlighted + 1, options_count).dimmed()
+        )?;
+    }
+    Ok(())
+}
+
+/// How many options of [`Clytia::options_menu`] are shown at once, if there are more than fit
+/// in a terminal `height` rows tall, or in [`Clytia::with_menu_height`].
+fn options_menu_window(options_count: usize, height: usize, settings: &Settings) -> Option<usize> {
+    // Leave room for the prompt, the position and the lines saying how many are out of view.
+    let fits = height
+        .saturating_sub(3 + usize::from(settings.menu_position))
+        .max(1);
+    let rows = settings
+        .menu_height
+        .map_or(fits, |rows| rows.clamp(1, fits));
+    Some(rows).filter(|rows| options_count > *rows)
+}
+
+/// Draw the line above or below a windowed menu, saying how many options are out of view in
+/// the direction of `arrow`, or nothing if there are none.
+fn draw_out_of_view<W: Write>(output: &mut W, arrow: char, count: usize) -> io::Result<()> {
+    match count {
+        0 => writeln!(output, "\r"),
+        count => writeln!(output, "\r{}", format!("{} {} more", arrow, count).dimmed()),
+    }
+}
+
+/// Draw the options of [`Clytia::options_menu`], highlighting the selected one. If the menu
+/// is windowed to `window` rows only the options from `offset` are drawn, between lines
+/// saying how many are out of view.
+fn draw_options_menu<W, T>(
+    output: &mut W,
+    options: &[T],
+    selected: usize,
+    offset: usize,
+    window: Option<usize>,
+    settings: &Settings,
+) -> io::Result<()>
+where
+    W: Write,
+    T: std::fmt::Display,
+{
+    draw_menu_position(output, selected, options.len(), settings)?;
+    let (offset, rows) = match window {
+        Some(rows) => (offset, rows),
+        None => (0, options.len()),
+    };
+    if window.is_some() {
+        draw_out_of_view(output, '↑', offset)?;
+    }
+    for (index, option) in options.iter().enumerate().skip(offset).take(rows) {
+        if index == selected {
+            writeln!(
+                output,
+                "{}{}\r",
+                format!("=> {}", option).color(settings.theme.highlight),
+                settings.cursor_hide()
+            )?;
+        } else {
+            writeln!(output, "   {}{}\r", option, settings.cursor_hide())?;
+        }
+    }
+    if window.is_some() {
+        draw_out_of_view(output, '↓', options.len().saturating_sub(offset + rows))?;
+    }
+    Ok(())
+}
+
+/// The key loop behind [`Clytia::options_menu`], expects the output to already be in raw mode.
+/// Options that don't fit in a terminal `height` rows tall are scrolled through. Returns the
+/// index of the selected option.
+fn run_options_menu<R, W, T>(
+    input: &mut R,
+    output: &mut W,
+    options: &[T],
+    height: usize,
+    settings: &Settings,
+) -> Result<usize>
+where
+    R: Read,
+    W: Write,
+    T: std::fmt::Display,
+{
+    run_options_menu_from(input, output, options, 0, height, settings)
+}
+
+/// [`run_options_menu`], starting with the option at `selected` highlighted.
+fn run_options_menu_from<R, W, T>(
+    input: &mut R,
+    output: &mut W,
+    options: &[T],
+    mut selected: usize,
+    height: usize,
+    settings: &Settings,
+) -> Result<usize>
+where
+    R: Read,
+    W: Write,
+    T: std::fmt::Display,
+{
+    let options_count = options.len();
+    let window = options_menu_window(options_count, height, settings);
+    let menu_lines = menu_lines(window.map_or(options_count, |rows| rows + 2), settings);
+    let rows = window.unwrap_or(options_count);
+    let mut offset = scroll_offset(0, selected, rows);
+
+    if settings.inline_selection {
+        // Scroll in room for the menu first, so the terminal doesn't scroll while it's drawn
+        // and the saved position still points at the end of the prompt line once it's cleared.
+        // Raw mode leaves the column alone on a newline.
+        write!(
+            output,
+            "{}{}{}\n\r",
+            "\n".repeat(menu_lines + 1),
+            termion::cursor::Up(menu_lines as u16 + 1),
+            termion::cursor::Save
+        )?;
+    }
+    draw_options_menu(output, options, selected, offset, window, settings)?;
+    'keys: for batch in KeyBatches::new(input, settings.menu_debounce) {
+        for key in batch? {
+            settings.log_key(key);
+            match key {
+                Key::Up => selected = previous_index(selected, options_count, settings.menu_wrap),
+                Key::Down => selected = next_index(selected, options_count, settings.menu_wrap),
+                Key::Char('\n') => break 'keys,
+                Key::Esc | Key::Ctrl('c') => {
+                    clear_lines(output, menu_lines)?;
+                    match settings.inline_selection {
+                        true => write!(output, "{}\r\n", termion::cursor::Restore)?,
+                        false => write!(output, "\r")?,
+                    }
+                    output.flush()?;
+                    return Err(Error::Cancelled);
+                }
+                _ => {}
+            }
+        }
+
+        offset = scroll_offset(offset, selected, rows);
+        clear_lines(output, menu_lines)?;
+        write!(output, "\r")?;
+        draw_options_menu(output, options, selected, offset, window, settings)?;
+    }
+
+    clear_lines(output, menu_lines)?;
+    if settings.inline_selection {
+        write!(
+            output,
+            "{} {}\r\n",
+            termion::cursor::Restore,
+            options[selected].color(settings.theme.success)
+        )?;
+    } else {
+        writeln!(
+            output,
+            "{}",
+            format!("\r=> {}\r", options[selected]).color(settings.theme.success)
+        )?;
+    }
+
+    Ok(selected)
+}
+
+/// The indexes of the `options` containing `filter`, ignoring case.
+fn filter_options<T: std::fmt::Display>(options: &[T], filter: &str) -> Vec<usize> {
+    let filter = filter.to_lowercase();
+    options
+        .iter()
+        .enumerate()
+        .filter(|(_, option)| option.to_string().to_lowercase().contains(&filter))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Draw the menu of [`Clytia::options_menu_filtered`], the `filter` typed so far above the
+/// options `shown` for matching it. Returns how many lines were drawn.
+fn draw_filtered_menu<W, T>(
+    output: &mut W,
+    filter: &str,
+    shown: &[T],
+    highlighted: usize,
+    offset: usize,
+    height: usize,
+    settings: &Settings,
+) -> io::Result<usize>
+where
+    W: Write,
+    T: std::fmt::Display,
+{
+    writeln!(
+        output,
+        "\r{} {}{}\r",
+        "/".color(settings.theme.prompt),
+        filter,
+        settings.cursor_hide()
+    )?;
+    if shown.is_empty() {
+        writeln!(output, "   {}\r", "(no matches)".dimmed())?;
+        return Ok(2);
+    }
+
+    // The filter takes up a line of the terminal.
+    let window = options_menu_window(shown.len(), height.saturating_sub(1), settings);
+    draw_options_menu(output, shown, highlighted, offset, window, settings)?;
+
+    Ok(1 + menu_lines(window.map_or(shown.len(), |rows| rows + 2), settings))
+}
+
+/// The key loop behind [`Clytia::options_menu_filtered`], expects the output to already be in
+/// raw mode. Returns the index in `options` of the selected option.
+fn run_filtered_menu<R, W, T>(
+    input: &mut R,
+    output: &mut W,
+    options: &[T],
+    height: usize,
+    settings: &Settings,
+) -> Result<usize>
+where
+    R: Read,
+    W: Write,
+    T: std::fmt::Display,
+{
+    let mut filter = String::new();
+    let mut matches = filter_options(options, &filter);
+    let mut highlighted = 0;
+    let mut offset = 0;
+    let mut picked = false;
+    let shown = |matches: &[usize]| -> Vec<&T> { matches.iter().map(|&i| &options[i]).collect() };
+    let mut drawn = draw_filtered_menu(
+        output,
+        &filter,
+        &shown(&matches),
+        highlighted,
+        offset,
+        height,
+        settings,
+    )?;
+
+    'keys: for batch in KeyBatches::new(input, settings.menu_debounce) {
+        for key in batch? {
+            settings.log_key(key);
+            let count = matches.len().max(1);
+            match key {
+                Key::Up => highlighted = previous_index(highlighted, count, settings.menu_wrap),
+                Key::Down => highlighted = next_index(highlighted, count, settings.menu_wrap),
+                Key::Char('\n') if !matches.is_empty() => {
+                    picked = true;
+                    break 'keys;
+                }
+                Key::Esc | Key::Ctrl('c') => {
+                    clear_lines(output, drawn)?;
+                    write!(output, "\r")?;
+                    output.flush()?;
+                    return Err(Error::Cancelled);
+                }
+                Key::Backspace => {
+                    if filter.pop().is_some() {
+                        matches = filter_options(options, &filter);
+                        (highlighted, offset) = (0, 0);
+                    }
+                }
+                Key::Char(c) if !c.is_control() => {
+                    filter.push(c);
+                    matches = filter_options(options, &filter);
+                    (highlighted, offset) = (0, 0);
+                }
+                _ => {}
+            }
+        }
+
+        let window = options_menu_window(matches.len(), height.saturating_sub(1), settings);
+        let rows = window.unwrap_or(matches.len()).max(1);
+        offset = scroll_offset(offset, highlighted, rows);
+        clear_lines(output, drawn)?;
+        write!(output, "\r")?;
+        drawn = draw_filtered_menu(
+            output,
+            &filter,
+            &shown(&matches),
+            highlighted,
+            offset,
+            height,
+            settings,
+        )?;
+    }
+
+    clear_lines(output, drawn)?;
+    if !picked {
+        // The input ended first, maybe with nothing matching the filter.
+        write!(output, "\r")?;
+        output.flush()?;
+        return Err(Error::NonOptionalInput);
+    }
+    let selected = matches[highlighted];
+    writeln!(
+        output,
+        "{}",
+        format!("\r=> {}\r", options[selected]).color(settings.theme.success)
+    )?;
+
+    Ok(selected)
+}
+
+/// The paths of the nodes of a [`Clytia::tree_select`] menu that are shown, those in the
+/// `expanded` branches, top to bottom.
+fn visible_tree_nodes<T>(root: &TreeNode<T>, expanded: &HashSet<Vec<usize>>) -> Vec<Vec<usize>> {
+    let mut shown = vec![Vec::new()];
+    let mut index = 0;
+    while index < shown.len() {
+        let path = shown[index].clone();
+        index += 1;
+        if expanded.contains(&path) {
+            let children = (0..root.get(&path).children().len()).map(|child| {
+                let mut child_path = path.clone();
+                child_path.push(child);
+                child_path
+            });
+            // The children go straight after their branch.
+            shown.splice(index..index, children);
+        }
+    }
+    shown
+}
+
+/// Every leaf of a [`Clytia::tree_select`] tree with its path, labelled with the branches
+/// leading to it, e.g. `src / bin / main.rs`.
+fn tree_leaves<T>(root: &TreeNode<T>) -> Vec<(Vec<usize>, String)> {
+    let all: HashSet<Vec<usize>> = tree_branches(root, Vec::new()).into_iter().collect();
+    visible_tree_nodes(root, &all)
+        .into_iter()
+        .filter(|path| matches!(root.get(path), TreeNode::Leaf { .. }))
+        .map(|path| {
+            let labels: Vec<&str> = (0..=path.len())
+                .map(|depth| root.get(&path[..depth]).label())
+                .collect();
+            (path, labels.join(" / "))
+        })
+        .collect()
+}
+
+/// The paths of `node`, at `path`, and every node under it that could be a branch.
+fn tree_branches<T>(node: &TreeNode<T>, path: Vec<usize>) -> Vec<Vec<usize>> {
+    let mut branches = Vec::new();
+    for (index, child) in node.children().iter().enumerate() {
+        let mut child_path = path.clone();
+        child_path.push(index);
+        branches.extend(tree_branches(child, child_path));
+    }
+    branches.push(path);
+    branches
+}
+
+/// The line shown for the node at `path` in a [`Clytia::tree_select`] menu, indented by its
+/// depth after a `▶` or `▼` if it's a branch.
+fn tree_line<T>(root: &TreeNode<T>, path: &[usize], expanded: &HashSet<Vec<usize>>) -> String {
+    let node = root.get(path);
+    let toggle = match node {
+        TreeNode::Leaf { .. } => ' ',
+        TreeNode::Branch { .. } if expanded.contains(path) => '▼',
+        TreeNode::Branch { .. } => '▶',
+    };
+    format!("{}{} {}", "  ".repeat(path.len()), toggle, node.label())
+}
+
+/// Draw the `lines` of a [`Clytia::tree_select`] menu, windowed to fit a terminal `height`
+/// rows tall. Returns how many lines were drawn.
+fn draw_tree_select<W: Write>(
+    output: &mut W,
+    lines: &[String],
+    highlighted: usize,
+    offset: usize,
+    height: usize,
+    settings: &Settings,
+) -> io::Result<usize> {
+    let window = options_menu_window(lines.len(), height, settings);
+    draw_options_menu(output, lines, highlighted, offset, window, settings)?;
+    Ok(menu_lines(
+        window.map_or(lines.len(), |rows| rows + 2),
+        settings,
+    ))
+}
+
+/// The key loop behind [`Clytia::tree_select`], expects the output to already be in raw mode.
+/// Returns the path of the picked leaf.
+fn run_tree_select<R, W, T>(
+    input: &mut R,
+    output: &mut W,
+    root: &TreeNode<T>,
+    height: usize,
+    settings: &Settings,
+) -> Result<Vec<usize>>
+where
+    R: Read,
+    W: Write,
+{
+    let tree_lines = |shown: &[Vec<usize>], expanded: &HashSet<Vec<usize>>| -> Vec<String> {
+        shown
+            .iter()
+            .map(|path| tree_line(root, path, expanded))
+            .collect()
+    };
+    let mut expanded = HashSet::from([Vec::new()]);
+    let mut shown = visible_tree_nodes(root, &expanded);
+    let mut highlighted = 0;
+    let mut offset = 0;
+    let mut picked = false;
+    let lines = tree_lines(&shown, &expanded);
+    let mut drawn = draw_tree_select(output, &lines, highlighted, offset, height, settings)?;
+
+    'keys: for batch in KeyBatches::new(input, settings.menu_debounce) {
+        for key in batch? {
+            settings.log_key(key);
+            let path = &shown[highlighted];
+            let is_branch = matches!(root.get(path), TreeNode::Branch { .. });
+            let is_expanded = expanded.contains(path);
+            match key {
+                Key::Up => {
+                    highlighted = previous_index(highlighted, shown.len(), settings.menu_wrap)
+                }
+                Key::Down => highlighted = next_index(highlighted, shown.len(), settings.menu_wrap),
+                Key::Right if is_branch && !is_expanded => {
+                    expanded.insert(path.clone());
+                }
+                Key::Right if is_branch && !root.get(path).children().is_empty() => {
+                    highlighted += 1;
+                }
+                Key::Left if is_expanded => {
+                    expanded.remove(path);
+                }
+                Key::Left if !path.is_empty() => {
+                    let parent = &path[..path.len() - 1];
+                    highlighted = shown
+                        .iter()
+                        .position(|shown_path| shown_path == parent)
+                        .unwrap_or(highlighted);
+                }
+                Key::Char('\n') if !is_branch => {
+                    picked = true;
+                    break 'keys;
+                }
+                Key::Char('\n') if is_expanded => {
+                    expanded.remove(path);
+                }
+                Key::Char('\n') => {
+                    expanded.insert(path.clone());
+                }
+                Key::Esc | Key::Ctrl('c') => {
+                    clear_lines(output, drawn)?;
+                    write!(output, "\r")?;
+                    output.flush()?;
+                    return Err(Error::Cancelled);
+                }
+                _ => {}
+            }
+            shown = visible_tree_nodes(root, &expanded);
+        }
+
+        let lines = tree_lines(&shown, &expanded);
+        let rows = options_menu_window(lines.len(), height, settings).unwrap_or(lines.len());
+        offset = scroll_offset(offset, highlighted, rows);
+        clear_lines(output, drawn)?;
+        write!(output, "\r")?;
+        drawn = draw_tree_select(output, &lines, highlighted, offset, height, settings)?;
+    }
+
+    clear_lines(output, drawn)?;
+    write!(output, "\r")?;
+    if !picked {
+        // The input ended before a leaf was picked.
+        output.flush()?;
+        return Err(Error::NonOptionalInput);
+    }
+    let path = shown[highlighted].clone();
+    writeln!(
+        output,
+        "{}",
+        format!("\r=> {}\r", root.get(&path).label()).color(settings.theme.success)
+    )?;
+
+    Ok(path)
+}
+
+/// The menu behind [`Clytia::numbered_select`], also shown in accessible mode in place of
+/// [`Clytia::options_menu`] and [`Clytia::multichoice`]. Reads the number of the option
+/// picked, or any number of them if `multiple` is set, after the options have been listed
+/// with [`draw_numbered`].
+fn run_numbered_menu<R, W, T>(
+    input: &mut R,
+    output: &mut Output<W>,
+    options: &[T],
+    multiple: bool,
+    settings: &Settings,
+) -> Result<Vec<usize>>
+where
+    R: Read,
+    W: Write,
+    T: std::fmt::Display,
+{
+    let (prompt, expected) = match multiple {
+        true => ("Numbers, separated by commas", "numbers"),
+        false => ("Number", "a number"),
+    };
+    let theme = settings.theme;
+
+    loop {
+        write!(
+            output,
+            "{} {} ",
+            prompt.color(theme.prompt),
+            "=>".color(theme.prompt)
+        )?;
+        output.flush()?;
+
+        let answer = match read_input_line(input, output)? {
+            Some(answer) => answer,
+            None => return Err(Error::NonOptionalInput),
+        };
+        match parse_numbered(&answer, options.len(), multiple) {
+            Some(selected) if multiple => {
+                draw_multichoice_result(output, options, &selected, settings)?;
+                return Ok(selected);
+            }
+            Some(selected) => {
+                let picked = format!("=> {}", options[selected[0]]);
+                writeln!(output, "{}", picked.color(theme.success))?;
+                return Ok(selected);
+            }
+            None => {
+                let error = format!("(expected {} from 1 to {})", expected, options.len());
+                writeln!(output, "{}", error.color(theme.error))?;
+            }
+        }
+    }
+}
+
+/// Ask for a line until `parse` accepts it, printing why it didn't on a line of its own. This
+/// is how inputs that otherwise redraw as the user types ask in accessible mode. `hint` is
+/// shown between the prompt and its `=>`, e.g. `[y/n]`.
+fn run_plain_input<R, W, T, F>(
+    input: &mut R,
+    output: &mut Output<W>,
+    prompt: &str,
+    hint: Option<&str>,
+    settings: &Settings,
+    mut parse: F,
+) -> Result<T>
+where
+    R: Read,
+    W: Write,
+    F: FnMut(&str) -> std::result::Result<T, String>,
+{
+    let theme = settings.theme;
+    loop {
+        draw_prompt(output, prompt, hint, &theme)?;
+
+        let answer = match read_input_line(input, output)? {
+            Some(answer) => answer,
+            None => return Err(Error::NonOptionalInput),
+        };
+        match parse(answer.trim()) {
+            Ok(value) => return Ok(value),
+            Err(reason) => writeln!(output, "{}", format!("({})", reason).color(theme.error))?,
+        }
+    }
+}
+
+/// List `options` numbered from 1 for [`run_numbered_menu`], e.g. `1) cats`, with `separator`
+/// between them.
+fn draw_numbered<W, T>(output: &mut W, options: &[T], separator: &str) -> io::Result<()>
+where
+    W: Write,
+    T: std::fmt::Display,
+{
+    let numbered: Vec<String> = options
+        .iter()
+        .enumerate()
+        .map(|(index, option)| format!("{}) {}", index + 1, option))
+        .collect();
+    writeln!(output, "{}", numbered.join(separator))
+}
+
+/// Lay out the names and descriptions of [`Clytia::options_menu_two_column`] as menu options,
+/// padding the names to the same width and cutting descriptions short to fit in `cols`
+/// alongside the `=> ` in front of the highlighted option.
+fn format_two_columns<T>(items: &[(String, String, T)], cols: usize) -> Vec<String> {
+    let name_width = items
+        .iter()
+        .map(|(name, _, _)| name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let room = cols.saturating_sub(name_width + 5);
+
+    items
+        .iter()
+        .map(|(name, description, _)| {
+            let description: String = if description.chars().count() <= room {
+                description.clone()
+            } else {
+                match room {
+                    0 => String::new(),
+                    room => description.chars().take(room - 1).chain(['…']).collect(),
+                }
+            };
+            format!("{:<width$}  {}", name, description, width = name_width)
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+/// How many options of a multichoice menu are shown at once, if there are more than
+/// fit in [`Clytia::with_menu_height`].
+fn multichoice_window(options_count: usize, settings: &Settings) -> Option<usize> {
+    settings
+        .menu_height
+        .map(|height| height.max(1))
+        .filter(|height| options_count > *height)
+}
+
+/// Move the first shown option, `offset`, as little as needed for `highlighted` to be one of
+/// the `rows` shown.
+fn scroll_offset(offset: usize, highlighted: usize, rows: usize) -> usize {
+    offset.clamp((highlighted + 1).saturating_sub(rows), highlighted)
+}
+
+/// Draw the options of [`Clytia::multichoice`] with their checkboxes, highlighting the current
+/// one. If the menu is windowed only the options from `offset` are drawn, between lines
+/// saying how many are out of view.
+fn draw_multichoice<W, T>(
+    output: &mut W,
+    options: &[T],
+    highlighted: usize,
+    selected: &HashSet<usize>,
+    offset: usize,
+    settings: &Settings,
+) -> io::Result<()>
+where
+    W: Write,
+    T: std::fmt::Display,
+{
+    draw_menu_position(output, highlighted, options.len(), settings)?;
+    let window = multichoice_window(options.len(), settings);
+    let (offset, rows) = match window {
+        Some(rows) => (offset, rows),
+        None => (0, options.len()),
+    };
+    let below = options.len().saturating_sub(offset + rows);
+    if window.is_some() {
+        draw_out_of_view(output, '↑', offset)?;
+    }
+    for (index, option) in options.iter().enumerate().skip(offset).take(rows) {
+        let checkbox = settings.checkbox(selected.contains(&index));
+        if highlighted == index {
+            let line = format!("{} {}", checkbox, option);
+            writeln!(output, "\r{}", line.color(settings.theme.highlight))?;
+        } else {
+            writeln!(output, "\r{} {}", checkbox, option)?;
+        }
+    }
+    if window.is_some() {
+        draw_out_of_view(output, '↓', below)?;
+    }
+    Ok(())
+}
+
+/// The key loop behind [`Clytia::multichoice`], expects the output to already be in raw mode.
+/// Starts with the `selected` indexes ticked, and returns the indexes of the selected options,
+/// in order.
+fn run_multichoice<R, W, T>(
+    input: &mut R,
+    output: &mut W,
+    options: &[T],
+    mut selected: HashSet<usize>,
+    settings: &Settings,
+) -> Result<Vec<usize>>
+where
+    R: Read,
+    W: Write,
+    T: std::fmt::Display,
+{
+    let mut keys = KeyBatches::new(input, settings.menu_debounce);
+    let mut highlighted = 0;
+    let exit = select_multichoice(
+        &mut keys,
+        output,
+        options,
+        settings,
+        &mut highlighted,
+        &mut selected,
+    )?;
+    if exit == MenuExit::Cancelled {
+        return Err(Error::Cancelled);
+    }
+
+    let mut selected: Vec<usize> = selected.into_iter().collect();
+    selected.sort_unstable();
+    draw_multichoice_result(output, options, &selected, settings)?;
+
+    Ok(selected)
+}
+
+/// Like [`run_multichoice`], but the user can cancel. Returns the indexes of the options
+/// selected either way, and whether they were confirmed. Nothing is shown once the menu is
+/// cleared if it was cancelled.
+fn run_multichoice_cancellable<R, W, T>(
+    input: &mut R,
+    output: &mut W,
+    options: &[T],
+    settings: &Settings,
+) -> Result<(Vec<usize>, bool)>
+where
+    R: Read,
+    W: Write,
+    T: std::fmt::Display,
+{
+    let mut keys = KeyBatches::new(input, settings.menu_debounce);
+    let mut highlighted = 0;
+    let mut selected = HashSet::new();
+    let confirmed = select_multichoice(
+        &mut keys,
+        output,
+        options,
+        settings,
+        &mut highlighted,
+        &mut selected,
+    )? != MenuExit::Cancelled;
+
+    let mut selected: Vec<usize> = selected.into_iter().collect();
+    selected.sort_unstable();
+    if confirmed {
+        draw_multichoice_result(output, options, &selected, settings)?;
+    }
+
+    Ok((selected, confirmed))
+}
+
+/// Like [`run_multichoice`], but asks the user to confirm their selection before it's
+/// returned, going back to the menu if they don't.
+fn run_multichoice_confirmed<R, W, T>(
+    input: &mut R,
+    output: &mut W,
+    options: &[T],
+    settings: &Settings,
+) -> Result<Vec<usize>>
+where
+    R: Read,
+    W: Write,
+    T: std::fmt::Display,
+{
+    // One iterator for the menu and the confirmation, so no keys typed ahead are lost between them.
+    let mut keys = KeyBatches::new(input, settings.menu_debounce);
+    let mut highlighted = 0;
+    let mut selected = HashSet::new();
+    loop {
+        match select_multichoice(
+            &mut keys,
+            output,
+            options,
+            settings,
+            &mut highlighted,
+            &mut selected,
+        )? {
+            MenuExit::Confirmed => {}
+            MenuExit::Cancelled => return Err(Error::Cancelled),
+            MenuExit::Ended => return Err(Error::NonOptionalInput),
+        }
+
+        let mut sorted: Vec<usize> = selected.iter().copied().collect();
+        sorted.sort_unstable();
+        let names: Vec<String> = sorted.iter().map(|i| options[*i].to_string()).collect();
+        let names = if names.is_empty() {
+            "nothing".to_string()
+        } else {
+            names.join(", ")
+        };
+        write!(
+            output,
+            "\r{} {} ",
+            format!("You selected {}, confirm?", names).color(settings.theme.prompt),
+            "[Y/n]".color(settings.theme.hint)
+        )?;
+        output.flush()?;
+
+        let confirmed = 'confirm: loop {
+            let batch = match keys.next() {
+                Some(batch) => batch?,
+                None => return Err(Error::NonOptionalInput),
+            };
+            for key in batch {
+                settings.log_key(key);
+                match key {
+                    Key::Char('y' | 'Y' | '\n') => break 'confirm true,
+                    Key::Char('n' | 'N') => break 'confirm false,
+                    Key::Esc | Key::Ctrl('c') => return Err(Error::Cancelled),
+                    _ => {}
+                }
+            }
+        };
+        write!(output, "{}\r", termion::clear::CurrentLine)?;
+
+        if confirmed {
+            draw_multichoice_result(output, options, &sorted, settings)?;
+            return Ok(sorted);
+        }
+    }
+}
+
+/// The key loop behind [`Clytia::multichoice_limited`], expects the output to already be in raw
+/// mode. Enter is refused with a warning until the number selected is within `range`.
+fn run_multichoice_limited<R, W, T>(
+    input: &mut R,
+    output: &mut W,
+    options: &[T],
+    range: RangeInclusive<usize>,
+    settings: &Settings,
+) -> Result<Vec<usize>>
+where
+    R: Read,
+    W: Write,
+    T: std::fmt::Display,
+{
+    let mut keys = KeyBatches::new(input, settings.menu_debounce);
+    let mut highlighted = 0;
+    let mut selected = HashSet::new();
+    let mut warned = false;
+    loop {
+        let exit = select_multichoice(
+            &mut keys,
+            output,
+            options,
+            settings,
+            &mut highlighted,
+            &mut selected,
+        )?;
+        if warned {
+            clear_lines(output, 1)?;
+            write!(output, "\r")?;
+        }
+        match exit {
+            MenuExit::Confirmed => {}
+            MenuExit::Cancelled => return Err(Error::Cancelled),
+            // Otherwise the selection would be refused again forever.
+            MenuExit::Ended => return Err(Error::NonOptionalInput),
+        }
+
+        let warning = match limit_warning(selected.len(), &range) {
+            Some(warning) => warning,
+            None => break,
+        };
+        writeln!(output, "\r{}\r", warning.color(settings.theme.error))?;
+        warned = true;
+    }
+
+    let mut selected: Vec<usize> = selected.into_iter().collect();
+    selected.sort_unstable();
+    draw_multichoice_result(output, options, &selected, settings)?;
+
+    Ok(selected)
+}
+
+/// What to tell the user when `count` options are selected in [`Clytia::multichoice_limited`],
+/// if that's outside of `range`.
+fn limit_warning(count: usize, range: &RangeInclusive<usize>) -> Option<String> {
+    let noun = |n: usize| match n {
+        1 => "option",
+        _ => "options",
+    };
+    if count < *range.start() {
+        let missing = *range.start() - count;
+        Some(format!("Select {} more {}", missing, noun(missing)))
+    } else if count > *range.end() {
+        let extra = count - *range.end();
+        Some(format!("Deselect {} {}", extra, noun(extra)))
+    } else {
+        None
+    }
+}
+
+/// How the user left a menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MenuExit {
+    /// They pressed enter.
+    Confirmed,
+    /// They backed out with escape or Ctrl-C.
+    Cancelled,
+    /// The input ended first.
+    Ended,
+}
+
+/// The key loop of the multichoice menus, reading from `keys` and starting from and updating
+/// the `highlighted` option and the `selected` ones. The menu is cleared again once the user
+/// leaves it, returning how they did.
+fn select_multichoice<R, W, T>(
+    keys: &mut KeyBatches<R>,
+    output: &mut W,
+    options: &[T],
+    settings: &Settings,
+    highlighted: &mut usize,
+    selected: &mut HashSet<usize>,
+) -> Result<MenuExit>
+where
+    R: Read,
+    W: Write,
+    T: std::fmt::Display,
+{
+    let options_count = options.len();
+    let window = multichoice_window(options_count, settings);
+    let menu_lines = menu_lines(window.map_or(options_count, |rows| rows + 2), settings);
+    let rows = window.unwrap_or(options_count);
+    let mut offset = scroll_offset(0, *highlighted, rows);
+    let mut exit = MenuExit::Ended;
+
+    write!(output, "{}", settings.cursor_hide())?;
+    draw_multichoice(output, options, *highlighted, selected, offset, settings)?;
+
+    'keys: for batch in keys.by_ref() {
+        for key in batch? {
+            settings.log_key(key);
+            match key {
+                Key::Up => {
+                    *highlighted = previous_index(*highlighted, options_count, settings.menu_wrap)
+                }
+                Key::Down => {
+                    *highlighted = next_index(*highlighted, options_count, settings.menu_wrap)
+                }
+                Key::Char(' ') if selected.contains(highlighted) => {
+                    selected.remove(highlighted);
+                }
+                Key::Char(' ') => {
+                    selected.insert(*highlighted);
+                }
+                Key::Char('\n') => {
+                    exit = MenuExit::Confirmed;
+                    break 'keys;
+                }
+                Key::Esc | Key::Ctrl('c') => {
+                    exit = MenuExit::Cancelled;
+                    break 'keys;
+                }
+                _ => {}
+            }
+        }
+
+        offset = scroll_offset(offset, *highlighted, rows);
+        clear_lines(output, menu_lines)?;
+        write!(output, "\r")?;
+        draw_multichoice(output, options, *highlighted, selected, offset, settings)?;
+    }
+
+    clear_lines(output, menu_lines)?;
+    write!(output, "\r")?;
+
+    Ok(exit)
+}
+
+/// Show the options picked in a multichoice menu once it's done.
+fn draw_multichoice_result<W, T>(
+    output: &mut W,
+    options: &[T],
+    selected: &[usize],
+    settings: &Settings,
+) -> io::Result<()>
+where
+    W: Write,
+    T: std::fmt::Display,
+{
+    if settings.compact_multichoice {
+        if !selected.is_empty() {
+            let names: Vec<String> = selected.iter().map(|i| options[*i].to_string()).collect();
+            writeln!(
+                output,
+                "{}",
+                format!("\rSelected: {}\r", names.join(", ")).color(settings.theme.success)
+            )?;
+        }
+    } else {
+        for index in selected {
+            writeln!(
+                output,
+                "{}",
+                format!("\r{} {}\r", settings.checkbox(true), options[*index])
+                    .color(settings.theme.success)
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Draw the items of [`Clytia::quantity_select`] with their checkboxes and quantities,
+/// highlighting the current one.
+fn draw_quantity_select<W, T>(
+    output: &mut W,
+    items: &[T],
+    highlighted: usize,
+    included: &HashSet<usize>,
+    quantities: &[u32],
+    settings: &Settings,
+) -> io::Result<()>
+where
+    W: Write,
+    T: std::fmt::Display,
+{
+    draw_menu_position(output, highlighted, items.len(), settings)?;
+    for (index, item) in items.iter().enumerate() {
+        let line = format!(
+            "{} {} × {}",
+            settings.checkbox(included.contains(&index)),
+            item,
+            quantities[index]
+        );
+        if highlighted == index {
+            writeln!(output, "\r{}", line.color(settings.theme.highlight))?;
+        } else {
+            writeln!(output, "\r{}", line)?;
+        }
+    }
+    Ok(())
+}
+
+/// The key loop behind [`Clytia::quantity_select`], expects the output to already be in raw
+/// mode. Returns the indexes of the included items with a quantity of at least 1, in order,
+/// along with their quantities.
+fn run_quantity_select<R, W, T>(
+    input: &mut R,
+    output: &mut W,
+    items: &[T],
+    settings: &Settings,
+) -> Result<Vec<(usize, u32)>>
+where
+    R: Read,
+    W: Write,
+    T: std::fmt::Display,
+{
+    let items_count = items.len();
+    let menu_lines = menu_lines(items_count, settings);
+    let mut highlighted = 0;
+    let mut included = HashSet::new();
+    let mut quantities = vec![1; items_count];
+
+    write!(output, "{}", settings.cursor_hide())?;
+    draw_quantity_select(output, items, highlighted, &included, &quantities, settings)?;
+
+    'keys: for batch in KeyBatches::new(input, settings.menu_debounce) {
+        for key in batch? {
+            settings.log_key(key);
+            match key {
+                Key::Up => {
+                    highlighted = previous_index(highlighted, items_count, settings.menu_wrap)
+                }
+                Key::Down => highlighted = next_index(highlighted, items_count, settings.menu_wrap),
+                Key::Left => quantities[highlighted] = quantities[highlighted].saturating_sub(1),
+                Key::Right => quantities[highlighted] = quantities[highlighted].saturating_add(1),
+                Key::Char(' ') if included.contains(&highlighted) => {
+                    included.remove(&highlighted);
+                }
+                Key::Char(' ') => {
+                    included.insert(highlighted);
+                }
+                Key::Char('\n') => break 'keys,
+                _ => {}
+            }
+        }
+
+        clear_lines(output, menu_lines)?;
+        write!(output, "\r")?;
+        draw_quantity_select(output, items, highlighted, &included, &quantities, settings)?;
+    }
+
+    clear_lines(output, menu_lines)?;
+    write!(output, "\r")?;
+
+    let selected: Vec<(usize, u32)> = (0..items_count)
+        .filter(|index| included.contains(index) && quantities[*index] > 0)
+        .map(|index| (index, quantities[index]))
+        .collect();
+    for (index, quantity) in &selected {
+        writeln!(
+            output,
+            "{}",
+            format!(
+                "\r{} {} × {}\r",
+                settings.checkbox(true),
+                items[*index],
+                quantity
+            )
+            .color(settings.theme.success)
+        )?;
+    }
+
+    Ok(selected)
+}
+
+/// Draw one frame of a [`Clytia::nested_spinner`], replacing the `drawn` subtask lines
+/// of the previous frame. Returns how many subtask lines are drawn now.
+fn draw_nested_spinner<W, S>(
+    output: &mut W,
+    symbol: char,
+    text: S,
+    subtasks: &[String],
+    drawn: usize,
+    color: AnsiColors,
+) -> io::Result<usize>
+where
+    W: Write,
+    S: std::fmt::Display,
+{
+    write!(output, "{}", termion::clear::CurrentLine)?;
+    clear_lines(output, drawn)?;
+    write!(output, "\r{} {}", symbol.color(color), text)?;
+    for (depth, subtask) in subtasks.iter().enumerate() {
+        write!(
+            output,
+            "\n{}\r{}{} {}",
+            termion::clear::CurrentLine,
+            "  ".repeat(depth + 1),
+            symbol.color(color),
+            subtask
+        )?;
+    }
+    output.flush()?;
+
+    Ok(subtasks.len())
+}
+
+/// Wait for an answer to [`Clytia::confirm_all`], on an output already in raw mode.
+fn run_confirm_all<R, W, S>(
+    input: &mut R,
+    output: &mut W,
+    prompt: S,
+    settings: &Settings,
+) -> Result<ConfirmAll>
+where
+    R: Read,
+    W: Write,
+    S: std::fmt::Display,
+{
+    draw_prompt(
+        output,
+        &prompt.to_string(),
+        Some("[y,n,a,q]"),
+        &settings.theme,
+    )?;
+
+    for key in input.keys() {
+        let key = key?;
+        settings.log_key(key);
+        let (answer, text) = match key {
+            Key::Char('y' | 'Y') => (ConfirmAll::Yes, "yes"),
+            Key::Char('n' | 'N') => (ConfirmAll::No, "no"),
+            Key::Char('a' | 'A') => (ConfirmAll::YesToAll, "yes to all"),
+            Key::Char('q' | 'Q') => (ConfirmAll::NoToAll, "no to all"),
+            Key::Ctrl('c') => break,
+            _ => continue,
+        };
+        write!(output, "{}\r\n", text.color(settings.theme.success))?;
+        output.flush()?;
+        return Ok(answer);
+    }
+
+    write!(output, "\r\n")?;
+    output.flush()?;
+    Err(Error::NonOptionalInput)
+}
+
+/// A line of a diff, see [`diff_lines`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// The most cells [`diff_lines`] fills in to find the lines two texts have in common, about
+/// 32MB. Past it, the changed lines are shown as all removed and then all added instead.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Diff `before` and `after` line by line, keeping the longest run of lines they have in
+/// common and marking the rest as removed or added.
+fn diff_lines<'a>(before: &'a str, after: &'a str) -> Vec<DiffLine<'a>> {
+    let before: Vec<&str> = before.lines().collect();
+    let after: Vec<&str> = after.lines().collect();
+
+    // Lines the two start and end with are the same either way, so only the middle is diffed.
+    let prefix = before
+        .iter()
+        .zip(&after)
+        .take_while(|(before, after)| before == after)
+        .count();
+    let suffix = before[prefix..]
+        .iter()
+        .rev()
+        .zip(after[prefix..].iter().rev())
+        .take_while(|(before, after)| before == after)
+        .count();
+
+    let mut lines: Vec<DiffLine> = before[..prefix]
+        .iter()
+        .map(|line| DiffLine::Same(line))
+        .collect();
+    lines.extend(diff_middle(
+        &before[prefix..before.len() - suffix],
+        &after[prefix..after.len() - suffix],
+    ));
+    lines.extend(
+        before[before.len() - suffix..]
+            .iter()
+            .map(|line| DiffLine::Same(line)),
+    );
+    lines
+}
+
+/// The longest common subsequence diff behind [`diff_lines`], falling back to all removed then
+/// all added past [`MAX_DIFF_CELLS`].
+fn diff_middle<'a>(before: &[&'a str], after: &[&'a str]) -> Vec<DiffLine<'a>> {
+    if before.len().saturating_mul(after.len()) > MAX_DIFF_CELLS {
+        return before
+            .iter()
+            .map(|line| DiffLine::Removed(line))
+            .chain(after.iter().map(|line| DiffLine::Added(line)))
+            .collect();
+    }
+
+    // common[i][j] is the length of the longest common subsequence of before[i..] and after[j..].
+    let mut common = vec![vec![0; after.len() + 1]; before.len() + 1];
+    for i in (0..before.len()).rev() {
+        for j in (0..after.len()).rev() {
+            common[i][j] = if before[i] == after[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < before.len() || j < after.len() {
+        if i < before.len() && j < after.len() && before[i] == after[j] {
+            lines.push(DiffLine::Same(before[i]));
+            i += 1;
+            j += 1;
+        } else if j == after.len() || (i < before.len() && common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(DiffLine::Removed(before[i]));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(after[j]));
+            j += 1;
+        }
+    }
+    lines
+}
+
+/// A line of [`Clytia::confirm_diff`] as it's shown, marked and coloured by whether it was
+/// removed or added.
+fn format_diff_line(line: &DiffLine, theme: &Theme) -> String {
+    match line {
+        DiffLine::Same(line) => format!("  {}", line),
+        DiffLine::Removed(line) => format!("- {}", line).color(theme.error).to_string(),
+        DiffLine::Added(line) => format!("+ {}", line).color(theme.success).to_string(),
+    }
+}
+
+/// The key loop of [`Clytia::confirm_diff`], on an output already in raw mode.
+/// Pages of the diff are `rows - 1` lines, leaving room for the prompt under them.
+fn run_confirm_diff<R, W>(
+    input: &mut R,
+    output: &mut W,
+    lines: &[DiffLine],
+    rows: usize,
+    settings: &Settings,
+) -> Result<bool>
+where
+    R: Read,
+    W: Write,
+{
+    let page = rows.saturating_sub(1).max(1);
+    let mut keys = input.keys();
+    for (index, line) in lines.iter().enumerate() {
+        if index > 0 && index % page == 0 {
+            write!(output, "{}", "-- more, q to skip --".dimmed())?;
+            output.flush()?;
+            let key = match keys.next() {
+                Some(key) => key?,
+                None => return Err(Error::NonOptionalInput),
+            };
+            settings.log_key(key);
+            write!(output, "{}\r", termion::clear::CurrentLine)?;
+            match key {
+                Key::Ctrl('c') => return Err(Error::NonOptionalInput),
+                Key::Char('q' | 'Q') => break,
+                _ => {}
+            }
+        }
+        write!(output, "{}\r\n", format_diff_line(line, &settings.theme))?;
+    }
+
+    draw_prompt(
+        output,
+        "Apply these changes?",
+        Some("[y/N]"),
+        &settings.theme,
+    )?;
+
+    for key in keys {
+        let key = key?;
+        settings.log_key(key);
+        let (answer, text) = match key {
+            Key::Char('y' | 'Y') => (true, "yes"),
+            Key::Char('n' | 'N' | '\n') => (false, "no"),
+            Key::Ctrl('c') => break,
+            _ => continue,
+        };
+        write!(output, "{}\r\n", text.color(settings.theme.success))?;
+        output.flush()?;
+        return Ok(answer);
+    }
+
+    write!(output, "\r\n")?;
+    output.flush()?;
+    Err(Error::NonOptionalInput)
+}
+
+/// The wait for a key of [`Clytia::warn_ack`], on an output already in raw mode.
+fn run_warn_ack<R, W, S>(input: &mut R, output: &mut W, text: S, settings: &Settings) -> Result<()>
+where
+    R: Read,
+    W: Write,
+    S: std::fmt::Display,
+{
+    let warning = format!("! {}", text);
+    write!(
+        output,
+        "\r{} {}{}",
+        warning.yellow().bold(),
+        "(press any key)".dimmed(),
+        settings.cursor_hide()
+    )?;
+    output.flush()?;
+
+    let mut keys = input.keys();
+    let key = next_key(&mut keys, settings)?.unwrap_or(Key::Ctrl('c'));
+    write!(
+        output,
+        "\r{}{}\r\n",
+        termion::clear::CurrentLine,
+        warning.dimmed()
+    )?;
+    output.flush()?;
+
+    match key {
+        Key::Ctrl('c') => Err(Error::NonOptionalInput),
+        _ => Ok(()),
+    }
+}
+
+/// The key loop of [`Clytia::pick_number`], on an output already in raw mode.
+fn run_pick_number<R, W, S>(
+    input: &mut R,
+    output: &mut W,
+    prompt: S,
+    range: RangeInclusive<u64>,
+    default: u64,
+    settings: &Settings,
+) -> Result<u64>
+where
+    R: Read,
+    W: Write,
+    S: std::fmt::Display,
+{
+    let (min, max) = (*range.start(), *range.end());
+    let mut value = default.clamp(min, max);
+    // Digits typed since the number was last stepped with the arrow keys.
+    let mut typed: Option<String> = None;
+    let mut keys = input.keys();
+    let theme = &settings.theme;
+
+    let picked = loop {
+        let shown = match &typed {
+            Some(typed) => typed.parse().ok(),
+            None => Some(value),
+        };
+        let number = match shown {
+            Some(n) if range.contains(&n) => n.color(theme.prompt).to_string(),
+            _ => typed
+                .as_deref()
+                .unwrap_or_default()
+                .color(theme.error)
+                .to_string(),
+        };
+        let left = if value > min && typed.is_none() {
+            "◀".color(theme.prompt).to_string()
+        } else {
+            "◀".dimmed().to_string()
+        };
+        let right = if value < max && typed.is_none() {
+            "▶".color(theme.prompt).to_string()
+        } else {
+            "▶".dimmed().to_string()
+        };
+        write!(
+            output,
+            "{}\r{} {} {} {} {}",
+            termion::clear::CurrentLine,
+            prompt.color(theme.prompt),
+            "=>".color(theme.prompt),
+            left,
+            number,
+            right
+        )?;
+        output.flush()?;
+
+        let key = match next_key(&mut keys, settings)? {
+            Some(key) => key,
+            None => break None,
+        };
+        match key {
+            Key::Left | Key::Right => {
+                if typed.take().is_some() {
+                    value = shown.unwrap_or(value).clamp(min, max);
+                }
+                value = match key {
+                    Key::Left => value.saturating_sub(1).max(min),
+                    _ => value.saturating_add(1).min(max),
+                };
+            }
+            Key::Char(c) if c.is_ascii_digit() => {
+                let typed = typed.get_or_insert_with(String::new);
+                if typed == "0" {
+                    typed.clear();
+                }
+                typed.push(c);
+            }
+            Key::Backspace => {
+                let typed = typed.get_or_insert_with(|| value.to_string());
+                typed.pop();
+            }
+            Key::Char('\n') => match shown {
+                Some(n) if range.contains(&n) => break Some(n),
+                _ => {}
+            },
+            Key::Ctrl('c') => break None,
+            _ => {}
+        }
+    };
+
+    match picked {
+        Some(n) => write!(
+            output,
+            "{}\r{} {} {}\r\n",
+            termion::clear::CurrentLine,
+            prompt.color(theme.prompt),
+            "=>".color(theme.prompt),
+            n.color(theme.success)
+        )?,
+        None => write!(output, "\r\n")?,
+    }
+    output.flush()?;
+
+    picked.ok_or(Error::NonOptionalInput)
+}
+
+/// The key loop behind [`Clytia::suggest_input`], expects the output to already be in raw mode.
+fn run_suggest_input<R, W, S, F>(
+    input: &mut R,
+    output: &mut W,
+    prompt: S,
+    suggest: F,
+    settings: &Settings,
+) -> Result<String>
+where
+    R: Read,
+    W: Write,
+    S: std::fmt::Display,
+    F: Fn(&str) -> Vec<String>,
+{
+    edit_with_suggestions(input, output, &prompt.to_string(), &suggest, None, settings)
+}
+
+/// The key loop behind [`Clytia::choice_input`], expects the output to already be in raw mode.
+fn run_choice_input<R, W, S>(
+    input: &mut R,
+    output: &mut W,
+    prompt: S,
+    allowed: &[&str],
+    settings: &Settings,
+) -> Result<String>
+where
+    R: Read,
+    W: Write,
+    S: std::fmt::Display,
+{
+    let suggest = |typed: &str| {
+        allowed
+            .iter()
+            .filter(|choice| choice.starts_with(typed))
+            .map(|choice| choice.to_string())
+            .collect()
+    };
+    edit_with_suggestions(
+        input,
+        output,
+        &prompt.to_string(),
+        &suggest,
+        Some(allowed),
+        settings,
+    )
+}
+
+/// Let the user edit a line in raw mode with the `suggest`ions for it listed below, until they
+/// accept the typed line or a suggestion with enter. Tab completes the line as far as the
+/// suggestions agree. With `allowed`, nothing else is accepted, and enter on anything else
+/// marks the prompt in the error colour until the line changes.
+fn edit_with_suggestions<R, W>(
+    input: &mut R,
+    output: &mut W,
+    prompt: &str,
+    suggest: &dyn Fn(&str) -> Vec<String>,
+    allowed: Option<&[&str]>,
+    settings: &Settings,
+) -> Result<String>
+where
+    R: Read,
+    W: Write,
+{
+    let theme = &settings.theme;
+    let mut typed = String::new();
+    let mut suggestions = suggest(&typed);
+    // The highlighted suggestion, none while the cursor is on the input line.
+    let mut highlighted: Option<usize> = None;
+    // Whether the last enter was on a line that isn't allowed, until the line changes.
+    let mut rejected = false;
+    let mut keys = input.keys();
+
+    let accepted = loop {
+        let color = match rejected {
+            true => theme.error,
+            false => theme.prompt,
+        };
+        let line = format!("{} {} {}", prompt.color(color), "=>".color(color), typed);
+        let listed = suggestions.len().min(settings.max_suggestions);
+        write!(output, "\r{}{}", termion::clear::AfterCursor, line)?;
+        for (index, suggestion) in suggestions[..listed].iter().enumerate() {
+            if highlighted == Some(index) {
+                write!(
+                    output,
+                    "\r\n{}",
+                    format!("=> {}", suggestion).color(theme.prompt)
+                )?;
+            } else {
+                write!(output, "\r\n   {}", suggestion.dimmed())?;
+            }
+        }
+        // Back up to the input line, rewriting it leaves the cursor at the end of it.
+        if listed > 0 {
+            write!(output, "{}", termion::cursor::Up(listed as u16))?;
+        }
+        write!(output, "\r{}", line)?;
+        output.flush()?;
+
+        let key = match next_key(&mut keys, settings)? {
+            Some(key) => key,
+            None => break None,
+        };
+        match key {
+            Key::Down if listed > 0 => {
+                highlighted = Some(match highlighted {
+                    Some(index) => (index + 1).min(listed - 1),
+                    None => 0,
+                });
+                continue;
+            }
+            Key::Up => {
+                highlighted = highlighted.and_then(|index| index.checked_sub(1));
+                continue;
+            }
+            Key::Char('\n') => {
+                let answer = match highlighted {
+                    Some(index) => suggestions[index].clone(),
+                    None => typed.clone(),
+                };
+                match allowed {
+                    Some(allowed) if allowed.contains(&answer.as_str()) => break Some(answer),
+                    None if !answer.trim().is_empty() => break Some(answer),
+                    Some(_) => rejected = true,
+                    None => {}
+                }
+                continue;
+            }
+            Key::Char('\t') => {
+                if let Some(common) = common_prefix(&suggestions) {
+                    if common.starts_with(&typed) {
+                        typed = common.to_string();
+                    }
+                }
+            }
+            Key::Char(c) => typed.push(c),
+            Key::Backspace => {
+                typed.pop();
+            }
+            Key::Ctrl('c') | Key::Ctrl('d') => break None,
+            _ => continue,
+        }
+
+        // The line changed, so what's suggested for it might have too.
+        highlighted = None;
+        rejected = false;
+        suggestions = suggest(&typed);
+    };
+
+    write!(output, "\r{}", termion::clear::AfterCursor)?;
+    match &accepted {
+        Some(accepted) => write!(
+            output,
+            "{} {} {}\r\n",
+            prompt.color(theme.prompt),
+            "=>".color(theme.prompt),
+            accepted.color(theme.success)
+        )?,
+        None => write!(output, "\r\n")?,
+    }
+    output.flush()?;
+
+    accepted.ok_or(Error::NonOptionalInput)
+}
+
+/// The longest prefix all of `candidates` start with, or [`None`] if there are none.
+fn common_prefix(candidates: &[String]) -> Option<&str> {
+    let (first, rest) = candidates.split_first()?;
+    Some(rest.iter().fold(first.as_str(), |common, candidate| {
+        let len = common
+            .chars()
+            .zip(candidate.chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .sum();
+        &common[..len]
+    }))
+}
+
+/// The key loop behind [`Clytia::masked_pattern_input`], expects the output to already be in
+/// raw mode.
+fn run_masked_pattern_input<R, W, S>(
+    input: &mut R,
+    output: &mut W,
+    prompt: S,
+    pattern: &str,
+    settings: &Settings,
+) -> Result<String>
+where
+    R: Read,
+    W: Write,
+    S: std::fmt::Display,
+{
+    let slots = pattern.chars().filter(|c| *c == '_').count();
+    let mut digits = String::new();
+    // Whether the last enter was before every slot was filled, until the input changes.
+    let mut rejected = false;
+    let mut keys = input.keys();
+
+    let submitted = loop {
+        let (filled, rest) = fill_pattern(pattern, &digits);
+        let color = match rejected {
+            true => settings.theme.error,
+            false => settings.theme.prompt,
+        };
+        write!(
+            output,
+            "{}\r{} {} {}{}",
+            termion::clear::CurrentLine,
+            prompt.color(color),
+            "=>".color(color),
+            filled,
+            rest.dimmed()
+        )?;
+        // Leave the cursor at the next slot.
+        if !rest.is_empty() {
+            write!(
+                output,
+                "{}",
+                termion::cursor::Left(rest.chars().count() as u16)
+            )?;
+        }
+        output.flush()?;
+
+        let key = match next_key(&mut keys, settings)? {
+            Some(key) => key,
+            None => break false,
+        };
+        match key {
+            Key::Char(c) if c.is_ascii_digit() && digits.len() < slots => {
+                digits.push(c);
+                rejected = false;
+            }
+            Key::Backspace => {
+                digits.pop();
+                rejected = false;
+            }
+            Key::Char('\n') if digits.len() == slots => break true,
+            Key::Char('\n') => rejected = true,
+            Key::Ctrl('c') | Key::Ctrl('d') => break false,
+            _ => {}
+        }
+    };
+
+    let (filled, _) = fill_pattern(pattern, &digits);
+    write!(output, "{}\r", termion::clear::CurrentLine)?;
+    match submitted {
+        true => write!(
+            output,
+            "{} {} {}\r\n",
+            prompt.color(settings.theme.prompt),
+            "=>".color(settings.theme.prompt),
+            filled.color(settings.theme.success)
+        )?,
+        false => write!(output, "\r\n")?,
+    }
+    output.flush()?;
+
+    match (submitted, settings.pattern_digits) {
+        (false, _) => Err(Error::NonOptionalInput),
+        (true, true) => Ok(digits),
+        (true, false) => Ok(filled),
+    }
+}
+
+/// Split `pattern` into the part filled in by `digits`, up to the next empty `_` slot, and
+/// the rest of it.
+fn fill_pattern(pattern: &str, digits: &str) -> (String, String) {
+    let mut digits = digits.chars();
+    let mut filled = String::new();
+    for (index, c) in pattern.char_indices() {
+        match c {
+            '_' => match digits.next() {
+                Some(digit) => filled.push(digit),
+                None => return (filled, pattern[index..].to_string()),
+            },
+            c => filled.push(c),
+        }
+    }
+    (filled, String::new())
+}
+
+/// The reading loop of [`Clytia::password_input`], on an output already in raw mode.
+fn run_password_input<R, W, S>(
+    input: &mut R,
+    output: &mut W,
+    prompt: S,
+    settings: &Settings,
+) -> Result<String>
+where
+    R: Read,
+    W: Write,
+    S: std::fmt::Display,
+{
+    let editor = LineEditor {
+        masked: true,
+        ..LineEditor::default()
+    };
+    let secret = edit_line(input, output, &prompt.to_string(), &editor, settings)?;
+    if secret.is_empty() {
+        return Err(Error::NonOptionalInput);
+    }
+
+    Ok(secret)
+}
+
+/// The editing loop of [`Clytia::live_validated_input`], on an output already in raw mode.
+fn run_live_validated_input<R, W, T, F, S>(
+    input: &mut R,
+    output: &mut W,
+    prompt: S,
+    validate: F,
+    settings: &Settings,
+) -> Result<T>
+where
+    R: Read,
+    W: Write,
+    T: FromStr,
+    F: Fn(&T) -> bool,
+    S: std::fmt::Display,
+{
+    let valid = |text: &str| validate_str(text, &validate).is_ok();
+    let editor = LineEditor {
+        valid: Some(&valid),
+        ..LineEditor::default()
+    };
+    let text = edit_line(input, output, &prompt.to_string(), &editor, settings)?;
+
+    validate_str(&text, validate)
+}
+
+/// The editing loop of [`Clytia::placeholder_input`], on an output already in raw mode.
+fn run_placeholder_input<R, W, S, P, T>(
+    input: &mut R,
+    output: &mut W,
+    prompt: S,
+    placeholder: P,
+    settings: &Settings,
+) -> Result<T>
+where
+    R: Read,
+    W: Write,
+    S: std::fmt::Display,
+    P: std::fmt::Display,
+    T: FromStr,
+{
+    let placeholder = placeholder.to_string();
+    let editor = LineEditor {
+        placeholder: Some(&placeholder),
+        ..LineEditor::default()
+    };
+    let text = edit_line(input, output, &prompt.to_string(), &editor, settings)?;
+
+    validate_str(&text, |_| true)
+}
+
+/// The editing loop of [`Clytia::help_input`], on an output already in raw mode.
+fn run_help_input<R, W, S, T>(
+    input: &mut R,
+    output: &mut W,
+    prompt: S,
+    help: Option<&str>,
+    settings: &Settings,
+) -> Result<T>
+where
+    R: Read,
+    W: Write,
+    S: std::fmt::Display,
+    T: FromStr,
+{
+    let editor = LineEditor {
+        help,
+        ..LineEditor::default()
+    };
+    let text = edit_line(input, output, &prompt.to_string(), &editor, settings)?;
+
+    validate_str(&text, |_| true)
+}
+
+/// The editing loop of [`Clytia::unit_input`], on an output already in raw mode.
+fn run_unit_input<R, W, S, T>(
+    input: &mut R,
+    output: &mut W,
+    prompt: S,
+    unit: &str,
+    settings: &Settings,
+) -> Result<T>
+where
+    R: Read,
+    W: Write,
+    S: std::fmt::Display,
+    T: FromStr,
+{
+    let editor = LineEditor {
+        unit: Some(unit),
+        ..LineEditor::default()
+    };
+    let text = edit_line(input, output, &prompt.to_string(), &editor, settings)?;
+    let value = text.trim();
+
+    validate_str(value.strip_suffix(unit).unwrap_or(value), |_| true)
+}
+
+/// What the line editor shows besides the text typed, see [`edit_line`].
+#[derive(Default)]
+struct LineEditor<'a> {
+    /// Shown dimmed while nothing has been typed, see [`Clytia::placeholder_input`].
+    placeholder: Option<&'a str>,
+    /// Shown dimmed after the text, see [`Clytia::unit_input`].
+    unit: Option<&'a str>,
+    /// Shows `*`s in place of the text unless it's revealed, see [`Clytia::password_input`].
+    masked: bool,
+    /// Checks the text after every key, marking it with a ✔ or ✖, and only lets valid text be
+    /// submitted, see [`Clytia::live_validated_input`].
+    valid: Option<&'a dyn Fn(&str) -> bool>,
+    /// Shown dimmed under the line while `?` toggles it on, see [`Clytia::help_input`].
+    help: Option<&'a str>,
+}
+
+/// Let the user edit a line in raw mode, drawn after `prompt` with whatever `editor` adds to
+/// it, until they press enter or the input ends. Returns the text entered, or
+/// [`Error::NonOptionalInput`] if they cancelled with Ctrl+C or Ctrl+D.
+fn edit_line<R, W>(
+    input: &mut R,
+    output: &mut W,
+    prompt: &str,
+    editor: &LineEditor,
+    settings: &Settings,
+) -> Result<String>
+where
+    R: Read,
+    W: Write,
+{
+    let mut output = BracketedPaste::new(output)?;
+    let mut line = LineBuffer {
+        help_key: editor.help.is_some(),
+        ..LineBuffer::default()
+    };
+    let mut buffer = [0; 1024];
+    let submitted = 'read: loop {
+        draw_edited_line(&mut *output, prompt, editor, &line, false, settings)?;
+
+        let read = input.read(&mut buffer)?;
+        if read == 0 {
+            break true;
+        }
+        let mut feed = line.feed(&buffer[..read]);
+        loop {
+            let valid = editor.valid.map_or(true, |valid| valid(&line.text()));
+            match feed {
+                LineFeed::Submit if valid => break 'read true,
+                LineFeed::Cancel => break 'read false,
+                // A rejected submission, carry on with anything typed after it.
+                LineFeed::Submit => feed = line.feed(&[]),
+                LineFeed::Pending => break,
+            }
+        }
+    };
+
+    draw_edited_line(&mut *output, prompt, editor, &line, true, settings)?;
+    write!(output, "\r\n")?;
+    drop(output);
+
+    if !submitted {
+        return Err(Error::NonOptionalInput);
+    }
+
+    Ok(line.into_string()?)
+}
+
+/// Draw the line being edited in [`edit_line`] over the last one, with the cursor left where
+/// typing carries on. Once it's `done` the placeholder isn't shown, and masked text is masked
+/// again even if it was revealed.
+fn draw_edited_line<W: Write>(
+    output: &mut W,
+    prompt: &str,
+    editor: &LineEditor,
+    line: &LineBuffer,
+    done: bool,
+    settings: &Settings,
+) -> io::Result<()> {
+    let theme = &settings.theme;
+    let help_lines: Vec<&str> = match editor.help {
+        Some(help) if line.help_shown && !done => help.lines().collect(),
+        _ => Vec::new(),
+    };
+    if !help_lines.is_empty() {
+        // Scroll in room for the help first, so the saved position stays on the line.
+        let rows = help_lines.len() as u16;
+        write!(
+            output,
+            "{}{}",
+            "\n".repeat(rows.into()),
+            termion::cursor::Up(rows)
+        )?;
+    }
+    write!(output, "{}\r", termion::clear::CurrentLine)?;
+    if editor.help.is_some() {
+        // Along with any help drawn under the line.
+        write!(output, "{}", termion::clear::AfterCursor)?;
+    }
+    write!(output, "{} ", prompt.color(theme.prompt))?;
+    if editor.help.is_some() && !done {
+        write!(output, "{} ", "(? for help)".color(theme.hint))?;
+    }
+    write!(output, "{} ", "=>".color(theme.prompt))?;
+
+    let placeholder = editor
+        .placeholder
+        .filter(|placeholder| !placeholder.is_empty() && line.is_empty() && !done);
+    if let Some(placeholder) = placeholder {
+        // Leave the cursor at the start of the placeholder, where typing begins.
+        write!(
+            output,
+            "{}{}",
+            placeholder.dimmed(),
+            termion::cursor::Left(placeholder.chars().count() as u16)
+        )?;
+    } else {
+        match editor.masked && (done || !line.revealed) {
+            true => write!(output, "{}", "*".repeat(line.len()))?,
+            false => write!(output, "{}", line.text())?,
+        }
+
+        // Anything after the text is drawn, then the cursor goes back to the end of the text.
+        let mut after = String::new();
+        let mut after_len = 0;
+        if let Some(unit) = editor.unit {
+            after += &format!(" {}", unit.dimmed());
+            after_len += unit.chars().count() + 1;
+        }
+        if let Some(valid) = editor.valid {
+            let mark = match valid(&line.text()) {
+                true => "✔".color(theme.success).to_string(),
+                false => "✖".color(theme.error).to_string(),
+            };
+            after += &format!(" {}", mark);
+            after_len += 2;
+        }
+        if after_len > 0 {
+            write!(
+                output,
+                "{}{}",
+                after,
+                termion::cursor::Left(after_len as u16)
+            )?;
+        }
+    }
+
+    if !help_lines.is_empty() {
+        write!(output, "{}", termion::cursor::Save)?;
+        for help_line in &help_lines {
+            write!(output, "\r\n{}", help_line.dimmed())?;
+        }
+        write!(output, "{}", termion::cursor::Restore)?;
+    }
+    output.flush()
+}
+
+/// Draw a frame of [`Clytia::progress_with_log_tail`], replacing the last one: `tail` in a
+/// region `lines` high, cutting each line short at `cols`, with the progress bar below it.
+fn draw_log_tail<W, S>(
+    output: &mut W,
+    tail: &[String],
+    lines: usize,
+    cols: usize,
+    prompt: S,
+    progress: f64,
+    settings: &Settings,
+) -> io::Result<()>
+where
+    W: Write,
+    S: std::fmt::Display,
+{
+    write!(output, "{}", termion::clear::CurrentLine)?;
+    clear_lines(output, lines + 1)?;
+    // Keep the newest line just above the bar, leaving any empty lines at the top.
+    let tail = &tail[tail.len().saturating_sub(lines)..];
+    for _ in tail.len()..lines {
+        writeln!(output, "\r")?;
+    }
+    for line in tail {
+        let line: String = line.chars().take(cols).collect();
+        writeln!(output, "\r{}", line.dimmed())?;
+    }
+    // The prompt's line, drawn over by the bar along with the prompt.
+    writeln!(output)?;
+
+    let cols = bar_width(cols, settings.max_bar_width);
+    draw_progress_bar(output, prompt, progress, cols, settings)
+}
+
+/// Draw a frame of a progress bar at `progress` percent, `cols` wide, replacing the last one.
+fn draw_progress_bar<W, S>(
+    output: &mut W,
+    prompt: S,
+    progress: f64,
+    cols: usize,
+    settings: &Settings,
+) -> io::Result<()>
+where
+    W: Write,
+    S: std::fmt::Display,
+{
+    let complete = progress >= 100.0;
+    let percent = format_percent(progress, settings.percent_precision);
+
+    // Clear the line, move up, clear that line, go to the start
+    write!(
+        output,
+        "{}{}\r{}{}",
+        termion::clear::CurrentLine,
+        termion::cursor::Up(1),
+        termion::clear::CurrentLine,
+        settings.cursor_hide()
+    )?;
+    writeln!(output, "{}", prompt)?;
+
+    let percent_style = settings
+        .percent_style
+        .unwrap_or_else(|| Style::new().color(settings.theme.prompt))
+        .style(format!("{}%", percent));
+    let bar_max_len = match cols.checked_sub(6 + percent.len()) {
+        Some(len) if len > 0 => len,
+        // No room for the bar on narrow terminals, just show the percentage.
+        _ => {
+            write!(output, "{}", percent_style)?;
+            return output.flush();
+        }
+    };
+
+    let bar = if !complete {
+        let bar_len = ((bar_max_len as f64 / 100f64) * progress.round()) as usize;
+        format!(
+            "[{}>{}| ",
+            "=".repeat(bar_len),
+            " ".repeat(bar_max_len - bar_len)
+        )
+    } else {
+        format!("[{}=| ", "=".repeat(bar_max_len))
+    };
+    let theme = &settings.theme;
+    write!(
+        output,
+        "{}{}{}",
+        bar.color(theme.prompt),
+        percent_style,
+        "]".color(theme.prompt)
+    )?;
+    output.flush()
+}
+
+/// Write a line once `progress` reaches the next quarter past `reported`, e.g. `50% complete`,
+/// for progress bars in accessible mode.
+fn report_milestone<W: Write>(output: &mut W, progress: f64, reported: &mut u8) -> io::Result<()> {
+    let reached = (progress.clamp(0.0, 100.0) / 25.0).floor() as u8 * 25;
+    if reached > *reported {
+        *reported = reached;
+        writeln!(output, "{}% complete", reached)?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+/// What spinners show in accessible mode instead of animating, their text once and then a
+/// `Still working...` line every [`StillWorking::EVERY`].
+#[derive(Debug)]
+struct StillWorking {
+    started: Instant,
+    /// How long the spinner had been going at the last line, none before its text is shown.
+    reported: Option<Duration>,
+}
+
+impl StillWorking {
+    const EVERY: Duration = Duration::from_secs(5);
+
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            reported: None,
+        }
+    }
+
+    /// Show `text` the first time, and a line saying how long it's been going once it's due.
+    fn report<W, S>(&mut self, output: &mut W, text: S) -> io::Result<()>
+    where
+        W: Write,
+        S: std::fmt::Display,
+    {
+        match self.reported {
+            None => {
+                writeln!(output, "{}", text)?;
+                self.reported = Some(Duration::ZERO);
+            }
+            Some(reported) if self.started.elapsed() >= reported + Self::EVERY => {
+                let reported = reported + Self::EVERY;
+                writeln!(output, "Still working... {}", format_duration(reported))?;
+                self.reported = Some(reported);
+            }
+            Some(_) => return Ok(()),
+        }
+        output.flush()
+    }
+}
+
+/// Skips redrawing a progress bar while what it shows hasn't changed, apart from
+/// redrawing it every [`RedrawThrottle::KEEP_ALIVE`] in case something else drew over it.
+#[derive(Debug, Default)]
+struct RedrawThrottle<T> {
+    last: Option<(T, Instant)>,
+}
+
+impl<T: PartialEq> RedrawThrottle<T> {
+    const KEEP_ALIVE: Duration = Duration::from_secs(1);
+
+    /// Whether a frame showing `frame` should be drawn, if so it's remembered as the last one.
+    fn should_redraw(&mut self, frame: T) -> bool {
+        if let Some((last, drawn)) = &self.last {
+            if *last == frame && drawn.elapsed() < Self::KEEP_ALIVE {
+                return false;
+            }
+        }
+        self.last = Some((frame, Instant::now()));
+        true
+    }
+}
+
+/// The overall percentage of a task made up of phases with the given `weights`, while it's
+/// `fraction` of the way through `phase`. Phases past the last are taken as the last.
+fn weighted_percent(weights: &[f64], phase: usize, fraction: f64) -> f64 {
+    let total: f64 = weights.iter().sum();
+    if weights.is_empty() || total <= 0.0 {
+        return 0.0;
+    }
+    let phase = phase.min(weights.len() - 1);
+    let done: f64 = weights[..phase].iter().sum();
+    (done + weights[phase] * fraction.clamp(0.0, 1.0)) / total * 100.0
+}
+
+/// The prompt of a [`Clytia::phased_progress_bar`], followed by the name of the current phase.
+struct PhaseLabel<'a, S, N, P> {
+    prompt: S,
+    phases: &'a [(N, f64)],
+    phase: P,
+}
+
+impl<S, N, P> std::fmt::Display for PhaseLabel<'_, S, N, P>
+where
+    S: std::fmt::Display,
+    N: std::fmt::Display,
+    P: Fn() -> usize,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let phase = (self.phase)();
+        match self.phases.get(phase).or_else(|| self.phases.last()) {
+            Some((name, _)) => write!(f, "{}: {}", self.prompt, name),
+            None => write!(f, "{}", self.prompt),
+        }
+    }
+}
+
+/// The prompt of a [`Clytia::pausable_progress_bar`], saying when it's paused.
+struct PauseLabel<'a, S> {
+    prompt: S,
+    pause: &'a ProgressPause,
+}
+
+impl<S: std::fmt::Display> std::fmt::Display for PauseLabel<'_, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.pause.is_paused() {
+            true => write!(f, "{} {}", self.prompt, "(paused)".dimmed()),
+            false => write!(f, "{}", self.prompt),
+        }
+    }
+}
+
+/// The width of a progress bar in a terminal `cols` wide, capped to `max_bar_width`.
+fn bar_width(cols: usize, max_bar_width: Option<usize>) -> usize {
+    match max_bar_width {
+        Some(max) => cols.min(max),
+        None => cols,
+    }
+}
+
+/// Format a percentage for a progress bar, zero padded to three digits before the point.
+///
+/// It's rounded down, so nothing short of done shows as `100%`.
+fn format_percent(percent: f64, precision: usize) -> String {
+    let scale = 10f64.powi(precision as i32);
+    let percent = (percent * scale).floor() / scale;
+    let width = match precision {
+        0 => 3,
+        precision => 4 + precision,
+    };
+    format!("{:0width$.precision$}", percent)
+}
+
+/// Parse a line read for [`Clytia::parsed_input`], falling back to `default` if nothing was
+/// typed.
+fn parse_answer<T: FromStr>(input: Option<String>, default: Option<T>) -> Result<T> {
+    match input {
+        None => match default {
+            Some(v) => Ok(v),
+            None => Err(Error::NonOptionalInput),
+        },
+        Some(n) if n.trim().trim_end().is_empty() => match default {
+            Some(v) => Ok(v),
+            None => Err(Error::NonOptionalInput),
+        },
+        Some(n) => {
+            let trimmed = n.trim().trim_end();
+            trimmed
+                .parse()
+                .map_err(|_| Error::parse_error::<T>(trimmed))
+        }
+    }
+}
+
+/// Reads from a file descriptor it doesn't own without buffering, so reading a line through
+/// [`Read::bytes`] leaves everything after it unread. See [`Clytia::parsed_input_timeout`].
+struct FdReader(RawFd);
+
+impl Read for FdReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            // SAFETY: `buf` is valid for writes of `buf.len()` bytes for the whole call.
+            let read = unsafe { libc::read(self.0, buf.as_mut_ptr().cast(), buf.len()) };
+            if read >= 0 {
+                return Ok(read as usize);
+            }
+            let e = io::Error::last_os_error();
+            if e.kind() != io::ErrorKind::Interrupted {
+                return Err(e);
+            }
+        }
+    }
+}
+
+/// Wait up to `timeout` for `fd` to have something to read, see
+/// [`Clytia::parsed_input_timeout`]. The end of the input counts as something to read.
+fn wait_readable(fd: i32, timeout: Duration) -> io::Result<bool> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let mut poll_fd = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let left = deadline.saturating_duration_since(Instant::now());
+        let millis = left.as_millis().min(i32::MAX as u128) as i32;
+        // SAFETY: `poll_fd` is a single valid `pollfd` that outlives the call.
+        match unsafe { libc::poll(&mut poll_fd, 1, millis) } {
+            -1 => {
+                let e = io::Error::last_os_error();
+                if e.kind() != io::ErrorKind::Interrupted {
+                    return Err(e);
+                }
+            }
+            // Timed out, unless the timeout was rounded down to whole milliseconds.
+            0 if Instant::now() >= deadline => return Ok(false),
+            0 => {}
+            _ => return Ok(true),
+        }
+    }
+}
+
+/// Wait `interval` for the next frame of an animation, returning `false` without waiting it
+/// out once `stop`'s sender is dropped, so the animation ends as soon as its task does.
+fn next_frame(stop: &Receiver<()>, interval: Duration) -> bool {
+    matches!(stop.recv_timeout(interval), Err(RecvTimeoutError::Timeout))
+}
+
+/// Whether this process is running in the background of the terminal `fd` is attached to,
+/// e.g. after being started with `&` or suspended and resumed with `bg`.
+///
+/// Changing terminal settings from the background stops the process (`SIGTTOU`), so
+/// methods that need raw mode ask line by line instead, and animations aren't drawn.
+fn in_background(fd: RawFd) -> bool {
+    // SAFETY: Both calls only read the process group IDs, `tcgetpgrp` returns -1 when
+    // `fd` isn't a terminal.
+    let (foreground, own) = unsafe { (libc::tcgetpgrp(fd), libc::getpgrp()) };
+    foreground != -1 && foreground != own
+}
+
+/// Put `output` into raw mode, unless the process is in the background.
+fn raw_mode<W: Write>(output: W, settings: &Settings) -> Result<RawTerminal<W>> {
+    if settings.in_background() {
+        return Err(Error::Background);
+    }
+    Ok(output.into_raw_mode()?)
+}
+
+/// The bars of a sparkline, shortest first.
+const SPARKLINE_BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Turn `values` into a sparkline, one bar per value, see [`Clytia::sparkline`].
+fn format_sparkline(values: &[f64]) -> String {
+    let finite = values.iter().copied().filter(|value| value.is_finite());
+    let min = finite.clone().fold(f64::INFINITY, f64::min);
+    let max = finite.fold(f64::NEG_INFINITY, f64::max);
+    let top = SPARKLINE_BARS.len() - 1;
+
+    values
+        .iter()
+        .map(|value| {
+            if !value.is_finite() {
+                ' '
+            } else if max == min {
+                SPARKLINE_BARS[top / 2]
+            } else {
+                SPARKLINE_BARS[((value - min) / (max - min) * top as f64).round() as usize]
+            }
+        })
+        .collect()
+}
+
+/// Draw a rule `cols` wide, with `label` centred in it if there is one.
+///
+/// A label too long to fit is still drawn whole, with a single rule character either side.
+fn draw_rule<W: Write>(
+    output: &mut W,
+    cols: usize,
+    label: Option<&str>,
+    settings: &Settings,
+) -> io::Result<()> {
+    let symbol = if settings.ascii_rules { "-" } else { "─" };
+    let line = |len: usize| settings.rule_style.style(symbol.repeat(len));
+
+    match label {
+        Some(label) => {
+            let rest = cols.saturating_sub(label.chars().count() + 2).max(2);
+            writeln!(
+                output,
+                "{} {} {}",
+                line(rest / 2),
+                label,
+                line(rest - rest / 2)
+            )
+        }
+        None => writeln!(output, "{}", line(cols)),
+    }
+}
+
+/// Keep the bottom row of a terminal `rows` tall out of the scroll region, and draw `text`
+/// there. The cursor is left where it was.
+///
+/// The first time, a line is scrolled in so the cursor isn't on the row being taken over.
+fn draw_status_bar<W, S>(output: &mut W, text: S, rows: u16, first: bool) -> io::Result<()>
+where
+    W: Write,
+    S: std::fmt::Display,
+{
+    if first {
+        write!(output, "\n{}", termion::cursor::Up(1))?;
+    }
+    // Setting the scroll region moves the cursor, so it's saved first.
+    write!(
+        output,
+        "{}\x1b[1;{}r{}{}{}{}",
+        termion::cursor::Save,
+        rows.saturating_sub(1).max(1),
+        termion::cursor::Goto(1, rows.max(1)),
+        termion::clear::CurrentLine,
+        text,
+        termion::cursor::Restore
+    )?;
+    output.flush()
+}
+
+/// Give the bottom row of a terminal `rows` tall back to the scroll region, clearing it.
+fn remove_status_bar<W: Write>(output: &mut W, rows: u16) -> io::Result<()> {
+    write!(
+        output,
+        "{}{}{}{}{}",
+        termion::cursor::Save,
+        SCROLL_REGION_RESET,
+        termion::cursor::Goto(1, rows.max(1)),
+        termion::clear::CurrentLine,
+        termion::cursor::Restore
+    )?;
+    output.flush()
+}
+
+/// Clear the current line and write `content` from the start of it.
+fn redraw_line<W, S>(output: &mut W, content: S) -> io::Result<()>
+where
+    W: Write,
+    S: std::fmt::Display,
+{
+    write!(output, "{}\r{}", termion::clear::CurrentLine, content)?;
+    output.flush()
+}
+
+/// Bracketed paste turned on for as long as this is held, so it's turned off again however
+/// the input ends, errors included.
+struct BracketedPaste<'a, W: Write>(&'a mut W);
+
+impl<'a, W: Write> BracketedPaste<'a, W> {
+    fn new(output: &'a mut W) -> io::Result<Self> {
+        write!(output, "{}", BRACKETED_PASTE_ON)?;
+        Ok(Self(output))
+    }
+}
+
+impl<W: Write> std::ops::Deref for BracketedPaste<'_, W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        self.0
+    }
+}
+
+impl<W: Write> std::ops::DerefMut for BracketedPaste<'_, W> {
+    fn deref_mut(&mut self) -> &mut W {
+        self.0
+    }
+}
+
+impl<W: Write> Drop for BracketedPaste<'_, W> {
+    fn drop(&mut self) {
+        let _ = write!(self.0, "{}", BRACKETED_PASTE_OFF);
+        let _ = self.0.flush();
+    }
+}
+
+/// What the caller should do after feeding bytes into a [`LineBuffer`].
+#[derive(Debug, PartialEq, Eq)]
+enum LineFeed {
+    /// Keep reading.
+    Pending,
+    /// The user pressed enter.
+    Submit,
+    /// The user pressed Ctrl+C or Ctrl+D.
+    Cancel,
+}
+
+/// The state of a line being typed (or pasted) in raw mode.
+///
+/// Bytes are fed in whatever chunks the terminal hands them over, so escape
+/// sequences and bracketed pastes can be split across reads.
+#[derive(Debug, Default)]
+struct LineBuffer {
+    line: Vec<u8>,
+    unparsed: Vec<u8>,
+    in_paste: bool,
+    /// Whether Ctrl+R has been pressed an odd number of times, see [`Clytia::password_input`].
+    revealed: bool,
+    /// Whether `?` on an empty line toggles `help_shown`, see [`Clytia::help_input`].
+    help_key: bool,
+    help_shown: bool,
+}
+
+impl LineBuffer {
+    /// Process a chunk of raw input.
+    fn feed(&mut self, bytes: &[u8]) -> LineFeed {
+        self.unparsed.extend_from_slice(bytes);
+
+        loop {
+            if self.in_paste {
+                match find(&self.unparsed, PASTE_END) {
+                    Some(end) => {
+                        let pasted: Vec<u8> =
+                            self.unparsed.drain(..end + PASTE_END.len()).collect();
+                        self.push_pasted(&pasted[..end]);
+                        self.in_paste = false;
+                        continue;
+                    }
+                    None => {
+                        // Hold back anything that could be the start of the end marker.
+                        let keep = (1..PASTE_END.len())
+                            .rev()
+                            .find(|n| self.unparsed.ends_with(&PASTE_END[..*n]))
+                            .unwrap_or(0);
+                        let take = self.unparsed.len() - keep;
+                        let pasted: Vec<u8> = self.unparsed.drain(..take).collect();
+                        self.push_pasted(&pasted);
+                        return LineFeed::Pending;
+                    }
+                }
+            }
+
+            let byte = match self.unparsed.first() {
+                Some(byte) => *byte,
+                None => return LineFeed::Pending,
+            };
+            match byte {
+                b'\x1b' => match escape_len(&self.unparsed) {
+                    Some(len) => {
+                        if self.unparsed.starts_with(PASTE_START) {
+                            self.in_paste = true;
+                        }
+                        self.unparsed.drain(..len);
+                    }
+                    None => return LineFeed::Pending,
+                },
+                b'\r' | b'\n' => {
+                    // Keep whatever followed, in case the submission is rejected.
+                    self.unparsed.remove(0);
+                    return LineFeed::Submit;
+                }
+                3 | 4 => {
+                    self.unparsed.clear();
+                    return LineFeed::Cancel;
+                }
+                0x7f | 0x08 => {
+                    self.unparsed.remove(0);
+                    self.backspace();
+                }
+                0x12 => {
+                    self.unparsed.remove(0);
+                    self.revealed = !self.revealed;
+                }
+                b'?' if self.help_key && self.line.is_empty() => {
+                    self.unparsed.remove(0);
+                    self.help_shown = !self.help_shown;
+                }
+                b if b < 0x20 => {
+                    self.unparsed.remove(0);
+                }
+                b => {
+                    self.unparsed.remove(0);
+                    self.line.push(b);
+                }
+            }
+        }
+    }
+
+    /// Pasted content is taken as is, apart from line breaks which would otherwise end the input.
+    fn push_pasted(&mut self, bytes: &[u8]) {
+        self.line
+            .extend(bytes.iter().filter(|b| **b != b'\r' && **b != b'\n'));
+    }
+
+    /// Remove the last character, which may be several bytes long.
+    fn backspace(&mut self) {
+        while let Some(byte) = self.line.pop() {
+            if byte & 0b1100_0000 != 0b1000_0000 {
+                break;
+            }
+        }
+    }
+
+    /// The number of characters entered so far.
+    fn len(&self) -> usize {
+        self.line
+            .iter()
+            .filter(|b| **b & 0b1100_0000 != 0b1000_0000)
+            .count()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.line.is_empty()
+    }
+
+    /// The line entered so far, with any incomplete characters replaced.
+    fn text(&self) -> std::borrow::Cow<'_, str> {
+        String::from_utf8_lossy(&self.line)
+    }
+
+    fn into_string(self) -> io::Result<String> {
+        String::from_utf8(self.line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Find the first position of `needle` in `haystack`.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// The length of the escape sequence at the start of `bytes`,
+/// or [`None`] if it hasn't been fully read yet.
+fn escape_len(bytes: &[u8]) -> Option<usize> {
+    match bytes.get(1)? {
+        b'[' => bytes
+            .iter()
+            .skip(2)
+            .position(|b| (0x40..=0x7e).contains(b))
+            .map(|end| end + 3),
+        b'O' => bytes.get(2).map(|_| 3),
+        _ => Some(1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod non_interactive {
+        use std::{
+            collections::HashSet,
+            os::unix::io::AsRawFd,
+            sync::atomic::AtomicUsize,
+            time::{Duration, Instant},
+        };
+
+        use owo_colors::OwoColorize;
+        use termion::event::Key;
+
+        use crate::{
+            bar_width, diff_lines, draw_log_tail, draw_multichoice, draw_nested_spinner,
+            draw_progress_bar, draw_rule, draw_status_bar, evaluate, fill_pattern, filter_options,
+            format_duration, format_percent, format_sparkline, format_two_columns,
+            options_menu_window, parse_color, parse_numbered, parse_range, remove_status_bar,
+            report_milestone, run_choice_input, run_confirm_all, run_confirm_diff,
+            run_filtered_menu, run_help_input, run_live_validated_input, run_masked_pattern_input,
+            run_multichoice, run_multichoice_cancellable, run_multichoice_confirmed,
+            run_multichoice_limited, run_options_menu, run_options_menu_from, run_password_input,
+            run_pick_number, run_placeholder_input, run_quantity_select, run_suggest_input,
+            run_tree_select, run_unit_input, run_warn_ack, scroll_offset, secret_from_env,
+            tree_leaves, validate_str, visible_tree_nodes, weighted_percent, AnsiColors, Clytia,
+            ConfirmAll, DiffLine, EmptyInput, Error, KeyBatches, LabelOverflow, LineBuffer,
+            LineFeed, LogTail, OsStr, Output, PauseLabel, PhaseLabel, ProgressPause, PromptStyle,
+            Recorder, RedrawThrottle, Replay, Settings, SpinnerStyle, Style, Theme, TreeNode,
+            VarError, BRACKETED_PASTE_OFF, BRACKETED_PASTE_ON, SPINNER_SYMBOLS,
+        };
+
+        /// Input handed over one chunk per read, like a terminal would.
+        struct Chunks(Vec<&'static [u8]>);
+
+        impl std::io::Read for Chunks {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                if self.0.is_empty() {
+                    return Ok(0);
+                }
+                let chunk = self.0.remove(0);
+                buf[..chunk.len()].copy_from_slice(chunk);
+                Ok(chunk.len())
+            }
+        }
+
+        #[test]
+        fn test_parsed_input_with_default() {
+            let output = Vec::new();
+            let input: Vec<u8> = vec![b'1', b'\n'];
+            let mut cli = Clytia::new(&input as &[u8], output);
+            assert!(cli.parsed_input("input a number", Some(0)).is_ok());
+            let s = std::str::from_utf8(cli.output());
+            assert!(s.is_ok());
+            let s = s.unwrap();
+            assert_eq!(
+                s,
+                format!(
+                    "{} {} {} ",
+                    "input a number".blue(),
+                    "(default: 0)".magenta(),
+                    "=>".blue()
+                )
+            );
+        }
+
+        #[test]
+        fn test_theme() {
+            let theme = Theme {
+                prompt: AnsiColors::Cyan,
+                hint: AnsiColors::Yellow,
+                success: AnsiColors::White,
+                highlight: AnsiColors::Magenta,
+                ..Theme::default()
+            };
+            let input: Vec<u8> = vec![b'\n'];
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            cli.with_theme(theme);
+            assert_eq!(cli.parsed_input("number", Some(0)).unwrap(), 0);
+            assert_eq!(
+                std::str::from_utf8(cli.output()).unwrap(),
+                format!(
+                    "{} {} {} ",
+                    "number".cyan(),
+                    "(default: 0)".yellow(),
+                    "=>".cyan()
+                )
+            );
+
+            let settings = Settings {
+                theme,
+                ..Default::default()
+            };
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"\x1b[B\n";
+            assert_eq!(
+                run_options_menu(&mut input, &mut output, &["cats", "dogs"], 24, &settings)
+                    .unwrap(),
+                1
+            );
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.contains(&"=> dogs".magenta().to_string()));
+            assert!(s.ends_with(&format!("{}\n", "\r=> dogs\r".white())));
+        }
+
+        #[test]
+        fn test_parsed_input_without_default() {
+            let output = Vec::new();
+            let input: Vec<u8> = vec![b'1', b'\n'];
+            let mut cli = Clytia::new(&input as &[u8], output);
+            assert!(cli.parsed_input::<_, usize>("input a number", None).is_ok());
+            let s = std::str::from_utf8(cli.output());
+            assert!(s.is_ok());
+            let s = s.unwrap();
+            assert_eq!(s, format!("{} {} ", "input a number".blue(), "=>".blue()));
+        }
+
+        #[test]
+        fn test_parsed_input_or_default() {
+            let input: Vec<u8> = b"\n".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            assert!(matches!(
+                cli.parsed_input_or_default::<_, usize>("Retries", None),
+                Err(Error::NonOptionalInput)
+            ));
+
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            cli.with_empty_input(EmptyInput::Default);
+            assert_eq!(
+                cli.parsed_input_or_default::<_, usize>("Retries", None)
+                    .unwrap(),
+                0
+            );
+
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            cli.with_empty_input(EmptyInput::Default);
+            assert_eq!(cli.parsed_input_or_default("Retries", Some(3)).unwrap(), 3);
+        }
+
+        #[test]
+        fn test_parsed_input_parse_error() {
+            let output = Vec::new();
+            let input: Vec<u8> = b"abc\n".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], output);
+            let err = cli
+                .parsed_input::<_, usize>("input a number", None)
+                .unwrap_err();
+            assert!(matches!(
+                &err,
+                Error::ParseError { input, expected: "usize" } if input == "abc"
+            ));
+            assert_eq!(err.to_string(), "Could not parse \"abc\" as usize");
+        }
+
+        #[test]
+        fn test_normalized_input() {
+            let output = Vec::new();
+            let input: Vec<u8> = b" 1234-5678 \n-\n".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], output);
+            let r = cli.normalized_input::<_, u64, _>("Card", None, |line| line.replace('-', ""));
+            assert_eq!(r.unwrap(), 12345678);
+            let r = cli.normalized_input("Card", Some(0u64), |line| line.replace('-', ""));
+            assert_eq!(r.unwrap(), 0);
+        }
+
+        #[test]
+        fn test_field_input() {
+            let input: Vec<u8> = b"\nabc\n11\n 7 \n".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            let r = cli.field_input::<_, usize, _>("number", None, |n| *n <= 10);
+            assert_eq!(r.unwrap(), 7);
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.contains(&"(an input is required)".red().to_string()));
+            assert!(s.contains(&"(Could not parse \"abc\" as usize)".red().to_string()));
+            assert!(s.contains(&"(\"11\" is not valid)".red().to_string()));
+
+            let input: Vec<u8> = b"11\n\n".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            let r = cli.field_input("number", Some(3), |n| *n <= 10);
+            assert_eq!(r.unwrap(), 3);
+
+            let input: Vec<u8> = b"11\n\x04".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            let r = cli.field_input::<_, usize, _>("number", None, |n| *n <= 10);
+            assert!(matches!(r, Err(Error::NonOptionalInput)));
+        }
+
+        #[test]
+        fn test_evaluate() {
+            assert_eq!(evaluate("12*3+1"), Ok(37.0));
+            assert_eq!(evaluate(" 1 + 2 * 3 "), Ok(7.0));
+            assert_eq!(evaluate("(1+2)*3"), Ok(9.0));
+            assert_eq!(evaluate("10-4-3"), Ok(3.0));
+            assert_eq!(evaluate("8/4/2"), Ok(1.0));
+            assert_eq!(evaluate("-2*-(3.5-1)"), Ok(5.0));
+            assert_eq!(evaluate("42"), Ok(42.0));
+            assert_eq!(evaluate("1/0"), Err("division by zero".to_string()));
+            assert_eq!(evaluate("(1+2"), Err("unmatched '('".to_string()));
+            assert_eq!(evaluate("1+2)"), Err("unmatched ')'".to_string()));
+            assert_eq!(evaluate("2*"), Err("incomplete expression".to_string()));
+            assert_eq!(evaluate(""), Err("incomplete expression".to_string()));
+            assert_eq!(evaluate("2x"), Err("unexpected 'x'".to_string()));
+            assert_eq!(
+                evaluate("1.2.3"),
+                Err("\"1.2.3\" isn't a number".to_string())
+            );
+        }
+
+        #[test]
+        fn test_amount_input() {
+            let input: Vec<u8> = b"\n3*\n3*4.5\n".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            assert_eq!(cli.amount_input("Total").unwrap(), 13.5);
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.contains(&"(an input is required)".red().to_string()));
+            assert!(s.contains(&format!(
+                "{} {}",
+                "3*".white(),
+                "(incomplete expression)".red()
+            )));
+
+            let input: Vec<u8> = b"x\n\x04".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            assert!(matches!(
+                cli.amount_input("Total"),
+                Err(Error::NonOptionalInput)
+            ));
+
+            // Running out of input stops asking again.
+            let mut cli = Clytia::new(&b"x\n"[..], Vec::new());
+            assert!(matches!(
+                cli.amount_input("Total"),
+                Err(Error::NonOptionalInput)
+            ));
+        }
+
+        #[test]
+        fn test_parsed_input_timeout() {
+            use std::{io::Write, os::unix::net::UnixStream, time::Instant};
+
+            let (input, mut answers) = UnixStream::pair().unwrap();
+            let mut cli = Clytia::new(input, Vec::new());
+            let timeout = Duration::from_millis(50);
+            let started = Instant::now();
+            let r: crate::Result<u32> = cli.parsed_input_timeout("Retries", Some(3), timeout);
+            assert_eq!(r.unwrap(), 3);
+            assert!(started.elapsed() >= timeout);
+            let r: crate::Result<u32> = cli.parsed_input_timeout("Retries", None, timeout);
+            assert!(matches!(r, Err(Error::NonOptionalInput)));
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.contains(&"(timed out)".magenta().to_string()));
+
+            answers.write_all(b"7\n").unwrap();
+            let r: crate::Result<u32> = cli.parsed_input_timeout("Retries", Some(3), timeout);
+            assert_eq!(r.unwrap(), 7);
+
+            // Answers arriving together are each left for their own prompt, even when the
+            // input reads ahead into a buffer.
+            struct Buffered(std::io::BufReader<UnixStream>);
+            impl std::io::Read for Buffered {
+                fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                    self.0.read(buf)
+                }
+            }
+            impl AsRawFd for Buffered {
+                fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+                    self.0.get_ref().as_raw_fd()
+                }
+            }
+            let (input, mut buffered_answers) = UnixStream::pair().unwrap();
+            let mut buffered = Clytia::new(Buffered(std::io::BufReader::new(input)), Vec::new());
+            buffered_answers.write_all(b"7\n8\n").unwrap();
+            let r: crate::Result<u32> = buffered.parsed_input_timeout("Retries", Some(3), timeout);
+            assert_eq!(r.unwrap(), 7);
+            let r: crate::Result<u32> = buffered.parsed_input_timeout("Retries", Some(3), timeout);
+            assert_eq!(r.unwrap(), 8);
+            let r: crate::Result<u32> = buffered.parsed_input_timeout("Retries", Some(3), timeout);
+            assert_eq!(r.unwrap(), 3);
+
+            // The end of the input falls back to the default straight away.
+            drop(answers);
+            let r: crate::Result<u32> =
+                cli.parsed_input_timeout("Retries", Some(3), Duration::from_secs(60));
+            assert_eq!(r.unwrap(), 3);
+        }
+
+        #[test]
+        fn test_confirm_then_progress_declined() {
+            for answer in ["n\n", "\n"] {
+                let mut cli = Clytia::new(answer.as_bytes(), Vec::new());
+                let ret = cli.confirm_then_progress(
+                    "Delete 1000 files?",
+                    "Deleting files",
+                    || 0,
+                    || -> Result<(), ()> { panic!("the task shouldn't run") },
+                );
+                assert!(ret.unwrap().is_none());
+                let s = std::str::from_utf8(cli.output()).unwrap();
+                assert!(s.contains(&format!(
+                    "{} {} {}",
+                    "Delete 1000 files?".blue(),
+                    "[y/N]".magenta(),
+                    "=>".blue()
+                )));
+            }
+
+            // A typo is asked again instead of giving up.
+            let mut cli = Clytia::new(&b"yse\nno\n"[..], Vec::new());
+            let ret = cli.confirm_then_progress(
+                "Delete 1000 files?",
+                "Deleting files",
+                || 0,
+                || -> Result<(), ()> { panic!("the task shouldn't run") },
+            );
+            assert!(ret.unwrap().is_none());
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.contains(&"(expected yes or no)".red().to_string()));
+            assert_eq!(s.matches("[y/N]").count(), 2);
+
+            let mut cli = Clytia::new(&b"\x03"[..], Vec::new());
+            let ret = cli.confirm_then_progress(
+                "Delete 1000 files?",
+                "Deleting files",
+                || 0,
+                || -> Result<(), ()> { panic!("the task shouldn't run") },
+            );
+            assert!(matches!(ret, Err(Error::NonOptionalInput)));
+        }
+
+        #[test]
+        fn test_confirm_then_progress_accepted() {
+            let mut cli = Clytia::new(&b"y\n"[..], Vec::new());
+            let ret = cli.confirm_then_progress(
+                "Delete 1000 files?",
+                "Deleting files",
+                || 100,
+                || -> Result<u32, ()> { Ok(1000) },
+            );
+            assert_eq!(ret.unwrap(), Some(Ok(1000)));
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.contains("Delete 1000 files?"));
+        }
+
+        #[test]
+        fn test_bool_input() {
+            let answers = [
+                ("yes", true),
+                ("Y", true),
+                ("1", true),
+                ("ON", true),
+                ("True", true),
+                ("no", false),
+                ("n", false),
+                ("0", false),
+                ("Off", false),
+                ("FALSE", false),
+            ];
+            for (answer, expected) in answers {
+                let input = format!(" {} \n", answer);
+                let mut cli = Clytia::new(input.as_bytes(), Vec::new());
+                assert_eq!(cli.bool_input("ok?", None).unwrap(), expected, "{}", answer);
+            }
+
+            let mut cli = Clytia::new(&b"\n"[..], Vec::new());
+            assert!(cli.bool_input("ok?", Some(true)).unwrap());
+
+            let mut cli = Clytia::new(&b"Maybe\n"[..], Vec::new());
+            assert!(matches!(
+                cli.bool_input("ok?", None),
+                Err(Error::ParseError {
+                    expected: "bool",
+                    ..
+                })
+            ));
+        }
+
+        #[test]
+        fn test_confirm_required() {
+            let input: Vec<u8> = b"\nmaybe\nN\n".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            assert!(!cli.confirm_required("Drop it?").unwrap());
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.starts_with(&format!(
+                "{} {} {} ",
+                "Drop it?".blue(),
+                "[y/n]".magenta(),
+                "=>".blue()
+            )));
+            assert!(s.contains(&"(an answer is required)".red().to_string()));
+            assert!(s.contains(&format!(
+                "{} {}",
+                "maybe".white(),
+                "(expected yes or no)".red()
+            )));
+
+            let mut cli = Clytia::new(&b"\x04"[..], Vec::new());
+            assert!(matches!(
+                cli.confirm_required("Drop it?"),
+                Err(Error::NonOptionalInput)
+            ));
+
+            // An empty answer asks again, but the end of the input doesn't.
+            let mut cli = Clytia::new(&b"\n"[..], Vec::new());
+            assert!(matches!(
+                cli.confirm_required("Drop it?"),
+                Err(Error::NonOptionalInput)
+            ));
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn test_prompt_struct() {
+            use crate::FieldType;
+
+            let input: Vec<u8> = b"clytia\neighty\n80\nyes\n".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            let value: serde_json::Value = cli
+                .prompt_struct(&[
+                    ("name", FieldType::String),
+                    ("port", FieldType::Integer),
+                    ("tls", FieldType::Bool),
+                ])
+                .unwrap();
+            let mut expected = serde_json::Map::new();
+            expected.insert("name".to_string(), "clytia".to_string().into());
+            expected.insert("port".to_string(), 80i64.into());
+            expected.insert("tls".to_string(), true.into());
+            assert_eq!(value, serde_json::Value::Object(expected));
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.contains(&format!(
+                "{} {}",
+                "eighty".white(),
+                "(expected a whole number)".red()
+            )));
+
+            let input: Vec<u8> = b"\x04".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            assert!(matches!(
+                cli.prompt_struct::<serde_json::Value>(&[("name", FieldType::String)]),
+                Err(Error::NonOptionalInput)
+            ));
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn test_parse_field() {
+            use crate::{parse_field, FieldType};
+
+            assert_eq!(
+                parse_field(" x ", FieldType::String),
+                Ok(serde_json::Value::String(" x ".to_string()))
+            );
+            assert_eq!(
+                parse_field("", FieldType::Integer),
+                Err("an input is required")
+            );
+            assert_eq!(
+                parse_field("1.5", FieldType::Integer),
+                Err("expected a whole number")
+            );
+            assert_eq!(
+                parse_field("inf", FieldType::Float),
+                Err("expected a number")
+            );
+            assert_eq!(
+                parse_field("off", FieldType::Bool),
+                Ok(serde_json::Value::Bool(false))
+            );
+            assert_eq!(
+                parse_field("18446744073709551615", FieldType::Integer),
+                Ok(serde_json::Value::from(u64::MAX))
+            );
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn test_offending_field() {
+            use crate::{offending_field, FieldType};
+
+            let schema = [
+                ("name", FieldType::String),
+                ("port", FieldType::Integer),
+                ("tls", FieldType::Bool),
+            ];
+            let name = serde_json::Value::String("clytia".to_string());
+            let port = serde_json::Value::from(70000i64);
+            let tls = serde_json::Value::Bool(true);
+            let values = [&name, &port, &tls];
+
+            let cases = [
+                ("missing field `tls`", Some(2)),
+                ("invalid value: integer `70000`, expected u16", Some(1)),
+                ("invalid type: string \"clytia\", expected a path", Some(0)),
+                ("invalid type: boolean `true`, expected a string", Some(2)),
+                ("unknown variant, expected `a` or `b`", None),
+            ];
+            for (error, expected) in cases {
+                assert_eq!(
+                    offending_field(error, &schema, &values),
+                    expected,
+                    "{}",
+                    error
+                );
+            }
+
+            // A value shared by two fields can't tell them apart.
+            let values = [&name, &port, &port];
+            assert_eq!(
+                offending_field(
+                    "invalid value: integer `70000`, expected u16",
+                    &schema,
+                    &values
+                ),
+                None
+            );
+        }
+
+        #[test]
+        fn test_format_duration() {
+            let cases = [
+                (Duration::ZERO, "0ms"),
+                (Duration::from_micros(999_999), "999ms"),
+                (Duration::from_millis(1_000), "1s"),
+                (Duration::from_millis(45_900), "45s"),
+                (Duration::from_secs(60), "1m00s"),
+                (Duration::from_secs(192), "3m12s"),
+                (Duration::from_secs(3_599), "59m59s"),
+                (Duration::from_secs(3_720), "1h02m"),
+                (Duration::from_secs(100 * 3_600 + 59), "100h00m"),
+            ];
+            for (duration, expected) in cases {
+                assert_eq!(format_duration(duration), expected);
+            }
+        }
+
+        #[test]
+        fn test_parse_range() {
+            assert_eq!(parse_range::<i32>(" 10..20 "), Ok((10, 20)));
+            assert_eq!(parse_range::<i32>("-5 .. -5"), Ok((-5, -5)));
+            assert_eq!(parse_range::<f64>("1.5..2.5"), Ok((1.5, 2.5)));
+            assert_eq!(parse_range::<i32>(""), Err("an input is required"));
+            assert_eq!(parse_range::<i32>("10-20"), Err("expected start..end"));
+            assert_eq!(parse_range::<i32>("a..20"), Err("invalid start"));
+            assert_eq!(parse_range::<i32>("10.."), Err("invalid end"));
+            assert_eq!(parse_range::<i32>("20..10"), Err("start is after end"));
+        }
+
+        #[test]
+        fn test_range_input() {
+            let input: Vec<u8> = b"20..10\n10..20\n".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            assert_eq!(cli.range_input::<_, u32>("Lines").unwrap(), (10, 20));
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.contains(&format!(
+                "{} {}",
+                "20..10".white(),
+                "(start is after end)".red()
+            )));
+
+            let input: Vec<u8> = b"\x04".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            assert!(matches!(
+                cli.range_input::<_, u32>("Lines"),
+                Err(Error::NonOptionalInput)
+            ));
+
+            let mut cli = Clytia::new(&b"20..10\n"[..], Vec::new());
+            assert!(matches!(
+                cli.range_input::<_, u32>("Lines"),
+                Err(Error::NonOptionalInput)
+            ));
+        }
+
+        #[test]
+        fn test_parse_color() {
+            assert_eq!(parse_color("#ff8800"), Some((255, 136, 0)));
+            assert_eq!(parse_color(" #F80 "), Some((255, 136, 0)));
+            assert_eq!(parse_color("rgb(12, 34,56)"), Some((12, 34, 56)));
+            assert_eq!(parse_color("Orange"), Some((255, 165, 0)));
+            assert_eq!(parse_color("#ff880"), None);
+            assert_eq!(parse_color("#gg8800"), None);
+            assert_eq!(parse_color("#+f+f+f"), None);
+            assert_eq!(parse_color("rgb(256, 0, 0)"), None);
+            assert_eq!(parse_color("rgb(1, 2)"), None);
+            assert_eq!(parse_color("chartreuse"), None);
+        }
+
+        #[test]
+        fn test_color_input() {
+            let input: Vec<u8> = b"#12\nteal\n".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            assert_eq!(cli.color_input("Accent").unwrap(), (0, 128, 128));
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.contains(
+                &"(expected #rrggbb, rgb(r, g, b) or a color name)"
+                    .red()
+                    .to_string()
+            ));
+            assert!(s.contains(&format!("teal {}\n", "██".truecolor(0, 128, 128))));
+
+            let mut cli = Clytia::new(&b"\x04"[..], Vec::new());
+            assert!(matches!(
+                cli.color_input("Accent"),
+                Err(Error::NonOptionalInput)
+            ));
+
+            let mut cli = Clytia::new(&b"#12\n"[..], Vec::new());
+            assert!(matches!(
+                cli.color_input("Accent"),
+                Err(Error::NonOptionalInput)
+            ));
+        }
+
+        #[test]
+        fn test_collect_inputs() {
+            let input: Vec<u8> = b"1\ntwo\n 2 \n3\n\n4\n".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            let r = cli.collect_inputs::<_, usize>("number");
+            assert_eq!(r.unwrap(), vec![1, 2, 3]);
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.contains(&format!(
+                "{} {} {}",
+                "=>".red(),
+                "two".white(),
+                "(Could not parse \"two\" as usize)".red()
+            )));
+
+            // Only the empty line finishes, anything else stopping the input cancels.
+            for input in [&b"a\nb"[..], b"a\n\x03", b"a\n\x04"] {
+                let mut cli = Clytia::new(input, Vec::new());
+                let r = cli.collect_inputs::<_, String>("letter");
+                assert!(matches!(r, Err(Error::NonOptionalInput)));
+            }
+        }
+
+        #[test]
+        fn test_validate_str() {
+            assert_eq!(
+                validate_str::<usize, _>("  4\n", |n| *n % 2 == 0).unwrap(),
+                4
+            );
+            assert!(matches!(
+                validate_str::<usize, _>(" \t", |_| true),
+                Err(Error::NonOptionalInput)
+            ));
+            assert!(matches!(
+                validate_str::<usize, _>("four", |_| true),
+                Err(Error::ParseError { .. })
+            ));
+            assert!(matches!(
+                validate_str::<usize, _>(" 3 ", |n| *n % 2 == 0),
+                Err(Error::InvalidInput(input)) if input == "3"
+            ));
+        }
+
+        #[test]
+        fn test_input_styled() {
+            let style = PromptStyle {
+                indicator: "!>",
+                indicator_color: AnsiColors::Red,
+                ..PromptStyle::default()
+            };
+            let input: Vec<u8> = b"1\n".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            let r = cli.parsed_input_styled::<_, usize>("Delete?", None, style);
+            assert_eq!(r.unwrap(), 1);
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert_eq!(s, format!("{} {} ", "Delete?".blue(), "!>".red()));
+
+            let input: Vec<u8> = b"3\n4\n".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            let r = cli.validated_input_styled("Count", "even", |n: &usize| n % 2 == 0, style);
+            assert_eq!(r.unwrap(), 4);
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.contains(&format!(
+                "{} {} ",
+                "(requirements: even)".magenta(),
+                "!>".red()
+            )));
+            assert!(!s.contains("=>"));
+        }
+
+        #[test]
+        fn test_label_width() {
+            let mut settings = Settings {
+                label_width: Some(6),
+                ..Settings::default()
+            };
+            assert_eq!(settings.label("Name"), "Name  ");
+            assert_eq!(settings.label("Postcode"), "Postcode");
+            settings.label_overflow = LabelOverflow::Truncate;
+            assert_eq!(settings.label("Postcode"), "Postc…");
+            assert_eq!(settings.label("Street"), "Street");
+
+            let input: Vec<u8> = b"Al\n".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            cli.with_label_width(Some(6));
+            let name: String = cli.parsed_input("Name", None).unwrap();
+            assert_eq!(name, "Al");
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert_eq!(s, format!("{} {} ", "Name  ".blue(), "=>".blue()));
+        }
+
+        #[test]
+        fn test_validated_input_retries() {
+            let output = Vec::new();
+            let input: Vec<u8> = b"\n3\n4\n".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], output);
+            let r = cli.validated_input::<usize, _, _, _>("input a number", "even", |n| n % 2 == 0);
+            assert_eq!(r.unwrap(), 4);
+        }
+
+        #[test]
+        fn test_validated_input_with_reason() {
+            let input: Vec<u8> = b"-3\n-2\n4\n".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            let r = cli.validated_input_with_reason("Pairs", "even and positive", |n: &i64| {
+                if n % 2 != 0 {
+                    Err("must be even".to_string())
+                } else if *n <= 0 {
+                    Err("must be positive".to_string())
+                } else {
+                    Ok(())
+                }
+            });
+            assert_eq!(r.unwrap(), 4);
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.contains(&format!("{} {}", "-3".white(), "(must be even)".red())));
+            assert!(s.contains(&format!("{} {}", "-2".white(), "(must be positive)".red())));
+
+            // The input ending stops the prompt instead of asking again forever.
+            let mut cli = Clytia::new(&b"3\n"[..], Vec::new());
+            let r = cli.validated_input_with_reason("Pairs", "even", |n: &i64| match n % 2 {
+                0 => Ok(()),
+                _ => Err("must be even".to_string()),
+            });
+            assert!(matches!(r, Err(Error::NonOptionalInput)));
+
+            // Without a reason only the rejected input is shown.
+            let input: Vec<u8> = b"3\n4\n".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            let r = cli.validated_input::<usize, _, _, _>("input a number", "even", |n| n % 2 == 0);
+            assert_eq!(r.unwrap(), 4);
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(!s.contains(&format!("{} ", "3".white())));
+        }
+
+        #[test]
+        fn test_validated_input_untrimmed() {
+            let output = Vec::new();
+            let input: Vec<u8> = b"\n  \n".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], output);
+            cli.with_untrimmed_validation(true);
+            let r = cli.validated_input("separator", "not empty", |s: &String| !s.is_empty());
+            assert_eq!(r.unwrap(), "  ");
+
+            let input: Vec<u8> = b" 4\n".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            cli.with_untrimmed_validation(true);
+            let r = cli.validated_input::<usize, _, _, _>("input a number", "even", |n| n % 2 == 0);
+            assert!(matches!(r, Err(Error::ParseError { input, .. }) if input == " 4"));
+        }
+
+        #[test]
+        fn test_alternate_screen() {
+            let output = Vec::new();
+            let input: Vec<u8> = Vec::new();
+            let mut cli = Clytia::new(&input as &[u8], output);
+            let r = cli.with_alternate_screen(|cli| cli.status_line("inside"));
+            assert!(r.is_ok());
+            let r = cli.with_alternate_screen(|_| Err::<(), _>(Error::NonOptionalInput));
+            assert!(matches!(r, Err(Error::NonOptionalInput)));
+            let s = std::str::from_utf8(cli.output());
+            assert!(s.is_ok());
+            let s = s.unwrap();
+            assert_eq!(
+                s,
+                format!(
+                    "{0}{2}\rinside{1}{0}{1}",
+                    termion::screen::ToAlternateScreen,
+                    termion::screen::ToMainScreen,
+                    termion::clear::CurrentLine
+                )
+            );
+        }
+
+        #[test]
+        fn test_progress_bar_accessible() {
+            let percent = AtomicUsize::new(50);
+            let mut cli = Clytia::new(&b""[..], Vec::new());
+            cli.with_accessible(true);
+            let ret = cli.progress_bar::<_, _, _, (), ()>(
+                "Deleting files",
+                || percent.load(std::sync::atomic::Ordering::SeqCst),
+                || {
+                    std::thread::sleep(Duration::from_millis(50));
+                    percent.store(100, std::sync::atomic::Ordering::SeqCst);
+                    Ok(())
+                },
+            );
+            assert!(ret.unwrap().is_ok());
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.contains("Deleting files\n50% complete\n100% complete\n"));
+        }
+
+        #[test]
+        fn test_progress_bar_counted_summary() {
+            let output = Vec::new();
+            let input: Vec<u8> = Vec::new();
+            let mut cli = Clytia::new(&input as &[u8], output);
+            cli.with_progress_summary(true);
+            assert!(cli
+                .progress_bar_counted::<_, _, _, (), ()>("Count to 3", 3, || 3, || Ok(()))
+                .is_ok());
+            let s = std::str::from_utf8(cli.output());
+            assert!(s.is_ok());
+            let s = s.unwrap();
+            let summary = s.trim_end_matches('\n').rsplit('\n').next().unwrap();
+            assert!(summary.contains("Completed 3 items in "));
+            assert!(s.ends_with('\n'));
+        }
+
+        #[test]
+        fn test_static_spinner_success() {
+            let output = Vec::new();
+            let input: Vec<u8> = Vec::new();
+            let mut cli = Clytia::new(&input as &[u8], output);
+            assert!(cli
+                .static_background_spinner::<_, _, (), ()>("Wait 1ms", || {
+                    std::thread::sleep(Duration::from_millis(1));
+                    Ok(())
+                })
+                .is_ok());
+            let s = std::str::from_utf8(cli.output());
+            assert!(s.is_ok());
+            let s = s.unwrap();
+            assert_eq!(
+                s,
+                format!(
+                    "\r{} Wait 1ms\r{}\n",
+                    SPINNER_SYMBOLS[0].blue(),
+                    "✔️  Wait 1ms".green()
+                )
+            );
+        }
+
+        #[test]
+        fn test_static_spinner_failure() {
+            let output = Vec::new();
+            let input: Vec<u8> = Vec::new();
+            let mut cli = Clytia::new(&input as &[u8], output);
+            assert!(cli
+                .static_background_spinner::<_, _, (), ()>("Wait 1ms", || {
+                    std::thread::sleep(Duration::from_millis(1));
+                    Err(())
+                })
+                .is_ok());
+            let s = std::str::from_utf8(cli.output());
+            assert!(s.is_ok());
+            let s = s.unwrap();
+            assert_eq!(
+                s,
+                format!(
+                    "\r{} Wait 1ms\r{}\n",
+                    SPINNER_SYMBOLS[0].blue(),
+                    "❌ Wait 1ms".red()
+                )
+            );
+        }
+
+        #[test]
+        fn test_dynamic_spinner_success() {
+            let output = Vec::new();
+            let input: Vec<u8> = Vec::new();
+            let mut cli = Clytia::new(&input as &[u8], output);
+            assert!(cli
+                .dynamic_background_spinner::<_, _, _, (), ()>(
+                    || "Wait 1ms",
+                    || {
+                        std::thread::sleep(Duration::from_millis(1));
+                        Ok(())
+                    }
+                )
+                .is_ok());
+            let s = std::str::from_utf8(cli.output());
+            assert!(s.is_ok());
+            let s = s.unwrap();
+            assert_eq!(
+                s,
+                format!(
+                    "{}\r{} Wait 1ms{}\r{}\n",
+                    termion::clear::CurrentLine,
+                    SPINNER_SYMBOLS[0].blue(),
+                    termion::clear::CurrentLine,
+                    "✔️  Wait 1ms".green()
+                )
+            );
+        }
+
+        #[test]
+        fn test_dynamic_spinner_failure() {
+            let output = Vec::new();
+            let input: Vec<u8> = Vec::new();
+            let mut cli = Clytia::new(&input as &[u8], output);
+            assert!(cli
+                .dynamic_background_spinner::<_, _, _, (), ()>(
+                    || "Wait 1ms",
+                    || {
+                        std::thread::sleep(Duration::from_millis(1));
+                        Err(())
+                    }
+                )
+                .is_ok());
+            let s = std::str::from_utf8(cli.output());
+            assert!(s.is_ok());
+            let s = s.unwrap();
+            assert_eq!(
+                s,
+                format!(
+                    "{}\r{} Wait 1ms{}\r{}\n",
+                    termion::clear::CurrentLine,
+                    SPINNER_SYMBOLS[0].blue(),
+                    termion::clear::CurrentLine,
+                    "❌ Wait 1ms".red()
+                )
+            );
+        }
+
+        #[test]
+        fn test_spinner_without_finish_newline() {
+            let output = Vec::new();
+            let input: Vec<u8> = Vec::new();
+            let mut cli = Clytia::new(&input as &[u8], output);
+            cli.with_finish_newline(false);
+            assert!(cli
+                .static_background_spinner::<_, _, (), ()>("Wait 1ms", || {
+                    std::thread::sleep(Duration::from_millis(1));
+                    Ok(())
+                })
+                .is_ok());
+            let s = std::str::from_utf8(cli.output());
+            assert!(s.is_ok());
+            let s = s.unwrap();
+            assert_eq!(
+                s,
+                format!(
+                    "\r{} Wait 1ms\r{}",
+                    SPINNER_SYMBOLS[0].blue(),
+                    "✔️  Wait 1ms".green()
+                )
+            );
+        }
+
+        #[test]
+        fn test_draw_nested_spinner() {
+            let mut output = Vec::new();
+            let subtasks = vec!["child".to_string(), "grandchild".to_string()];
+            let drawn =
+                draw_nested_spinner(&mut output, '*', "parent", &subtasks, 0, AnsiColors::Blue)
+                    .unwrap();
+            assert_eq!(drawn, 2);
+            let drawn = draw_nested_spinner(
+                &mut output,
+                '*',
+                "parent",
+                &subtasks[..1],
+                drawn,
+                AnsiColors::Blue,
+            );
+            assert_eq!(drawn.unwrap(), 1);
+            let s = std::str::from_utf8(&output).unwrap();
+            let clear = termion::clear::CurrentLine;
+            let up = termion::cursor::Up(1);
+            assert_eq!(
+                s,
+                format!(
+                    "{0}\r{2} parent\n{0}\r  {2} child\n{0}\r    {2} grandchild\
+                     {0}{1}{0}{1}{0}\r{2} parent\n{0}\r  {2} child",
+                    clear,
+                    up,
+                    '*'.blue()
+                )
+            );
+        }
+
+        #[test]
+        fn test_nested_spinner() {
+            let output = Vec::new();
+            let input: Vec<u8> = Vec::new();
+            let mut cli = Clytia::new(&input as &[u8], output);
+            let r = cli.nested_spinner::<_, _, _, ()>("parent", |subtasks| {
+                subtasks.run("child", |subtasks| subtasks.run("grandchild", |_| Ok(2)))
+            });
+            assert_eq!(r.unwrap(), Ok(2));
+            let s = std::str::from_utf8(cli.output());
+            assert!(s.is_ok());
+            let s = s.unwrap();
+            assert!(s.ends_with(&format!("\r{}\n", "✔️  parent".green())));
+        }
+
+        #[test]
+        fn test_status_line() {
+            let output = Vec::new();
+            let input: Vec<u8> = Vec::new();
+            let mut cli = Clytia::new(&input as &[u8], output);
+            assert!(cli.status_line("first").is_ok());
+            assert!(cli.status_line("second").is_ok());
+            assert!(cli.clear_status_line().is_ok());
+            let s = std::str::from_utf8(cli.output());
+            assert!(s.is_ok());
+            let s = s.unwrap();
+            assert_eq!(
+                s,
+                format!("{0}\rfirst{0}\rsecond{0}\r", termion::clear::CurrentLine)
+            );
+        }
+
+        #[test]
+        fn test_spinner_wait() {
+            let output = Vec::new();
+            let input: Vec<u8> = Vec::new();
+            let mut cli = Clytia::new(&input as &[u8], output);
+            let mut child = std::process::Command::new("true").spawn().unwrap();
+            let status = cli.spinner_wait("Running true", &mut child);
+            assert!(status.unwrap().success());
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.ends_with(&format!("\r{}\n", "✔️  Running true".green())));
+
+            let output = Vec::new();
+            let mut cli = Clytia::new(&input as &[u8], output);
+            let mut child = std::process::Command::new("false").spawn().unwrap();
+            let status = cli.spinner_wait("Running false", &mut child);
+            assert!(!status.unwrap().success());
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.ends_with(&format!("\r{}\n", "❌ Running false".red())));
+        }
+
+        #[test]
+        fn test_indented() {
+            use std::io::Write;
+
+            let mut output = Output::new(Vec::new());
+            output.indent = 2;
+            write!(
+                output,
+                "a\r\nb{}\r{}c",
+                termion::cursor::Up(1),
+                termion::clear::CurrentLine
+            )
+            .unwrap();
+            write!(output, "\r").unwrap();
+            write!(output, "{}d", "e".blue()).unwrap();
+            assert_eq!(
+                std::str::from_utf8(&output.inner).unwrap(),
+                format!(
+                    "  a\r\n  b{}\r{}  c\r\x1b[34m  e\x1b[39md",
+                    termion::cursor::Up(1),
+                    termion::clear::CurrentLine
+                )
+            );
+
+            let input: Vec<u8> = b"1\n2\n".to_vec();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            cli.with_indent(2);
+            assert_eq!(cli.parsed_input::<_, u8>("first", None).unwrap(), 1);
+            assert_eq!(cli.parsed_input::<_, u8>("second", None).unwrap(), 2);
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert_eq!(
+                s,
+                format!(
+                    "{} {} {} {} ",
+                    "  first".blue(),
+                    "=>".blue(),
+                    "  second".blue(),
+                    "=>".blue()
+                )
+            );
+        }
+
+        #[test]
+        fn test_immediate_flush() {
+            use std::io::Write;
+
+            /// Output counting how often it's flushed.
+            #[derive(Debug, Default)]
+            struct Flushes(usize);
+
+            impl Write for Flushes {
+                fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                    Ok(buf.len())
+                }
+
+                fn flush(&mut self) -> std::io::Result<()> {
+                    self.0 += 1;
+                    Ok(())
+                }
+            }
+
+            let mut output = Output::new(Flushes::default());
+            write!(output, "a{}b", 1).unwrap();
+            assert_eq!(output.inner.0, 0);
+            output.immediate_flush = true;
+            write!(output, "a{}b", 1).unwrap();
+            assert_eq!(output.inner.0, 3);
+            output.indent = 2;
+            write!(output, "\rc").unwrap();
+            assert_eq!(output.inner.0, 4);
+        }
+
+        #[test]
+        fn test_batch() {
+            let input: Vec<u8> = Vec::new();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            cli.with_indent(2);
+            let ret = cli.batch(|cli| {
+                cli.sparkline(&[1.0, 2.0])?;
+                cli.batch(|cli| cli.sparkline(&[2.0, 1.0]))?;
+                assert!(cli.output().is_empty());
+                Ok(3)
+            });
+            assert_eq!(ret.unwrap(), 3);
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.contains("  ▁█"));
+            assert!(s.contains("  █▁"));
+
+            // Output from a failing batch is still written.
+            let written = cli.output().len();
+            let ret = cli.batch(|cli| -> crate::Result<()> {
+                cli.sparkline(&[])?;
+                Err(Error::NonOptionalInput)
+            });
+            assert!(matches!(ret, Err(Error::NonOptionalInput)));
+            assert!(cli.output().len() > written);
+        }
+
+        #[test]
+        fn test_capture() {
+            let input: Vec<u8> = Vec::new();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            cli.with_indent(2);
+            let ret = cli.batch(|cli| {
+                cli.sparkline(&[1.0, 2.0])?;
+                let (ret, captured) = cli.capture(|cli| {
+                    cli.batch(|cli| cli.sparkline(&[2.0, 1.0]))?;
+                    Ok::<_, Error>(3)
+                });
+                assert!(captured.contains("  █▁"));
+                ret.map(|ret| (ret, captured))
+            });
+            let (ret, captured) = ret.unwrap();
+            assert_eq!(ret, 3);
+
+            // Only what was written outside the capture reaches the output.
+            let s = std::str::from_utf8(cli.output()).unwrap().to_string();
+            assert!(s.contains("  ▁█"));
+            assert!(!s.contains("█▁"));
+            cli.sparkline(&[2.0, 1.0]).unwrap();
+            let after = std::str::from_utf8(cli.output()).unwrap();
+            assert_eq!(after, s + &captured);
+        }
+
+        #[test]
+        fn test_progress_pause() {
+            let pause = ProgressPause::default();
+            assert!(!pause.is_paused());
+            pause.resume();
+            assert_eq!(pause.paused_for(), Duration::ZERO);
+
+            pause.pause();
+            std::thread::sleep(Duration::from_millis(20));
+            pause.pause();
+            assert!(pause.is_paused());
+            assert!(pause.paused_for() >= Duration::from_millis(20));
+            pause.resume();
+            assert!(!pause.is_paused());
+            let paused_for = pause.paused_for();
+            assert!(paused_for >= Duration::from_millis(20));
+            std::thread::sleep(Duration::from_millis(10));
+            assert_eq!(pause.paused_for(), paused_for);
+
+            let label = PauseLabel {
+                prompt: "Downloading",
+                pause: &pause,
+            };
+            assert_eq!(label.to_string(), "Downloading");
+            pause.pause();
+            assert_eq!(
+                label.to_string(),
+                format!("Downloading {}", "(paused)".dimmed())
+            );
+        }
+
+        #[test]
+        fn test_spinner_recv() {
+            let input: Vec<u8> = Vec::new();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            let (tx, rx) = crossbeam::channel::unbounded();
+            tx.send(42).unwrap();
+            assert_eq!(cli.spinner_recv("Waiting", &rx).unwrap(), 42);
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.ends_with(&format!("\r{}\n", "✔️  Waiting".green())));
+
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            drop(tx);
+            assert!(matches!(
+                cli.spinner_recv("Waiting", &rx),
+                Err(Error::Disconnected)
+            ));
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.ends_with(&format!("\r{}\n", "❌ Waiting".red())));
+        }
+
+        #[test]
+        fn test_spinner_frames() {
+            let input: Vec<u8> = Vec::new();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            assert!(matches!(
+                cli.with_spinner_frames(Vec::new()),
+                Err(Error::EmptyFrames)
+            ));
+            assert_eq!(cli.settings.spinner_frames, SPINNER_SYMBOLS);
+
+            cli.with_spinner_frames(vec!['-', '|']).unwrap();
+            let ret = cli.static_background_spinner("Waiting", || -> Result<(), ()> {
+                std::thread::sleep(Duration::from_millis(120));
+                Ok(())
+            });
+            assert!(ret.unwrap().is_ok());
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.starts_with(&format!("\r{} Waiting\r{} Waiting", '-'.blue(), '|'.blue())));
+        }
+
+        #[test]
+        fn test_spinner_interval() {
+            let input: Vec<u8> = Vec::new();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            cli.with_spinner_frames(vec!['-', '|'])
+                .unwrap()
+                .with_spinner_interval(Duration::from_secs(1));
+            let started = Instant::now();
+            let ret = cli.static_background_spinner("Waiting", || -> Result<(), ()> {
+                std::thread::sleep(Duration::from_millis(120));
+                Ok(())
+            });
+            assert!(ret.unwrap().is_ok());
+            // The spinner stops with the task rather than finishing its frame.
+            assert!(started.elapsed() < Duration::from_millis(500));
+            // The task finishes before the second frame is due.
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.starts_with(&format!("\r{} Waiting", '-'.blue())));
+            assert!(!s.contains(&format!("{} Waiting", '|'.blue())));
+
+            cli.with_fps(0);
+            assert_eq!(cli.settings.frame_interval, Duration::from_secs(1));
+            cli.with_fps(25);
+            assert_eq!(cli.settings.frame_interval, Duration::from_millis(40));
+            cli.with_fps(1000);
+            assert_eq!(cli.settings.frame_interval, Duration::from_millis(10));
+            cli.with_spinner_interval(Duration::ZERO);
+            assert_eq!(cli.settings.frame_interval, Duration::from_millis(10));
+            cli.with_spinner_interval(Duration::from_secs(60));
+            assert_eq!(cli.settings.frame_interval, Duration::from_secs(1));
+        }
+
+        #[test]
+        fn test_spinner_iter() {
+            let output = Vec::new();
+            let input: Vec<u8> = Vec::new();
+            let mut cli = Clytia::new(&input as &[u8], output);
+            let items: Vec<u32> = cli.spinner_iter("Counting", vec![1, 2, 3]).collect();
+            assert_eq!(items, vec![1, 2, 3]);
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.starts_with(&format!("\r{} Counting (0)", SPINNER_SYMBOLS[0].blue())));
+            assert!(s.ends_with(&format!(
+                "{}\r{}\n",
+                termion::clear::CurrentLine,
+                "✔️  Counting (3)".green()
+            )));
+        }
+
+        #[test]
+        fn test_spinner_counted() {
+            let input: Vec<u8> = Vec::new();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            let ret = cli.spinner_counted("Copying", 10, |done| -> Result<(), ()> {
+                done.fetch_add(3, std::sync::atomic::Ordering::SeqCst);
+                Ok(())
+            });
+            assert!(ret.unwrap().is_ok());
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.ends_with(&format!("\r{}\n", "✔️  Copying (10/10)".green())));
+
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            let ret = cli.spinner_counted("Copying", 10, |done| -> Result<(), ()> {
+                done.fetch_add(3, std::sync::atomic::Ordering::SeqCst);
+                Err(())
+            });
+            assert!(ret.unwrap().is_err());
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.ends_with(&format!("\r{}\n", "❌ Copying (3/10)".red())));
+        }
+
+        #[test]
+        fn test_redraw_throttle() {
+            let mut throttle = RedrawThrottle::default();
+            assert!(throttle.should_redraw(("042", 80, "a")));
+            assert!(!throttle.should_redraw(("042", 80, "a")));
+            assert!(throttle.should_redraw(("043", 80, "a")));
+            assert!(throttle.should_redraw(("043", 100, "a")));
+            assert!(!throttle.should_redraw(("043", 100, "a")));
+            assert!(throttle.should_redraw(("043", 100, "b")));
+        }
+
+        #[test]
+        fn test_bar_width() {
+            assert_eq!(bar_width(200, None), 200);
+            assert_eq!(bar_width(200, Some(60)), 60);
+            assert_eq!(bar_width(40, Some(60)), 40);
+        }
+
+        #[test]
+        fn test_format_percent() {
+            assert_eq!(format_percent(0.0, 0), "000");
+            assert_eq!(format_percent(42.0, 0), "042");
+            assert_eq!(format_percent(99.9, 0), "099");
+            assert_eq!(format_percent(100.0, 0), "100");
+            assert_eq!(format_percent(42.56, 1), "042.5");
+            assert_eq!(format_percent(7.0, 2), "007.00");
+            assert_eq!(format_percent(100.0, 1), "100.0");
+        }
+
+        #[test]
+        fn test_confirm_all() {
+            let answers: [(&[u8], ConfirmAll); 4] = [
+                (b"y", ConfirmAll::Yes),
+                (b"N", ConfirmAll::No),
+                (b"a", ConfirmAll::YesToAll),
+                (b"q", ConfirmAll::NoToAll),
+            ];
+            for (mut input, expected) in answers {
+                let mut output = Vec::new();
+                let answer =
+                    run_confirm_all(&mut input, &mut output, "Delete?", &Settings::default());
+                assert_eq!(answer.unwrap(), expected);
+            }
+
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"x\x1b[Aa";
+            let answer = run_confirm_all(&mut input, &mut output, "Delete?", &Settings::default());
+            assert_eq!(answer.unwrap(), ConfirmAll::YesToAll);
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.ends_with(&format!("{}\r\n", "yes to all".green())));
+
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"x\x03y";
+            let answer = run_confirm_all(&mut input, &mut output, "Delete?", &Settings::default());
+            assert!(matches!(answer, Err(Error::NonOptionalInput)));
+
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"x";
+            let answer = run_confirm_all(&mut input, &mut output, "Delete?", &Settings::default());
+            assert!(matches!(answer, Err(Error::NonOptionalInput)));
+        }
+
+        #[test]
+        fn test_key_hook() {
+            let keys = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+            let logged = keys.clone();
+            let mut cli = Clytia::new(&b""[..], Vec::new());
+            cli.with_key_hook(move |key| logged.lock().unwrap().push(key));
+
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"\x1b[Bx\n";
+            let selected =
+                run_options_menu(&mut input, &mut output, &["a", "b"], 24, &cli.settings);
+            assert_eq!(selected.unwrap(), 1);
+            assert_eq!(
+                *keys.lock().unwrap(),
+                vec![Key::Down, Key::Char('x'), Key::Char('\n')]
+            );
+        }
+
+        #[test]
+        fn test_on_finish() {
+            let finished = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+            let logged = finished.clone();
+            let mut cli = Clytia::new(&b""[..], Vec::new());
+            cli.with_on_finish(move |text, success, _| {
+                logged.lock().unwrap().push((text.to_string(), success))
+            });
+
+            let ret = cli.static_background_spinner("Uploading", || -> Result<(), ()> { Err(()) });
+            assert!(ret.unwrap().is_err());
+            let items: Vec<u32> = cli.spinner_iter("Counting", vec![1, 2]).collect();
+            assert_eq!(items, vec![1, 2]);
+            assert_eq!(
+                *finished.lock().unwrap(),
+                vec![
+                    ("Uploading".to_string(), false),
+                    ("Counting".to_string(), true)
+                ]
+            );
+        }
+
+        #[test]
+        fn test_diff_lines() {
+            use DiffLine::*;
+
+            assert_eq!(
+                diff_lines("a\nb\nc\n", "a\nB\nc\nd"),
+                vec![Same("a"), Removed("b"), Added("B"), Same("c"), Added("d")]
+            );
+            assert_eq!(diff_lines("", "x"), vec![Added("x")]);
+            assert_eq!(diff_lines("x\ny", ""), vec![Removed("x"), Removed("y")]);
+            assert_eq!(diff_lines("same", "same"), vec![Same("same")]);
+
+            // Too big to diff in full, so the changed middle is removed then added.
+            let before = (0..3000).map(|i| format!("{}\n", i)).collect::<String>();
+            let after = (0..3000)
+                .map(|i| format!("{}\n", i * 2))
+                .collect::<String>();
+            let lines = diff_lines(&before, &after);
+            assert_eq!(lines[0], Same("0"));
+            assert_eq!(lines[1], Removed("1"));
+            assert_eq!(lines[3000], Added("2"));
+            assert_eq!(lines.len(), 1 + 2 * 2999);
+        }
+
+        #[test]
+        fn test_confirm_diff() {
+            let lines = diff_lines("a\nb\nc\nd\n", "a\nc\nd\ne\n");
+            let confirm = |mut input: &[u8], rows, output: &mut Vec<u8>| {
+                run_confirm_diff(&mut input, output, &lines, rows, &Settings::default())
+            };
+
+            let mut output = Vec::new();
+            assert!(confirm(b"y", 24, &mut output).unwrap());
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.starts_with(&format!("  a\r\n{}\r\n  c\r\n", "- b".red())));
+            assert!(s.contains(&format!("{}\r\n", "+ e".green())));
+            assert!(!s.contains("more"));
+
+            assert!(!confirm(b"x\n", 24, &mut Vec::new()).unwrap());
+            assert!(matches!(
+                confirm(b"\x03", 24, &mut Vec::new()),
+                Err(Error::NonOptionalInput)
+            ));
+
+            // Three lines a page, the first key shows the next page, q skips the rest.
+            let mut output = Vec::new();
+            assert!(confirm(b" y", 4, &mut output).unwrap());
+            let s = std::str::from_utf8(&output).unwrap();
+            assert_eq!(s.matches("more").count(), 1);
+            assert!(s.contains("+ e"));
+
+            let mut output = Vec::new();
+            assert!(!confirm(b"qn", 3, &mut output).unwrap());
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(!s.contains("  d"));
+        }
+
+        #[test]
+        fn test_pick_number() {
+            let pick = |mut input: &[u8]| {
+                let mut output = Vec::new();
+                run_pick_number(
+                    &mut input,
+                    &mut output,
+                    "Port",
+                    1024..=65535,
+                    8080,
+                    &Settings::default(),
+                )
+            };
+            assert_eq!(pick(b"\n").unwrap(), 8080);
+            assert_eq!(pick(b"\x1b[C\x1b[C\x1b[D\n").unwrap(), 8081);
+            // Typed numbers replace the current one, and can be stepped from.
+            assert_eq!(pick(b"3000\x1b[C\n").unwrap(), 3001);
+            // Enter is ignored while out of range.
+            assert_eq!(pick(b"80\n00\n").unwrap(), 8000);
+            assert_eq!(pick(b"\x7f\x7f90\n").unwrap(), 8090);
+            // Stepping an out of range number brings it back into range.
+            assert_eq!(pick(b"99999\x1b[D\n").unwrap(), 65534);
+            assert!(matches!(pick(b"\x03"), Err(Error::NonOptionalInput)));
+            assert!(matches!(pick(b"1"), Err(Error::NonOptionalInput)));
+
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"\x1b[D\x1b[D\n";
+            let picked = run_pick_number(
+                &mut input,
+                &mut output,
+                "Count",
+                0..=5,
+                1,
+                &Settings::default(),
+            );
+            assert_eq!(picked.unwrap(), 0);
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.ends_with(&format!("{} {}\r\n", "=>".blue(), 0.green())));
+        }
+
+        #[test]
+        fn test_secret_from_env() {
+            use std::os::unix::ffi::OsStringExt;
+
+            let var = OsStr::new("API_TOKEN");
+            let secret = |value| secret_from_env(var, value);
+            assert_eq!(
+                secret(Ok("hunter2".to_string())).unwrap(),
+                Some("hunter2".to_string())
+            );
+            assert_eq!(secret(Ok(String::new())).unwrap(), None);
+            assert_eq!(secret(Err(VarError::NotPresent)).unwrap(), None);
+            let invalid = std::ffi::OsString::from_vec(vec![0xff]);
+            assert!(matches!(
+                secret(Err(VarError::NotUnicode(invalid))),
+                Err(Error::InvalidInput(var)) if var == "API_TOKEN"
+            ));
+        }
+
+        #[test]
+        fn test_draw_rule() {
+            let settings = Settings {
+                rule_style: Style::new(),
+                ..Default::default()
+            };
+            let rule = |cols, label| {
+                let mut output = Vec::new();
+                draw_rule(&mut output, cols, label, &settings).unwrap();
+                String::from_utf8(output).unwrap()
+            };
+            assert_eq!(rule(5, None), "─────\n");
+            assert_eq!(rule(17, Some("Config")), "──── Config ─────\n");
+            assert_eq!(rule(4, Some("Config")), "─ Config ─\n");
+
+            let settings = Settings {
+                rule_style: Style::new(),
+                ascii_rules: true,
+                ..Default::default()
+            };
+            let mut output = Vec::new();
+            draw_rule(&mut output, 10, Some("ab"), &settings).unwrap();
+            assert_eq!(output, b"--- ab ---\n");
+        }
+
+        #[cfg(feature = "anyhow")]
+        #[test]
+        fn test_try_spinner() {
+            let mut cli = Clytia::new(&b""[..], Vec::new());
+            assert_eq!(cli.try_spinner("Adding", || Ok(1 + 1)).unwrap(), 2);
+            let failed = cli.try_spinner("Failing", || -> anyhow::Result<()> {
+                anyhow::bail!("out of cheese")
+            });
+            assert!(matches!(&failed, Err(Error::Task(_))));
+            assert_eq!(failed.unwrap_err().to_string(), "out of cheese");
+        }
+
+        #[test]
+        fn test_format_sparkline() {
+            assert_eq!(format_sparkline(&[]), "");
+            assert_eq!(format_sparkline(&[0.0, 7.0, 3.5, 1.0]), "▁█▅▂");
+            assert_eq!(format_sparkline(&[-2.0, -1.0, 12.0]), "▁▂█");
+            assert_eq!(format_sparkline(&[5.0, 5.0, 5.0]), "▄▄▄");
+            assert_eq!(format_sparkline(&[1.0, f64::NAN, 2.0]), "▁ █");
+            assert_eq!(format_sparkline(&[f64::INFINITY]), " ");
+        }
+
+        #[test]
+        fn test_record_replay() {
+            use std::io::Read;
+
+            let mut log = Vec::new();
+            let mut recorder = Recorder::new(&b"\x1b[B \n"[..], &mut log);
+            let selected = run_multichoice(
+                &mut recorder,
+                &mut Vec::new(),
+                &["cats", "dogs"],
+                HashSet::new(),
+                &Settings::default(),
+            );
+            assert_eq!(selected.unwrap(), vec![1]);
+            let log = String::from_utf8(log).unwrap();
+            let hex: String = log
+                .lines()
+                .map(|line| line.split_once(' ').unwrap().1)
+                .collect();
+            assert_eq!(hex, "1b5b42200a");
+
+            let mut replay = Replay::new(log.as_bytes()).unwrap();
+            let selected = run_multichoice(
+                &mut replay,
+                &mut Vec::new(),
+                &["cats", "dogs"],
+                HashSet::new(),
+                &Settings::default(),
+            );
+            assert_eq!(selected.unwrap(), vec![1]);
+
+            // Reads smaller than what was recorded are split up.
+            let mut replay = Replay::new(&b"0 616263\n5 64"[..]).unwrap();
+            let mut buf = [0; 2];
+            assert_eq!(replay.read(&mut buf).unwrap(), 2);
+            assert_eq!(&buf, b"ab");
+            assert_eq!(replay.read(&mut buf).unwrap(), 1);
+            assert_eq!(replay.read(&mut buf).unwrap(), 1);
+            assert_eq!(buf[0], b'd');
+            assert_eq!(replay.read(&mut buf).unwrap(), 0);
+
+            assert!(matches!(
+                Replay::new(&b"0 6z"[..]),
+                Err(Error::InvalidInput(line)) if line == "0 6z"
+            ));
+        }
+
+        #[test]
+        fn test_status_bar() {
+            let save = termion::cursor::Save.to_string();
+            let restore = termion::cursor::Restore.to_string();
+
+            let mut output = Vec::new();
+            draw_status_bar(&mut output, "Step 1/3", 24, true).unwrap();
+            assert_eq!(
+                String::from_utf8(output).unwrap(),
+                format!(
+                    "\n{}{}\x1b[1;23r{}{}Step 1/3{}",
+                    termion::cursor::Up(1),
+                    save,
+                    termion::cursor::Goto(1, 24),
+                    termion::clear::CurrentLine,
+                    restore
+                )
+            );
+
+            let mut output = Vec::new();
+            draw_status_bar(&mut output, "Step 2/3", 30, false).unwrap();
+            let s = String::from_utf8(output).unwrap();
+            assert!(s.starts_with(&format!("{}\x1b[1;29r", save)));
+
+            let mut output = Vec::new();
+            remove_status_bar(&mut output, 30).unwrap();
+            let s = String::from_utf8(output).unwrap();
+            assert!(s.starts_with(&format!("{}\x1b[r", save)));
+            assert!(s.ends_with(&restore));
+
+            // Nothing to clear, so the terminal isn't needed.
+            let mut cli = Clytia::new(&b""[..], Vec::new());
+            assert!(cli.clear_status_bar().is_ok());
+        }
+
+        #[test]
+        fn test_weighted_percent() {
+            let weights = [3.0, 1.0];
+            assert_eq!(weighted_percent(&weights, 0, 0.0), 0.0);
+            assert_eq!(weighted_percent(&weights, 0, 0.5), 37.5);
+            assert_eq!(weighted_percent(&weights, 1, 0.0), 75.0);
+            assert_eq!(weighted_percent(&weights, 1, 1.0), 100.0);
+            assert_eq!(weighted_percent(&weights, 5, 2.0), 100.0);
+            assert_eq!(weighted_percent(&[], 0, 0.5), 0.0);
+            assert_eq!(weighted_percent(&[0.0], 0, 0.5), 0.0);
+
+            let phases = [("downloading", 3.0), ("unpacking", 1.0)];
+            let label = |phase| {
+                PhaseLabel {
+                    prompt: "Installing",
+                    phases: &phases,
+                    phase: || phase,
+                }
+                .to_string()
+            };
+            assert_eq!(label(0), "Installing: downloading");
+            assert_eq!(label(1), "Installing: unpacking");
+            assert_eq!(label(2), "Installing: unpacking");
+        }
+
+        #[test]
+        fn test_choice_input() {
+            let allowed = ["DE", "DK", "FR", "GB"];
+            let settings = Settings {
+                max_suggestions: 1,
+                ..Default::default()
+            };
+            let run = |mut input: &[u8], output: &mut Vec<u8>| {
+                run_choice_input(&mut input, output, "Country", &allowed, &settings)
+            };
+
+            let mut output = Vec::new();
+            assert_eq!(run(b"F\t\n", &mut output).unwrap(), "FR");
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.contains(&format!("   {}", "FR".dimmed())));
+            assert!(s.ends_with(&format!("{} {}\r\n", "=>".blue(), "FR".green())));
+
+            let mut output = Vec::new();
+            // Tab stops where the candidates differ, and only one of them is listed.
+            assert_eq!(run(b"\tD\tK\n", &mut output).unwrap(), "DK");
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(!s.contains(&format!("   {}", "GB".dimmed())));
+
+            let mut output = Vec::new();
+            // Values outside of the set are rejected until corrected.
+            assert_eq!(run(b"US\n\x7f\x7fGB\n", &mut output).unwrap(), "GB");
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.contains(&format!("{} {} US", "Country".red(), "=>".red())));
+
+            assert!(matches!(
+                run(b"D\n\x03", &mut Vec::new()),
+                Err(Error::NonOptionalInput)
+            ));
+            assert_eq!(run(b"G\x1b[B\n", &mut Vec::new()).unwrap(), "GB");
+        }
+
+        #[test]
+        fn test_suggest_input() {
+            let fruits = ["apple", "apricot", "banana", "blackberry", "cherry"];
+            let suggest = |typed: &str| {
+                fruits
+                    .iter()
+                    .filter(|fruit| fruit.starts_with(typed))
+                    .map(|fruit| fruit.to_string())
+                    .collect()
+            };
+            let settings = Settings {
+                max_suggestions: 3,
+                ..Default::default()
+            };
+            let run = |mut input: &[u8], output: &mut Vec<u8>| {
+                run_suggest_input(&mut input, output, "Fruit", suggest, &settings)
+            };
+
+            let mut output = Vec::new();
+            assert_eq!(run(b"ap\x1b[B\x1b[B\n", &mut output).unwrap(), "apricot");
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.contains(&format!("   {}", "apple".dimmed())));
+            assert!(s.ends_with(&format!("{} {}\r\n", "=>".blue(), "apricot".green())));
+            // Only the first three are listed.
+            assert!(!s.contains("cherry"));
+
+            let mut output = Vec::new();
+            // Typing leaves the suggestions, Up from the first goes back to the input.
+            assert_eq!(run(b"\x1b[Bb\x1b[B\x1b[A\n", &mut output).unwrap(), "b");
+            assert_eq!(run(b"kiwi\x7f\x7fwi\n", &mut output).unwrap(), "kiwi");
+            assert_eq!(
+                run(b"\x1b[B\x1b[B\x1b[B\x1b[B\n", &mut output).unwrap(),
+                "banana"
+            );
+            assert!(matches!(
+                run(b"\n\x03", &mut output),
+                Err(Error::NonOptionalInput)
+            ));
+            assert!(matches!(
+                run(b"ap", &mut output),
+                Err(Error::NonOptionalInput)
+            ));
+            // Tab completes as far as the suggestions agree.
+            assert_eq!(run(b"b\t\n", &mut output).unwrap(), "b");
+            assert_eq!(run(b"ba\t\n", &mut output).unwrap(), "banana");
+        }
+
+        #[test]
+        fn test_spinner_style_by_name() {
+            assert_eq!(SpinnerStyle::by_name("moon"), Some(SpinnerStyle::MOON));
+            assert_eq!(
+                SpinnerStyle::by_name("Braille").unwrap().frames(),
+                SPINNER_SYMBOLS
+            );
+            assert_eq!(SpinnerStyle::by_name("sun"), None);
+            for style in SpinnerStyle::ALL {
+                assert_eq!(SpinnerStyle::by_name(style.name()), Some(style));
+                assert!(!style.frames().is_empty());
+            }
+        }
+
+        #[test]
+        fn test_toast() {
+            let output = Vec::new();
+            let input: Vec<u8> = Vec::new();
+            let mut cli = Clytia::new(&input as &[u8], output);
+            assert!(cli.toast("Copied!", Duration::from_millis(1)).is_ok());
+            let s = std::str::from_utf8(cli.output());
+            assert!(s.is_ok());
+            let s = s.unwrap();
+            assert_eq!(
+                s,
+                format!(
+                    "{0}\r{1}{0}\r",
+                    termion::clear::CurrentLine,
+                    "Copied!".blue()
+                )
+            );
+
+            // Anything but a terminal just gets the message on its own line.
+            let (pipe, _other) = std::os::unix::net::UnixStream::pair().unwrap();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            cli.with_output_fd(Some(pipe.as_raw_fd()));
+            assert!(cli.toast("Copied!", Duration::from_millis(1)).is_ok());
+            assert_eq!(cli.output(), b"Copied!\n");
+        }
+
+        #[test]
+        fn test_options_menu_empty() {
+            let output = Vec::new();
+            let input: Vec<u8> = vec![b'\n'];
+            let mut cli = Clytia::new(&input as &[u8], output);
+            let options: Vec<&str> = Vec::new();
+            assert!(matches!(
+                cli.options_menu(options),
+                Err(Error::EmptyOptions)
+            ));
+            assert!(cli.output().is_empty());
+        }
+
+        #[test]
+        fn test_options_menu_indexed() {
+            let mut cli = Clytia::new(&b"2\n"[..], Vec::new());
+            cli.with_accessible(true);
+            assert_eq!(cli.options_menu_indexed(["Alex", "Alex"]).unwrap(), 1);
+            let options: [&str; 0] = [];
+            assert!(matches!(
+                cli.options_menu_indexed(options),
+                Err(Error::EmptyOptions)
+            ));
+        }
+
+        #[test]
+        fn test_options_menu_with_default() {
+            let options = ["light", "dark", "system"];
+            let settings = Settings::default();
+            let mut input: &[u8] = b"\x1b[B\n";
+            let selected =
+                run_options_menu_from(&mut input, &mut Vec::new(), &options, 1, 24, &settings);
+            assert_eq!(selected.unwrap(), 2);
+
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"\n";
+            let selected =
+                run_options_menu_from(&mut input, &mut output, &options, 2, 24, &settings);
+            assert_eq!(selected.unwrap(), 2);
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.starts_with("   light"));
+
+            // An index out of range is fine too.
+            let mut cli = Clytia::new(&b"2\n"[..], Vec::new());
+            cli.with_accessible(true);
+            assert_eq!(cli.options_menu_with_default(options, 7).unwrap(), "dark");
+        }
+
+        #[test]
+        fn test_multichoice_empty() {
+            let output = Vec::new();
+            let input: Vec<u8> = vec![b'\n'];
+            let mut cli = Clytia::new(&input as &[u8], output);
+            let options: Vec<&str> = Vec::new();
+            assert!(matches!(cli.multichoice(options), Err(Error::EmptyOptions)));
+            assert!(cli.output().is_empty());
+        }
+
+        #[test]
+        fn test_inline_selection() {
+            let settings = Settings {
+                inline_selection: true,
+                ..Settings::default()
+            };
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"\x1b[B\n";
+            let selected =
+                run_options_menu(&mut input, &mut output, &["cats", "dogs"], 24, &settings);
+            assert_eq!(selected.unwrap(), 1);
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.starts_with(&format!(
+                "\n\n\n{}{}\n\r",
+                termion::cursor::Up(3),
+                termion::cursor::Save
+            )));
+            let clear = format!("{}{}", termion::cursor::Up(1), termion::clear::CurrentLine);
+            assert!(s.ends_with(&format!(
+                "{0}{0}{1} {2}\r\n",
+                clear,
+                termion::cursor::Restore,
+                "dogs".green()
+            )));
+        }
+
+        #[test]
+        fn test_visible_cursor() {
+            let settings = Settings {
+                hide_cursor: false,
+                ..Settings::default()
+            };
+            let hide = termion::cursor::Hide.to_string();
+
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"\x1b[B\n";
+            let selected =
+                run_options_menu(&mut input, &mut output, &["cats", "dogs"], 24, &settings);
+            assert_eq!(selected.unwrap(), 1);
+            assert!(!std::str::from_utf8(&output).unwrap().contains(&hide));
+
+            let mut output = Vec::new();
+            let mut input: &[u8] = b" \n";
+            let selected = run_multichoice(
+                &mut input,
+                &mut output,
+                &["cats", "dogs"],
+                HashSet::new(),
+                &settings,
+            );
+            assert_eq!(selected.unwrap(), vec![0]);
+            assert!(!std::str::from_utf8(&output).unwrap().contains(&hide));
+
+            let mut output = Vec::new();
+            draw_progress_bar(&mut output, "Working", 50.0, 40, &settings).unwrap();
+            assert!(!std::str::from_utf8(&output).unwrap().contains(&hide));
+        }
+
+        #[test]
+        fn test_percent_style() {
+            let styles = [
+                Style::new().blue(),
+                Style::new().white().bold(),
+                Style::new().red().italic(),
+                Style::new(),
+            ];
+            let mut drawn = Vec::new();
+            for style in styles {
+                let settings = Settings {
+                    percent_style: Some(style),
+                    ..Settings::default()
+                };
+                let mut output = Vec::new();
+                draw_progress_bar(&mut output, "Working", 42.0, 40, &settings).unwrap();
+                let s = String::from_utf8(output).unwrap();
+                // The percentage has its own style, the bar keeps its colour.
+                assert!(s.ends_with(&format!("{}{}", style.style("042%"), "]".blue())));
+                drawn.push(s);
+            }
+            drawn.dedup();
+            assert_eq!(drawn.len(), styles.len());
+        }
+
+        #[test]
+        fn test_theme_widgets() {
+            let settings = Settings {
+                theme: Theme {
+                    success: AnsiColors::Cyan,
+                    error: AnsiColors::Yellow,
+                    highlight: AnsiColors::Magenta,
+                    ..Theme::default()
+                },
+                ..Settings::default()
+            };
+            let mut output = Vec::new();
+            let mut input: &[u8] = b" \n";
+            let options = ["cats", "dogs"];
+            run_multichoice(&mut input, &mut output, &options, HashSet::new(), &settings).unwrap();
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.contains(&"[X] cats".magenta().to_string()));
+            assert!(s.contains(&"\r[X] cats\r".cyan().to_string()));
+
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"y";
+            let lines = diff_lines("a\n", "b\n");
+            assert!(run_confirm_diff(&mut input, &mut output, &lines, 24, &settings).unwrap());
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.contains(&"- a".yellow().to_string()));
+            assert!(s.contains(&"+ b".cyan().to_string()));
+            assert!(s.ends_with(&format!("{}\r\n", "yes".cyan())));
+        }
+
+        #[test]
+        fn test_progress_bar_narrow() {
+            let settings = Settings::default();
+            for cols in [0, 1, 9] {
+                let mut output = Vec::new();
+                draw_progress_bar(&mut output, "Working", 50.0, cols, &settings).unwrap();
+                let s = std::str::from_utf8(&output).unwrap();
+                assert!(s.ends_with(&Style::new().blue().style("050%").to_string()));
+                assert!(!s.contains("| "));
+            }
+
+            let mut output = Vec::new();
+            draw_progress_bar(&mut output, "Working", 50.0, 10, &settings).unwrap();
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.ends_with(&format!(
+                "{}{}{}",
+                "[> | ".blue(),
+                Style::new().blue().style("050%"),
+                "]".blue()
+            )));
+
+            // The bar and its percentage follow the theme.
+            let settings = Settings {
+                theme: Theme {
+                    prompt: AnsiColors::Cyan,
+                    ..Theme::default()
+                },
+                ..Settings::default()
+            };
+            let mut output = Vec::new();
+            draw_progress_bar(&mut output, "Working", 50.0, 10, &settings).unwrap();
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.ends_with(&format!(
+                "{}{}{}",
+                "[> | ".cyan(),
+                Style::new().cyan().style("050%"),
+                "]".cyan()
+            )));
+        }
+
+        #[test]
+        fn test_warn_ack() {
+            let settings = Settings::default();
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"x";
+            run_warn_ack(&mut input, &mut output, "Disk almost full", &settings).unwrap();
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.starts_with(&format!("\r{}", "! Disk almost full".yellow().bold())));
+            assert!(s.ends_with(&format!(
+                "\r{}{}\r\n",
+                termion::clear::CurrentLine,
+                "! Disk almost full".dimmed()
+            )));
+
+            let mut input: &[u8] = b"\x03";
+            let ret = run_warn_ack(&mut input, &mut Vec::new(), "Disk almost full", &settings);
+            assert!(matches!(ret, Err(Error::NonOptionalInput)));
+            let mut input: &[u8] = b"";
+            let ret = run_warn_ack(&mut input, &mut Vec::new(), "Disk almost full", &settings);
+            assert!(matches!(ret, Err(Error::NonOptionalInput)));
+        }
+
+        #[test]
+        fn test_numbered_select() {
+            let mut cli = Clytia::new(&b"0\nfour\n3\n"[..], Vec::new());
+            assert_eq!(cli.numbered_select(["a", "b", "c"]).unwrap(), "c");
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.starts_with("1) a  2) b  3) c\n"));
+            assert_eq!(
+                s.matches(&"(expected a number from 1 to 3)".red().to_string())
+                    .count(),
+                2
+            );
+
+            let mut cli = Clytia::new(&b""[..], Vec::new());
+            assert!(matches!(
+                cli.numbered_select(Vec::<&str>::new()),
+                Err(Error::EmptyOptions)
+            ));
+
+            // The end of the input stops it asking again.
+            let mut cli = Clytia::new(&b"0\n"[..], Vec::new());
+            assert!(matches!(
+                cli.numbered_select(["a", "b", "c"]),
+                Err(Error::NonOptionalInput)
+            ));
+        }
+
+        #[test]
+        fn test_accessible() {
+            let mut cli = Clytia::new(&b"3\n2\n2, 1 2\n"[..], Vec::new());
+            cli.with_accessible(true);
+            assert_eq!(cli.options_menu(["cats", "dogs"]).unwrap(), "dogs");
+            assert_eq!(
+                cli.multichoice(["cats", "dogs"]).unwrap(),
+                vec!["cats", "dogs"]
+            );
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.starts_with("1) cats\n2) dogs\n"));
+            assert!(s.contains(&"(expected a number from 1 to 2)".red().to_string()));
+            assert!(!s.contains(&termion::cursor::Up(1).to_string()));
+
+            let mut cli = Clytia::new(&b""[..], Vec::new());
+            cli.with_accessible(true);
+            let ret = cli.static_background_spinner("Waiting", || -> Result<(), ()> {
+                std::thread::sleep(Duration::from_millis(120));
+                Ok(())
+            });
+            assert!(ret.unwrap().is_ok());
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.starts_with("Waiting\n\r"));
+            assert!(!s.contains(SPINNER_SYMBOLS[0]));
+
+            let mut output = Vec::new();
+            let mut reported = 0;
+            for progress in [10.0, 30.0, 40.0, 99.0] {
+                report_milestone(&mut output, progress, &mut reported).unwrap();
+            }
+            assert_eq!(output, b"25% complete\n75% complete\n");
+        }
+
+        #[test]
+        fn test_accessible_inputs() {
+            let input = b"x\na\n99\n\nFR\n(555) 123-45\n555 123 4567\n";
+            let mut cli = Clytia::new(&input[..], Vec::new());
+            cli.with_accessible(true);
+            assert_eq!(cli.confirm_all("Delete?").unwrap(), ConfirmAll::YesToAll);
+            assert_eq!(cli.pick_number("Port", 1..=10, 4).unwrap(), 4);
+            assert_eq!(cli.choice_input("Country", &["DE", "FR"]).unwrap(), "FR");
+            let phone = cli.masked_pattern_input("Phone", "(___) ___-____");
+            assert_eq!(phone.unwrap(), "(555) 123-4567");
+            assert!(matches!(
+                cli.choice_input("Country", &["DE"]),
+                Err(Error::NonOptionalInput)
+            ));
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.contains(&"(expected y, n, a or q)".red().to_string()));
+            assert!(s.contains(&"(expected a number from 1 to 10)".red().to_string()));
+            assert!(s.contains(&"(expected 10 digits)".red().to_string()));
+            assert!(!s.contains(&termion::cursor::Up(1).to_string()));
+
+            let mut cli = Clytia::new(&b"1\n1, 2\n2, 3\n\n0\n"[..], Vec::new());
+            cli.with_accessible(true);
+            let options = ["cheese", "ham", "olives"];
+            let toppings = cli.multichoice_limited(options, 2..=2).unwrap();
+            assert_eq!(toppings, vec!["cheese", "ham"]);
+            let order = cli.quantity_select(&options).unwrap();
+            assert_eq!(order, vec![("ham", 1)]);
+            let (selected, confirmed) = cli.multichoice_cancellable(options).unwrap();
+            assert!(selected.is_empty() && !confirmed);
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.contains(&"(Select 1 more option)".red().to_string()));
+
+            let mut cli = Clytia::new(&b"y\n"[..], Vec::new());
+            cli.with_accessible(true);
+            cli.status_line("Starting").unwrap();
+            cli.clear_status_line().unwrap();
+            assert!(cli.confirm_diff("a\nb\n", "a\nc\n").unwrap());
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            let removed = "- b".red().to_string();
+            assert!(s.starts_with(&format!("Starting\n  a\n{}\n", removed)));
+        }
+
+        #[test]
+        fn test_parse_numbered() {
+            assert_eq!(parse_numbered(" 2 ", 3, false), Some(vec![1]));
+            assert_eq!(parse_numbered("3,1, 3", 3, true), Some(vec![0, 2]));
+            assert_eq!(parse_numbered("", 3, true), Some(vec![]));
+            assert_eq!(parse_numbered("", 3, false), None);
+            assert_eq!(parse_numbered("1 2", 3, false), None);
+            assert_eq!(parse_numbered("0", 3, true), None);
+            assert_eq!(parse_numbered("4", 3, false), None);
+            assert_eq!(parse_numbered("two", 3, false), None);
+        }
+
+        #[test]
+        fn test_multichoice_limited() {
+            let options = ["cheese", "ham", "olives"];
+            let settings = Settings::default();
+            let mut output = Vec::new();
+            // Nothing, then all three, then two of them.
+            let mut input: &[u8] = b"\n \x1b[B \x1b[B \n \n";
+            let selected =
+                run_multichoice_limited(&mut input, &mut output, &options, 1..=2, &settings);
+            assert_eq!(selected.unwrap(), vec![0, 1]);
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.contains(&"Select 1 more option".red().to_string()));
+            assert!(s.contains(&"Deselect 1 option".red().to_string()));
+
+            // Running out of input with too few selected doesn't wait forever.
+            let mut input: &[u8] = b"\n";
+            let selected =
+                run_multichoice_limited(&mut input, &mut Vec::new(), &options, 1..=2, &settings);
+            assert!(matches!(selected, Err(Error::NonOptionalInput)));
+
+            let input: Vec<u8> = Vec::new();
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            assert!(matches!(
+                cli.multichoice_limited(options, 4..=5),
+                Err(Error::UnmetLimits)
+            ));
+            #[allow(clippy::reversed_empty_ranges)]
+            let empty = 2..=1;
+            assert!(matches!(
+                cli.multichoice_limited(options, empty),
+                Err(Error::UnmetLimits)
+            ));
+        }
+
+        #[test]
+        fn test_log_tail() {
+            let log = LogTail::new(2);
+            log.log("one");
+            log.log("two\nthree");
+            log.set_progress(150.0);
+            assert_eq!(log.tail(), vec!["two", "three"]);
+            assert_eq!(log.progress(), 100.0);
+
+            let settings = Settings::default();
+            let mut output = Vec::new();
+            let tail = vec!["compiling clytia".to_string()];
+            draw_log_tail(&mut output, &tail, 2, 12, "Build", 50.0, &settings).unwrap();
+            let s = std::str::from_utf8(&output).unwrap();
+            let clear = format!("{}{}", termion::cursor::Up(1), termion::clear::CurrentLine);
+            assert!(s.starts_with(&format!(
+                "{}{}{}",
+                termion::clear::CurrentLine,
+                clear,
+                clear
+            )));
+            // The empty line goes above the line logged, which is cut short.
+            assert!(s.contains(&format!("\r\n\r{}\n\n", "compiling cl".dimmed())));
+            assert!(s.contains("Build\n"));
+        }
+
+        #[test]
+        fn test_menu_wrap() {
+            let settings = Settings {
+                menu_wrap: false,
+                ..Default::default()
+            };
+            let options = ["cats", "dogs"];
+            let mut input: &[u8] = b"\x1b[A\n";
+            let selected = run_options_menu(&mut input, &mut Vec::new(), &options, 24, &settings);
+            assert_eq!(selected.unwrap(), 0);
+            let mut input: &[u8] = b"\x1b[B\x1b[B \n";
+            let selected = run_multichoice(
+                &mut input,
+                &mut Vec::new(),
+                &options,
+                HashSet::new(),
+                &settings,
+            );
+            assert_eq!(selected.unwrap(), vec![1]);
+            let mut input: &[u8] = b"\x1b[A\n";
+            let selected = run_options_menu(
+                &mut input,
+                &mut Vec::new(),
+                &options,
+                24,
+                &Settings::default(),
+            );
+            assert_eq!(selected.unwrap(), 1);
+
+            // The global setting is back once the menu is done, even if it fails.
+            let input: Vec<u8> = vec![b'\n'];
+            let mut cli = Clytia::new(&input as &[u8], Vec::new());
+            assert!(cli.options_menu_with_wrap(options, false).is_err());
+            assert!(cli.settings.menu_wrap);
+        }
+
+        #[test]
+        fn test_options_menu_single_item() {
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"\x1b[A\x1b[B\x1b[B\n";
+            let selected =
+                run_options_menu(&mut input, &mut output, &["only"], 24, &Settings::default());
+            assert_eq!(selected.unwrap(), 0);
+            let s = std::str::from_utf8(&output).unwrap();
+            let clear = format!("{}{}", termion::cursor::Up(1), termion::clear::CurrentLine);
+            let drawn = format!("{}{}\r\n", "=> only".blue(), termion::cursor::Hide);
+            assert_eq!(
+                s,
+                format!(
+                    "{0}{1}\r{0}{1}\r{0}{1}\r{0}{1}{2}\n",
+                    drawn,
+                    clear,
+                    "\r=> only\r".green()
+                )
+            );
+        }
+
+        #[test]
+        fn test_options_menu_two_items() {
+            let mut output = Vec::new();
+            // Down twice wraps back to the top, then up wraps to the bottom.
+            let mut input: &[u8] = b"\x1b[B\x1b[B\x1b[A\n";
+            let selected = run_options_menu(
+                &mut input,
+                &mut output,
+                &["cats", "dogs"],
+                24,
+                &Settings::default(),
+            );
+            assert_eq!(selected.unwrap(), 1);
+            let s = std::str::from_utf8(&output).unwrap();
+            let clear = format!("{}{}", termion::cursor::Up(1), termion::clear::CurrentLine);
+            assert!(s.ends_with(&format!("{0}{0}{1}\n", clear, "\r=> dogs\r".green())));
+
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"\x1b[A\x1b[A\n";
+            let selected = run_options_menu(
+                &mut input,
+                &mut output,
+                &["cats", "dogs"],
+                24,
+                &Settings::default(),
+            );
+            assert_eq!(selected.unwrap(), 0);
+        }
+
+        #[test]
+        fn test_options_menu_debounced() {
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"\x1b[B\x1b[B\x1b[B\n";
+            let settings = Settings {
+                menu_debounce: Some(Duration::from_millis(1)),
+                ..Settings::default()
+            };
+            let selected =
+                run_options_menu(&mut input, &mut output, &["cats", "dogs"], 24, &settings);
+            assert_eq!(selected.unwrap(), 1);
+            // All the keys arrive in one batch, so the menu is never redrawn before enter.
+            let s = std::str::from_utf8(&output).unwrap();
+            assert_eq!(s.matches(&format!("{}", termion::cursor::Up(1))).count(), 2);
+        }
+
+        #[test]
+        fn test_key_batches_split_escape() {
+            let input = Chunks(vec![b"\x1b[B\x1b[", b"A \x1b"]);
+            let batches: Vec<Vec<Key>> = KeyBatches::new(input, Some(Duration::from_millis(1)))
+                .collect::<std::io::Result<_>>()
+                .unwrap();
+            assert_eq!(
+                batches,
+                vec![vec![Key::Down], vec![Key::Up, Key::Char(' '), Key::Esc]]
+            );
+        }
+
+        #[test]
+        fn test_multichoice_single_item() {
+            let mut output = Vec::new();
+            let mut input: &[u8] = b" \x1b[B \x1b[A \n";
+            let selected = run_multichoice(
+                &mut input,
+                &mut output,
+                &["only"],
+                HashSet::new(),
+                &Settings::default(),
+            );
+            assert_eq!(selected.unwrap(), vec![0]);
+            let s = std::str::from_utf8(&output).unwrap();
+            let clear = format!("{}{}", termion::cursor::Up(1), termion::clear::CurrentLine);
+            assert!(s.ends_with(&format!("{}\r{}\n", clear, "\r[X] only\r".green())));
+        }
+
+        #[test]
+        fn test_multichoice_preselected() {
+            let mut output = Vec::new();
+            // Untick the first preselected item, leaving the other.
+            let mut input: &[u8] = b" \n";
+            let selected = run_multichoice(
+                &mut input,
+                &mut output,
+                &["news", "offers", "updates"],
+                HashSet::from([0, 2]),
+                &Settings::default(),
+            );
+            assert_eq!(selected.unwrap(), vec![2]);
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.contains("[ ] offers"));
+            assert!(s.contains("[X] updates"));
+
+            let mut cli = Clytia::new(&b"1, 3\n"[..], Vec::new());
+            cli.with_accessible(true);
+            let selected = cli.multichoice_with_defaults(["news", "offers", "updates"], &[1, 7]);
+            assert_eq!(selected.unwrap(), vec!["news", "updates"]);
+        }
+
+        #[test]
+        fn test_multichoice_two_items() {
+            let mut output = Vec::new();
+            // Select the bottom item by wrapping up, toggle it off again after wrapping down twice,
+            // then select the top item.
+            let mut input: &[u8] = b"\x1b[A \x1b[B\x1b[B \x1b[A \n";
+            let selected = run_multichoice(
+                &mut input,
+                &mut output,
+                &["cats", "dogs"],
+                HashSet::new(),
+                &Settings::default(),
+            );
+            assert_eq!(selected.unwrap(), vec![0]);
+            let s = std::str::from_utf8(&output).unwrap();
+            let clear = format!("{}{}", termion::cursor::Up(1), termion::clear::CurrentLine);
+            assert!(s.ends_with(&format!("{0}{0}\r{1}\n", clear, "\r[X] cats\r".green())));
+
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"\x1b[B \x1b[B \n";
+            let selected = run_multichoice(
+                &mut input,
+                &mut output,
+                &["cats", "dogs"],
+                HashSet::new(),
+                &Settings::default(),
+            );
+            assert_eq!(selected.unwrap(), vec![0, 1]);
+        }
 
-        for c in input_stream.keys() {
-            match c? {
-                Key::Up => highlighted = (highlighted + options_count - 1) % options_count,
-                Key::Down => highlighted = (highlighted + 1) % options_count,
-                Key::Char(' ') if selected.contains(&highlighted) => {
-                    selected.remove(&highlighted);
-                }
-                Key::Char(' ') => {
-                    selected.insert(highlighted);
-                }
-                Key::Char('\n') => break,
-                _ => {}
-            }
+        #[test]
+        fn test_format_two_columns() {
+            let items = [
+                ("build".to_string(), "Compile the project".to_string(), 0),
+                ("test".to_string(), "Run the tests".to_string(), 1),
+                ("doc".to_string(), String::new(), 2),
+            ];
+            assert_eq!(
+                format_two_columns(&items, 80),
+                vec!["build  Compile the project", "test   Run the tests", "doc"]
+            );
+            assert_eq!(
+                format_two_columns(&items, 20),
+                vec!["build  Compile t…", "test   Run the t…", "doc"]
+            );
+            assert_eq!(format_two_columns(&items, 8), vec!["build", "test", "doc"]);
 
-            for _ in 0..options_count {
-                write!(
-                    output_stream,
-                    "{}{}",
-                    termion::cursor::Up(1),
-                    termion::clear::CurrentLine
-                )?;
-            }
-            write!(output_stream, "\r")?;
-            for (index, option) in options.as_ref().iter().enumerate() {
-                if selected.contains(&index) {
-                    if highlighted == index {
-                        writeln!(output_stream, "\r{}", format!("[X] {}", option).blue())?;
-                    } else {
-                        writeln!(output_stream, "\r[X] {}", option)?;
-                    }
-                } else if highlighted == index {
-                    writeln!(output_stream, "\r{}", format!("[ ] {}", option).blue())?;
-                } else {
-                    writeln!(output_stream, "\r[ ] {}", option)?;
-                }
-            }
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"\x1b[B\n";
+            let rows = format_two_columns(&items, 80);
+            let selected =
+                run_options_menu(&mut input, &mut output, &rows, 24, &Settings::default());
+            assert_eq!(selected.unwrap(), 1);
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.contains(&"=> test   Run the tests".blue().to_string()));
         }
 
-        for _ in 0..options_count {
-            write!(
-                output_stream,
-                "{}{}",
-                termion::cursor::Up(1),
-                termion::clear::CurrentLine
-            )?;
+        #[test]
+        fn test_quantity_select() {
+            let mut output = Vec::new();
+            // Include coffee at 3, tea dropped to 0, leave cake out at 2.
+            let mut input: &[u8] = b" \x1b[C\x1b[C\x1b[B \x1b[D\x1b[B\x1b[C\n";
+            let selected = run_quantity_select(
+                &mut input,
+                &mut output,
+                &["coffee", "tea", "cake"],
+                &Settings::default(),
+            );
+            assert_eq!(selected.unwrap(), vec![(0, 3)]);
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.starts_with(&format!(
+                "{}\r{}\n\r[ ] tea × 1\n\r[ ] cake × 1\n",
+                termion::cursor::Hide,
+                "[ ] coffee × 1".blue()
+            )));
+            assert!(s.contains(&"[ ] cake × 2".blue().to_string()));
+            assert!(s.ends_with(&format!("\r{}\n", "\r[X] coffee × 3\r".green())));
         }
-        write!(output_stream, "\r")?;
 
-        let returns = options
-            .as_ref()
-            .iter()
-            .enumerate()
-            .filter(|(index, _)| selected.contains(index))
-            .map(|(_, option)| option.clone())
-            .collect();
+        #[test]
+        fn test_checkboxes() {
+            let mut cli = Clytia::new(&b""[..], Vec::new());
+            cli.with_checkboxes("☑", "( )");
+            let settings = &cli.settings;
+            assert_eq!(settings.checkbox(true), "☑  ");
+            assert_eq!(settings.checkbox(false), "( )");
+
+            let mut output = Vec::new();
+            draw_multichoice(
+                &mut output,
+                &["a", "b"],
+                1,
+                &HashSet::from([0, 1]),
+                0,
+                settings,
+            )
+            .unwrap();
+            assert_eq!(
+                std::str::from_utf8(&output).unwrap(),
+                format!("\r☑   a\n\r{}\n", "☑   b".blue())
+            );
 
-        for option in &returns {
-            writeln!(output_stream, "{}", format!("\r[X] {}\r", option).green())?;
+            let mut output = Vec::new();
+            let mut input: &[u8] = b" \n";
+            let selected = run_multichoice(
+                &mut input,
+                &mut output,
+                &["a", "b"],
+                HashSet::new(),
+                settings,
+            );
+            assert_eq!(selected.unwrap(), vec![0]);
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.ends_with(&format!("{}\n", "\r☑   a\r".green())));
         }
 
-        Ok(returns)
-    }
-}
+        #[test]
+        fn test_multichoice_cancellable() {
+            let mut output = Vec::new();
+            let mut input: &[u8] = b" \x1b[B \x03";
+            let selected = run_multichoice_cancellable(
+                &mut input,
+                &mut output,
+                &["cats", "dogs", "rabbits"],
+                &Settings::default(),
+            );
+            assert_eq!(selected.unwrap(), (vec![0, 1], false));
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(!s.contains(&"\r[X] cats\r".green().to_string()));
 
-impl Default for Clytia<Stdin, Stdout> {
-    fn default() -> Self {
-        Self {
-            input: io::stdin(),
-            output: io::stdout(),
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"\x1b[B \n";
+            let selected = run_multichoice_cancellable(
+                &mut input,
+                &mut output,
+                &["cats", "dogs"],
+                &Settings::default(),
+            );
+            assert_eq!(selected.unwrap(), (vec![1], true));
+
+            // Cancelling a plain multichoice drops what was selected.
+            let mut output = Vec::new();
+            let mut input: &[u8] = b" \x03\x1b[B \n";
+            let selected = run_multichoice(
+                &mut input,
+                &mut output,
+                &["cats", "dogs"],
+                HashSet::new(),
+                &Settings::default(),
+            );
+            assert!(matches!(selected, Err(Error::Cancelled)));
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(!s.contains("=>"));
+
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"\x1b[B\x03";
+            let selected = run_options_menu(
+                &mut input,
+                &mut output,
+                &["cats", "dogs"],
+                24,
+                &Settings::default(),
+            );
+            assert!(matches!(selected, Err(Error::Cancelled)));
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(!s.contains(&"\r=> dogs\r".green().to_string()));
         }
-    }
-}
 
-#[cfg(test)]
-mod tests {
-    mod non_interactive {
-        use std::time::Duration;
+        #[test]
+        fn test_menu_position() {
+            let settings = Settings {
+                menu_position: true,
+                ..Settings::default()
+            };
+            let clear = format!("{}{}", termion::cursor::Up(1), termion::clear::CurrentLine);
 
-        use owo_colors::OwoColorize;
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"\x1b[A\n";
+            let selected =
+                run_options_menu(&mut input, &mut output, &["cats", "dogs"], 24, &settings);
+            assert_eq!(selected.unwrap(), 1);
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.starts_with(&format!("\r{}\r\n", "(1/2)".dimmed())));
+            assert!(s.contains(&format!("\r{}\r\n", "(2/2)".dimmed())));
+            assert!(s.ends_with(&format!("{0}{0}{0}{1}\n", clear, "\r=> dogs\r".green())));
 
-        use crate::{Clytia, SPINNER_SYMBOLS};
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"\x1b[B \n";
+            let selected = run_multichoice(
+                &mut input,
+                &mut output,
+                &["cats", "dogs"],
+                HashSet::new(),
+                &settings,
+            );
+            assert_eq!(selected.unwrap(), vec![1]);
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.contains(&format!("\r{}\r\n", "(2/2)".dimmed())));
+            assert!(s.ends_with(&format!("{0}{0}{0}\r{1}\n", clear, "\r[X] dogs\r".green())));
+        }
 
         #[test]
-        fn test_parsed_input_with_default() {
-            let output = Vec::new();
-            let input: Vec<u8> = vec![b'1', b'\n'];
-            let mut cli = Clytia::new(&input as &[u8], output);
-            assert!(cli.parsed_input("input a number", Some(0)).is_ok());
-            let s = std::str::from_utf8(cli.output());
-            assert!(s.is_ok());
-            let s = s.unwrap();
+        fn test_tree_select() {
+            let tree = TreeNode::branch(
+                "src",
+                vec![
+                    TreeNode::leaf("lib.rs", 1),
+                    TreeNode::branch("bin", vec![TreeNode::leaf("main.rs", 2)]),
+                ],
+            );
+            let expanded = HashSet::from([vec![], vec![1]]);
             assert_eq!(
-                s,
-                format!(
-                    "{} {} {} ",
-                    "input a number".blue(),
-                    "(default: 0)".magenta(),
-                    "=>".blue()
-                )
+                visible_tree_nodes(&tree, &expanded),
+                vec![vec![], vec![0], vec![1], vec![1, 0]]
+            );
+            assert_eq!(
+                visible_tree_nodes(&tree, &HashSet::from([vec![]])),
+                vec![vec![], vec![0], vec![1]]
+            );
+            assert_eq!(
+                tree_leaves(&tree),
+                vec![
+                    (vec![0], "src / lib.rs".to_string()),
+                    (vec![1, 0], "src / bin / main.rs".to_string())
+                ]
             );
+
+            let settings = Settings::default();
+            let mut output = Vec::new();
+            // Down to `bin`, expand it, move into it and pick `main.rs`.
+            let mut input: &[u8] = b"\x1b[B\x1b[B\x1b[C\x1b[C\n";
+            let path = run_tree_select(&mut input, &mut output, &tree, 24, &settings);
+            assert_eq!(path.unwrap(), vec![1, 0]);
+            assert_eq!(tree.clone().into_value(&[1, 0]), Some(2));
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.contains("   ▼ src"));
+            assert!(s.contains("     ▶ bin"));
+            assert!(s.contains(&"=>   ▼ bin".blue().to_string()));
+            assert!(s.contains(&"=>       main.rs".blue().to_string()));
+            assert!(s.ends_with(&format!("{}\n", "\r=> main.rs\r".green())));
+
+            // Left moves up to the branch then collapses it, enter expands it again.
+            let mut input: &[u8] = b"\x1b[B\x1b[D\x1b[D\n\x1b[B\n";
+            let path = run_tree_select(&mut input, &mut Vec::new(), &tree, 24, &settings);
+            assert_eq!(path.unwrap(), vec![0]);
+
+            let mut input: &[u8] = b"\x1b[B\x03";
+            let path = run_tree_select(&mut input, &mut Vec::new(), &tree, 24, &settings);
+            assert!(matches!(path, Err(Error::Cancelled)));
+            let mut input: &[u8] = b"\x1b[B";
+            let path = run_tree_select(&mut input, &mut Vec::new(), &tree, 24, &settings);
+            assert!(matches!(path, Err(Error::NonOptionalInput)));
+
+            let mut cli = Clytia::new(&b"2\n"[..], Vec::new());
+            cli.with_accessible(true);
+            assert_eq!(cli.tree_select(tree).unwrap(), 2);
+            let s = std::str::from_utf8(cli.output()).unwrap();
+            assert!(s.starts_with("1) src / lib.rs\n2) src / bin / main.rs\n"));
+            let empty = TreeNode::<u32>::branch("empty", vec![TreeNode::branch("also", vec![])]);
+            assert!(matches!(cli.tree_select(empty), Err(Error::EmptyOptions)));
         }
 
         #[test]
-        fn test_parsed_input_without_default() {
-            let output = Vec::new();
-            let input: Vec<u8> = vec![b'1', b'\n'];
-            let mut cli = Clytia::new(&input as &[u8], output);
-            assert!(cli.parsed_input::<_, usize>("input a number", None).is_ok());
-            let s = std::str::from_utf8(cli.output());
-            assert!(s.is_ok());
-            let s = s.unwrap();
-            assert_eq!(s, format!("{} {} ", "input a number".blue(), "=>".blue()));
+        fn test_filtered_menu() {
+            let options = ["Europe/London", "Europe/Paris", "America/New_York"];
+            assert_eq!(filter_options(&options, ""), vec![0, 1, 2]);
+            assert_eq!(filter_options(&options, "EU"), vec![0, 1]);
+            assert_eq!(filter_options(&options, "york"), vec![2]);
+            assert!(filter_options(&options, "tokyo").is_empty());
+
+            let settings = Settings::default();
+            let mut output = Vec::new();
+            // Narrow to Europe, then move to the second match.
+            let mut input: &[u8] = b"eu\x1b[B\n";
+            let selected = run_filtered_menu(&mut input, &mut output, &options, 24, &settings);
+            assert_eq!(selected.unwrap(), 1);
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.contains(&format!("\r{} eu", "/".blue())));
+            assert!(s.ends_with(&format!("{}\n", "\r=> Europe/Paris\r".green())));
+
+            // Enter does nothing without a match, backspace widens the filter again.
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"yx\n\x7f\n";
+            let selected = run_filtered_menu(&mut input, &mut output, &options, 24, &settings);
+            assert_eq!(selected.unwrap(), 2);
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.contains(&"(no matches)".dimmed().to_string()));
+
+            let mut input: &[u8] = b"eu\x03";
+            let selected = run_filtered_menu(&mut input, &mut Vec::new(), &options, 24, &settings);
+            assert!(matches!(selected, Err(Error::Cancelled)));
+
+            // The input ending, with or without any matches, isn't a selection.
+            for input in [&b"tokyo"[..], b"eu"] {
+                let mut input = input;
+                let selected =
+                    run_filtered_menu(&mut input, &mut Vec::new(), &options, 24, &settings);
+                assert!(matches!(selected, Err(Error::NonOptionalInput)));
+            }
         }
 
         #[test]
-        fn test_static_spinner_success() {
-            let output = Vec::new();
-            let input: Vec<u8> = Vec::new();
-            let mut cli = Clytia::new(&input as &[u8], output);
-            assert!(cli
-                .static_background_spinner::<_, _, (), ()>("Wait 1ms", || {
-                    std::thread::sleep(Duration::from_millis(1));
-                    Ok(())
-                })
-                .is_ok());
-            let s = std::str::from_utf8(cli.output());
-            assert!(s.is_ok());
-            let s = s.unwrap();
+        fn test_options_menu_window() {
+            let settings = Settings::default();
+            // A terminal 6 rows tall fits 3 options under the prompt, between the lines
+            // saying how many are out of view.
+            let options: Vec<String> = (1..=10).map(|i| format!("option {}", i)).collect();
+            assert_eq!(options_menu_window(options.len(), 6, &settings), Some(3));
+            assert_eq!(options_menu_window(3, 6, &settings), None);
+            assert_eq!(options_menu_window(options.len(), 1, &settings), Some(1));
+            let settings_height = Settings {
+                menu_height: Some(2),
+                ..Settings::default()
+            };
             assert_eq!(
-                s,
-                format!(
-                    "\r{} Wait 1ms\r{}\n",
-                    SPINNER_SYMBOLS[0].blue(),
-                    "✔️  Wait 1ms".green()
-                )
+                options_menu_window(options.len(), 24, &settings_height),
+                Some(2)
+            );
+            assert_eq!(
+                options_menu_window(options.len(), 6, &settings_height),
+                Some(2)
             );
+
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"\x1b[B\x1b[B\x1b[B\x1b[B\n";
+            let selected = run_options_menu(&mut input, &mut output, &options, 6, &settings);
+            assert_eq!(selected.unwrap(), 4);
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.starts_with(&format!(
+                "\r\n{1}{0}\r\n   option 2{0}\r\n   option 3{0}\r\n\r{2}\n",
+                settings.cursor_hide(),
+                "=> option 1".blue(),
+                "↓ 7 more".dimmed()
+            )));
+            assert!(s.contains(&format!(
+                "\r{1}\n   option 3{0}\r\n   option 4{0}\r\n{2}{0}\r\n\r{3}\n",
+                settings.cursor_hide(),
+                "↑ 2 more".dimmed(),
+                "=> option 5".blue(),
+                "↓ 5 more".dimmed()
+            )));
+            // Only the window's lines and the two around it are cleared between redraws.
+            let clear = format!("{}{}", termion::cursor::Up(1), termion::clear::CurrentLine);
+            assert!(!s.contains(&clear.repeat(6)));
         }
 
         #[test]
-        fn test_static_spinner_failure() {
-            let output = Vec::new();
-            let input: Vec<u8> = Vec::new();
-            let mut cli = Clytia::new(&input as &[u8], output);
-            assert!(cli
-                .static_background_spinner::<_, _, (), ()>("Wait 1ms", || {
-                    std::thread::sleep(Duration::from_millis(1));
-                    Err(())
-                })
-                .is_ok());
-            let s = std::str::from_utf8(cli.output());
-            assert!(s.is_ok());
-            let s = s.unwrap();
+        fn test_multichoice_window() {
+            let settings = Settings {
+                menu_height: Some(2),
+                ..Settings::default()
+            };
+            let options = ["a", "b", "c", "d"];
+            let mut output = Vec::new();
+            draw_multichoice(&mut output, &options, 2, &HashSet::from([0]), 1, &settings).unwrap();
+            let s = std::str::from_utf8(&output).unwrap();
             assert_eq!(
                 s,
                 format!(
-                    "\r{} Wait 1ms\r{}\n",
-                    SPINNER_SYMBOLS[0].blue(),
-                    "❌ Wait 1ms".red()
+                    "\r{}\n\r[ ] b\n\r{}\n\r{}\n",
+                    "↑ 1 more".dimmed(),
+                    "[ ] c".blue(),
+                    "↓ 1 more".dimmed()
                 )
             );
+
+            // Selections out of view are kept, and wrapping around scrolls back to the top.
+            let clear = format!("{}{}", termion::cursor::Up(1), termion::clear::CurrentLine);
+            let mut output = Vec::new();
+            let mut input: &[u8] = b" \x1b[B\x1b[B\x1b[B \x1b[B\n";
+            let selected =
+                run_multichoice(&mut input, &mut output, &options, HashSet::new(), &settings);
+            assert_eq!(selected.unwrap(), vec![0, 3]);
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.contains(&format!(
+                "\r{}\n\r[ ] c\n\r{}\n\r\n",
+                "↑ 2 more".dimmed(),
+                "[X] d".blue()
+            )));
+            assert!(s.contains(&format!(
+                "{0}{0}{0}{0}\r\r\n\r{1}\n\r[ ] b\n",
+                clear,
+                "[X] a".blue()
+            )));
+
+            // Lists that fit aren't windowed.
+            let mut output = Vec::new();
+            draw_multichoice(&mut output, &options[..2], 0, &HashSet::new(), 0, &settings).unwrap();
+            assert_eq!(
+                output,
+                format!("\r{}\n\r[ ] b\n", "[ ] a".blue()).as_bytes()
+            );
         }
 
         #[test]
-        fn test_dynamic_spinner_success() {
-            let output = Vec::new();
-            let input: Vec<u8> = Vec::new();
-            let mut cli = Clytia::new(&input as &[u8], output);
-            assert!(cli
-                .dynamic_background_spinner::<_, _, _, (), ()>(
-                    || "Wait 1ms",
-                    || {
-                        std::thread::sleep(Duration::from_millis(1));
-                        Ok(())
-                    }
-                )
-                .is_ok());
-            let s = std::str::from_utf8(cli.output());
-            assert!(s.is_ok());
-            let s = s.unwrap();
-            assert_eq!(
-                s,
-                format!(
-                    "{}\r{} Wait 1ms{}\r{}\n",
-                    termion::clear::CurrentLine,
-                    SPINNER_SYMBOLS[0].blue(),
-                    termion::clear::CurrentLine,
-                    "✔️  Wait 1ms".green()
-                )
+        fn test_scroll_offset() {
+            assert_eq!(scroll_offset(0, 0, 3), 0);
+            assert_eq!(scroll_offset(0, 2, 3), 0);
+            assert_eq!(scroll_offset(0, 3, 3), 1);
+            assert_eq!(scroll_offset(4, 5, 3), 4);
+            assert_eq!(scroll_offset(4, 1, 3), 1);
+            assert_eq!(scroll_offset(1, 9, 3), 7);
+        }
+
+        #[test]
+        fn test_multichoice_compact() {
+            let mut output = Vec::new();
+            let mut input: &[u8] = b" \x1b[B\x1b[B \n";
+            let settings = Settings {
+                compact_multichoice: true,
+                ..Settings::default()
+            };
+            let selected = run_multichoice(
+                &mut input,
+                &mut output,
+                &["cats", "dogs", "rabbits"],
+                HashSet::new(),
+                &settings,
             );
+            assert_eq!(selected.unwrap(), vec![0, 2]);
+            let s = std::str::from_utf8(&output).unwrap();
+            let clear = format!("{}{}", termion::cursor::Up(1), termion::clear::CurrentLine);
+            assert!(s.ends_with(&format!(
+                "{0}{0}{0}\r{1}\n",
+                clear,
+                "\rSelected: cats, rabbits\r".green()
+            )));
         }
 
         #[test]
-        fn test_dynamic_spinner_failure() {
-            let output = Vec::new();
-            let input: Vec<u8> = Vec::new();
-            let mut cli = Clytia::new(&input as &[u8], output);
-            assert!(cli
-                .dynamic_background_spinner::<_, _, _, (), ()>(
-                    || "Wait 1ms",
-                    || {
-                        std::thread::sleep(Duration::from_millis(1));
-                        Err(())
-                    }
-                )
-                .is_ok());
-            let s = std::str::from_utf8(cli.output());
-            assert!(s.is_ok());
-            let s = s.unwrap();
+        fn test_live_validated_input() {
+            let settings = Settings::default();
+            let mut output = Vec::new();
+            // Enter is ignored while the input is invalid.
+            let mut input = Chunks(vec![b"80", b"\r", b"\x7f\x7f8080", b"\r"]);
+            let port = run_live_validated_input(
+                &mut input,
+                &mut output,
+                "Port",
+                |port: &u16| *port >= 1024,
+                &settings,
+            );
+            assert_eq!(port.unwrap(), 8080);
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.contains(&format!("80 {}", "✖".red())));
+            assert!(s.contains(&format!("8080 {}", "✔".green())));
+            assert_eq!(s.matches(BRACKETED_PASTE_ON).count(), 1);
+            assert!(s.ends_with(BRACKETED_PASTE_OFF));
+
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"80\r\x7f\x7f8080\r";
+            let port = run_live_validated_input(
+                &mut input,
+                &mut output,
+                "Port",
+                |port: &u16| *port >= 1024,
+                &settings,
+            );
+            assert_eq!(port.unwrap(), 8080);
+
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"80\x03";
+            let port = run_live_validated_input(
+                &mut input,
+                &mut output,
+                "Port",
+                |port: &u16| *port >= 1024,
+                &settings,
+            );
+            assert!(matches!(port, Err(Error::NonOptionalInput)));
+
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"80";
+            let port = run_live_validated_input(
+                &mut input,
+                &mut output,
+                "Port",
+                |port: &u16| *port >= 1024,
+                &settings,
+            );
+            assert!(matches!(port, Err(Error::InvalidInput(input)) if input == "80"));
+        }
+
+        #[test]
+        fn test_multichoice_confirmed() {
+            let mut output = Vec::new();
+            // Select dogs and decline, then add cats and accept.
+            let mut input: &[u8] = b"\x1b[B \nn\x1b[A \ny";
+            let selected = run_multichoice_confirmed(
+                &mut input,
+                &mut output,
+                &["cats", "dogs"],
+                &Settings::default(),
+            );
+            assert_eq!(selected.unwrap(), vec![0, 1]);
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.contains(&"You selected dogs, confirm?".blue().to_string()));
+            assert!(s.ends_with(&format!(
+                "{}\r{}\n{}\n",
+                termion::clear::CurrentLine,
+                "\r[X] cats\r".green(),
+                "\r[X] dogs\r".green()
+            )));
+
+            // Backing out of the confirmation cancels like the menu does.
+            for key in [&b"\x03"[..], b"\x1b"] {
+                let mut input = Chunks(vec![b"\n", key]);
+                let selected = run_multichoice_confirmed(
+                    &mut input,
+                    &mut Vec::new(),
+                    &["cats", "dogs"],
+                    &Settings::default(),
+                );
+                assert!(matches!(selected, Err(Error::Cancelled)));
+            }
+
+            let mut input: &[u8] = b"\n";
+            let selected = run_multichoice_confirmed(
+                &mut input,
+                &mut Vec::new(),
+                &["cats", "dogs"],
+                &Settings::default(),
+            );
+            assert!(matches!(selected, Err(Error::NonOptionalInput)));
+        }
+
+        #[test]
+        fn test_placeholder_input() {
+            let settings = Settings::default();
+            let mut output = Vec::new();
+            let mut input = Chunks(vec![b"Al", b"ice\r"]);
+            let name: Result<String, _> =
+                run_placeholder_input(&mut input, &mut output, "Name", "e.g. Bob", &settings);
+            assert_eq!(name.unwrap(), "Alice");
+            let s = std::str::from_utf8(&output).unwrap();
+            let placeholder = format!("{}{}", "e.g. Bob".dimmed(), termion::cursor::Left(8));
+            assert_eq!(s.matches(&placeholder).count(), 1);
+            assert!(s.contains(&format!("{} Al", "=>".blue())));
+
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"\r";
+            let name: Result<String, _> =
+                run_placeholder_input(&mut input, &mut output, "Name", "e.g. Bob", &settings);
+            assert!(matches!(name, Err(Error::NonOptionalInput)));
+
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"many\r";
+            let count: Result<usize, _> =
+                run_placeholder_input(&mut input, &mut output, "Count", "e.g. 3", &settings);
+            assert!(matches!(count, Err(Error::ParseError { .. })));
+        }
+
+        #[test]
+        fn test_help_input() {
+            let settings = Settings::default();
+            let help = "Five fields\ne.g. 0 9 * * 1-5";
+            let mut output = Vec::new();
+            let mut input = Chunks(vec![b"?", b"?", b"?", b"why?\r"]);
+            let answer: Result<String, _> =
+                run_help_input(&mut input, &mut output, "Schedule", Some(help), &settings);
+            assert_eq!(answer.unwrap(), "why?");
+            let s = std::str::from_utf8(&output).unwrap();
+            let shown = format!(
+                "{}\r\n{}\r\n{}{}",
+                termion::cursor::Save,
+                "Five fields".dimmed(),
+                "e.g. 0 9 * * 1-5".dimmed(),
+                termion::cursor::Restore
+            );
+            // Shown, hidden, then shown again while the answer is typed.
+            assert_eq!(s.matches(&shown).count(), 2);
+            assert!(s.contains(&"(? for help)".magenta().to_string()));
+            assert_eq!(s.matches(BRACKETED_PASTE_ON).count(), 1);
+            // The help is cleared along with the hint once the answer is in.
+            assert!(s.ends_with(&format!(
+                "{}{} {} why?\r\n{}",
+                termion::clear::AfterCursor,
+                "Schedule".blue(),
+                "=>".blue(),
+                BRACKETED_PASTE_OFF
+            )));
+
+            // A pasted `?` is part of the answer.
+            let mut input: &[u8] = b"\x1b[200~?\x1b[201~\r";
+            let answer: Result<String, _> = run_help_input(
+                &mut input,
+                &mut Vec::new(),
+                "Schedule",
+                Some(help),
+                &settings,
+            );
+            assert_eq!(answer.unwrap(), "?");
+
+            // Without help, so is a typed one.
+            let mut output = Vec::new();
+            let mut input: &[u8] = b"?\r";
+            let answer: Result<String, _> =
+                run_help_input(&mut input, &mut output, "Schedule", None::<&str>, &settings);
+            assert_eq!(answer.unwrap(), "?");
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(!s.contains("(? for help)"));
+        }
+
+        #[test]
+        fn test_password_input() {
+            let settings = Settings::default();
+            let mut output = Vec::new();
+            let mut input = Chunks(vec![b"hu", b"n\x12", b"t", b"\x12er\r"]);
+            let password = run_password_input(&mut input, &mut output, "Password", &settings);
+            assert_eq!(password.unwrap(), "hunter");
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.contains(&format!("{} **", "=>".blue())));
+            assert!(s.contains(&format!("{} hun", "=>".blue())));
+            assert!(s.contains(&format!("{} hunt", "=>".blue())));
+            assert!(!s.contains("hunte"));
+            // The secret is masked again once it's submitted.
+            assert!(s.ends_with(&format!(
+                "{} ******\r\n{}",
+                "=>".blue(),
+                BRACKETED_PASTE_OFF
+            )));
+
+            let mut input: &[u8] = b"\x12\x03";
+            let password = run_password_input(&mut input, &mut Vec::new(), "Password", &settings);
+            assert!(matches!(password, Err(Error::NonOptionalInput)));
+
+            // Paste mode is turned off again when reading fails too.
+            struct Broken;
+            impl std::io::Read for Broken {
+                fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                    Err(std::io::ErrorKind::BrokenPipe.into())
+                }
+            }
+            let mut output = Vec::new();
+            let password = run_password_input(&mut Broken, &mut output, "Password", &settings);
+            assert!(matches!(password, Err(Error::Io(_))));
+            assert!(output.ends_with(BRACKETED_PASTE_OFF.as_bytes()));
+        }
+
+        #[test]
+        fn test_masked_pattern_input() {
             assert_eq!(
-                s,
-                format!(
-                    "{}\r{} Wait 1ms{}\r{}\n",
-                    termion::clear::CurrentLine,
-                    SPINNER_SYMBOLS[0].blue(),
-                    termion::clear::CurrentLine,
-                    "❌ Wait 1ms".red()
-                )
+                fill_pattern("(___) ___", "555"),
+                ("(555) ".to_string(), "___".to_string())
+            );
+            assert_eq!(
+                fill_pattern("__-__", ""),
+                ("".to_string(), "__-__".to_string())
             );
+            assert_eq!(
+                fill_pattern("__-__", "1234"),
+                ("12-34".to_string(), "".to_string())
+            );
+
+            let settings = Settings::default();
+            let mut output = Vec::new();
+            // Letters and digits past the last slot are ignored, enter is refused until every
+            // slot is filled.
+            let mut input: &[u8] = b"55a5\n12\x7f234\n";
+            let phone =
+                run_masked_pattern_input(&mut input, &mut output, "Phone", "(___) ___", &settings);
+            assert_eq!(phone.unwrap(), "(555) 123");
+            let s = std::str::from_utf8(&output).unwrap();
+            assert!(s.contains(&format!(
+                "{} {} (555) {}",
+                "Phone".red(),
+                "=>".red(),
+                "___".dimmed()
+            )));
+            assert!(s.ends_with(&format!("{} {}\r\n", "=>".blue(), "(555) 123".green())));
+
+            let settings = Settings {
+                pattern_digits: true,
+                ..Default::default()
+            };
+            let mut input: &[u8] = b"12345\n";
+            let date =
+                run_masked_pattern_input(&mut input, &mut Vec::new(), "Date", "__/__", &settings);
+            assert_eq!(date.unwrap(), "1234");
+            let mut input: &[u8] = b"1\x03";
+            let date =
+                run_masked_pattern_input(&mut input, &mut Vec::new(), "Date", "__/__", &settings);
+            assert!(matches!(date, Err(Error::NonOptionalInput)));
+        }
+
+        #[test]
+        fn test_unit_input() {
+            let settings = Settings::default();
+            let mut output = Vec::new();
+            let mut input = Chunks(vec![b"51", b"2\r"]);
+            let memory: Result<u64, _> =
+                run_unit_input(&mut input, &mut output, "Memory", "MB", &settings);
+            assert_eq!(memory.unwrap(), 512);
+            let s = std::str::from_utf8(&output).unwrap();
+            let unit = format!(" {}{}", "MB".dimmed(), termion::cursor::Left(3));
+            assert!(s.contains(&format!("{} 51{}", "=>".blue(), unit)));
+
+            let mut input: &[u8] = b"30 s\r";
+            let timeout: Result<u32, _> =
+                run_unit_input(&mut input, &mut Vec::new(), "Timeout", "s", &settings);
+            assert_eq!(timeout.unwrap(), 30);
+
+            let mut input: &[u8] = b"\r";
+            let timeout: Result<u32, _> =
+                run_unit_input(&mut input, &mut Vec::new(), "Timeout", "s", &settings);
+            assert!(matches!(timeout, Err(Error::NonOptionalInput)));
+
+            let mut input: &[u8] = b"30m\r";
+            let timeout: Result<u32, _> =
+                run_unit_input(&mut input, &mut Vec::new(), "Timeout", "s", &settings);
+            assert!(matches!(timeout, Err(Error::ParseError { .. })));
+        }
+
+        #[test]
+        fn test_line_buffer_typing() {
+            let mut line = LineBuffer::default();
+            assert_eq!(line.feed(b"hunter"), LineFeed::Pending);
+            assert_eq!(line.feed(b"3\x7f2\x1b[D"), LineFeed::Pending);
+            assert_eq!(line.len(), 7);
+            assert_eq!(line.feed("é\x7fé".as_bytes()), LineFeed::Pending);
+            assert_eq!(line.len(), 8);
+            assert_eq!(line.feed(b"\rignored"), LineFeed::Submit);
+            assert_eq!(line.into_string().unwrap(), "hunter2é");
+        }
+
+        #[test]
+        fn test_line_buffer_paste() {
+            let mut line = LineBuffer::default();
+            assert_eq!(line.feed(b"\x1b[200~a long\nsec"), LineFeed::Pending);
+            assert_eq!(line.feed(b"ret\r\n\x1b[20"), LineFeed::Pending);
+            assert_eq!(line.len(), 12);
+            assert_eq!(line.feed(b"1~!\r"), LineFeed::Submit);
+            assert_eq!(line.into_string().unwrap(), "a longsecret!");
+
+            let mut line = LineBuffer::default();
+            assert_eq!(line.feed(b"abc\x03"), LineFeed::Cancel);
         }
     }
 
@@ -998,5 +11623,14 @@ mod tests {
             let mut cli = Clytia::new(input, output);
             assert!(cli.multichoice(vec!["cats", "dogs", "rabbits"]).is_ok())
         }
+
+        #[test]
+        fn test_password_input() {
+            let output = std::io::stdout();
+            let input = std::io::stdin();
+
+            let mut cli = Clytia::new(input, output);
+            assert!(cli.password_input("Enter a password").is_ok())
+        }
     }
 }